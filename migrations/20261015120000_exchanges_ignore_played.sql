@@ -0,0 +1 @@
+ALTER TABLE exchanges ADD COLUMN ignore_played INTEGER NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+ALTER TABLE exchanges ADD COLUMN blind INTEGER NOT NULL DEFAULT FALSE;
+ALTER TABLE exchanges ADD COLUMN public_confirmations INTEGER NOT NULL DEFAULT FALSE;
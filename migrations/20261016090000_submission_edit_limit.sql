@@ -0,0 +1,2 @@
+ALTER TABLE submissions ADD COLUMN edit_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE exchanges ADD COLUMN max_submission_edits INTEGER NOT NULL DEFAULT 0 CHECK(max_submission_edits >= 0);
@@ -0,0 +1,5 @@
+CREATE TABLE guild_settings (
+    guild INTEGER PRIMARY KEY NOT NULL,
+
+    allow_bot_submissions INTEGER NOT NULL DEFAULT FALSE
+) STRICT;
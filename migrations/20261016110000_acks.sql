@@ -0,0 +1,13 @@
+CREATE TABLE acks (
+    id INTEGER PRIMARY KEY NOT NULL,
+    exchange_id INTEGER NOT NULL,
+    member INTEGER NOT NULL,
+    acked_at TEXT NOT NULL,
+
+    CONSTRAINT fk_exchange_id
+        FOREIGN KEY (exchange_id)
+        REFERENCES exchanges(id)
+        ON DELETE CASCADE,
+
+    CONSTRAINT uniq_exchange_id_member UNIQUE (exchange_id, member)
+) STRICT;
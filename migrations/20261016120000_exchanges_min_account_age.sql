@@ -0,0 +1 @@
+ALTER TABLE exchanges ADD COLUMN min_account_age_seconds INTEGER NOT NULL DEFAULT 0 CHECK(min_account_age_seconds >= 0);
@@ -0,0 +1,13 @@
+CREATE TABLE exchange_jams (
+    id INTEGER PRIMARY KEY NOT NULL,
+    exchange_id INTEGER NOT NULL,
+    jam_type TEXT NOT NULL,
+    jam_link TEXT NOT NULL,
+
+    CONSTRAINT fk_exchange_id
+        FOREIGN KEY (exchange_id)
+        REFERENCES exchanges(id)
+        ON DELETE CASCADE,
+
+    CONSTRAINT uniq_exchange_id_jam_link UNIQUE (exchange_id, jam_link)
+) STRICT;
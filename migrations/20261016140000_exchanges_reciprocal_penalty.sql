@@ -0,0 +1 @@
+ALTER TABLE exchanges ADD COLUMN reciprocal_penalty INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+ALTER TABLE exchanges ADD COLUMN revoke_lock_seconds INTEGER NOT NULL DEFAULT 0 CHECK(revoke_lock_seconds >= 0);
@@ -0,0 +1 @@
+ALTER TABLE assignments ADD COLUMN delivered TEXT NOT NULL DEFAULT 'Pending';
@@ -0,0 +1 @@
+ALTER TABLE exchanges ADD COLUMN created_at TEXT;
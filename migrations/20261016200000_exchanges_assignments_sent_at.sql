@@ -0,0 +1 @@
+ALTER TABLE exchanges ADD COLUMN assignments_sent_at TEXT;
@@ -0,0 +1 @@
+ALTER TABLE exchanges ADD COLUMN announce_stats INTEGER NOT NULL DEFAULT FALSE;
@@ -0,0 +1 @@
+ALTER TABLE guild_settings ADD COLUMN assignment_log_channel INTEGER;
@@ -0,0 +1,12 @@
+CREATE TABLE backfill_entries (
+    id INTEGER PRIMARY KEY NOT NULL,
+    exchange_id INTEGER NOT NULL,
+    link TEXT NOT NULL,
+
+    CONSTRAINT fk_exchange_id
+        FOREIGN KEY (exchange_id)
+        REFERENCES exchanges(id)
+        ON DELETE CASCADE,
+
+    CONSTRAINT uniq_exchange_id_link UNIQUE (exchange_id, link)
+) STRICT;
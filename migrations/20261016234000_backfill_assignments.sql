@@ -0,0 +1,37 @@
+-- Assignments can point to a backfill entry instead of a submission
+CREATE TABLE assignments_new (
+    id INTEGER PRIMARY KEY NOT NULL,
+    exchange_id INTEGER NOT NULL,
+    reviewer INTEGER NOT NULL,
+    submission_id INTEGER,
+    backfill_entry_id INTEGER,
+    delivered TEXT NOT NULL DEFAULT 'Pending',
+
+    CONSTRAINT fk_exchange_id
+        FOREIGN KEY (exchange_id)
+        REFERENCES exchanges(id)
+        ON DELETE CASCADE,
+
+    CONSTRAINT fk_submission_id
+        FOREIGN KEY (submission_id)
+        REFERENCES submissions(id)
+        ON DELETE CASCADE,
+
+    CONSTRAINT fk_backfill_entry_id
+        FOREIGN KEY (backfill_entry_id)
+        REFERENCES backfill_entries(id)
+        ON DELETE CASCADE,
+
+    CONSTRAINT uniq_reviewer_submission_id UNIQUE (reviewer, submission_id),
+    CONSTRAINT uniq_reviewer_backfill_entry_id UNIQUE (reviewer, backfill_entry_id),
+
+    CONSTRAINT chk_assigned_entry
+        CHECK ((submission_id IS NULL) != (backfill_entry_id IS NULL))
+) STRICT;
+
+INSERT INTO assignments_new (id, exchange_id, reviewer, submission_id, delivered)
+SELECT id, exchange_id, reviewer, submission_id, delivered FROM assignments;
+
+DROP TABLE assignments;
+
+ALTER TABLE assignments_new RENAME TO assignments;
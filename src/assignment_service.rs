@@ -1,50 +1,175 @@
-use std::{error::Error, sync::Arc, thread};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    error::Error,
+    sync::Arc,
+    thread,
+};
 
-use indoc::formatdoc;
 use poise::serenity_prelude::UserId;
 use serenity::http::Http;
 use time::{Duration, OffsetDateTime};
-use tokio::{runtime::Handle, select, sync::Notify};
+use tokio::{
+    runtime::Handle,
+    select,
+    sync::{broadcast::error::RecvError, Notify},
+    task::JoinError,
+};
 use tracing::{debug, error, info, info_span, warn, Instrument};
 
 use crate::{
-    models::{types::UtcDateTime, Exchange, ExchangeState, Submission},
+    circuit_breaker::{CircuitBreaker, DEFAULT_COOLDOWN, DEFAULT_FAILURE_THRESHOLD},
+    message_sender::{CircuitBreakingSender, MessageSender},
+    models::{types::UtcDateTime, DeliveryStatus, Exchange, ExchangeId, ExchangeState, Submission},
     repository::{
-        ExchangeRepository, ExchangeStorageEvent, PlayedGameRepository, SubmissionRepository,
+        AckRepository, AssignmentRepository, BackfillRepository, ExchangeRepository,
+        ExchangeStorageEvent, GuildSettingsRepository, PlayedGameRepository, SubmissionRepository,
     },
     solver::dinic,
     utils::{
+        announcements::{
+            admin_summary, assignment_log, assignment_stats, assignments_dm, submissions_closed,
+            submissions_open, AssignedEntry,
+        },
         assignment_network::AssignmentNetwork,
-        formatting::{format_local, format_utc},
+        link_preview::LinkPreviewer,
     },
 };
 
 pub struct AssignmentService {
     shutdown: Arc<Notify>,
-    http: Arc<Http>,
+    sender: Arc<dyn MessageSender>,
     exchange_repository: Arc<ExchangeRepository>,
     submission_repository: Arc<SubmissionRepository>,
     played_game_repository: Arc<PlayedGameRepository>,
+    assignment_repository: Arc<AssignmentRepository>,
+    ack_repository: Arc<AckRepository>,
+    guild_settings_repository: Arc<GuildSettingsRepository>,
+    backfill_repository: Arc<BackfillRepository>,
+    /// How many ending exchanges a single assignment pass handles. The rest wait for the next
+    /// pass, which is scheduled right away.
+    max_exchanges_per_pass: usize,
+    /// Fetches entry titles for assignment DMs when enabled, otherwise only links are sent.
+    link_previewer: Option<LinkPreviewer>,
+    /// Solved networks of the exchanges accepting submissions, kept up to date while submissions
+    /// arrive so that the assignments at the deadline are quick. `None` unless enabled.
+    incremental_networks: Option<HashMap<ExchangeId, AssignmentNetwork>>,
+}
+
+/// What happened to a single exchange during an assignment pass.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExchangeAssignmentOutcome {
+    pub exchange_id: ExchangeId,
+    pub stats: AssignmentStats,
+    pub dms_delivered: usize,
+    pub dms_failed: usize,
+    /// Submitters who never confirmed with `/ready` that they can receive DMs.
+    pub unacked_submitters: Vec<UserId>,
+    pub final_state: ExchangeState,
+}
+
+/// Figures about the assignments of an exchange that don't identify anyone.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AssignmentStats {
+    /// Members who were assigned at least one entry.
+    pub reviewers: usize,
+    /// Entries submitted to the exchange.
+    pub entries: usize,
+    /// Assignments of submitted entries.
+    pub assignments: usize,
+    /// Assignments of entries from the backfill pool.
+    pub backfill_assignments: usize,
+}
+
+impl AssignmentStats {
+    fn new(
+        entries: usize,
+        assignments: &BTreeMap<UserId, Vec<Submission>>,
+        backfill_assignments: &BTreeMap<UserId, Vec<String>>,
+    ) -> Self {
+        AssignmentStats {
+            reviewers: assignments
+                .iter()
+                .filter(|(user, assigned)| {
+                    !assigned.is_empty() || backfill_assignments.contains_key(user)
+                })
+                .count(),
+            entries,
+            assignments: assignments.values().map(Vec::len).sum(),
+            backfill_assignments: backfill_assignments.values().map(Vec::len).sum(),
+        }
+    }
+
+    pub fn average_reviews_per_entry(&self) -> f64 {
+        if self.entries == 0 {
+            0.0
+        } else {
+            self.assignments as f64 / self.entries as f64
+        }
+    }
+}
+
+/// The result of one assignment pass.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssignmentPass {
+    pub outcomes: Vec<ExchangeAssignmentOutcome>,
+    /// Whether some ending exchanges were left for the next pass.
+    pub has_more: bool,
 }
 
+impl ExchangeAssignmentOutcome {
+    fn without_assignments(exchange_id: ExchangeId, final_state: ExchangeState) -> Self {
+        ExchangeAssignmentOutcome {
+            exchange_id,
+            stats: AssignmentStats::default(),
+            dms_delivered: 0,
+            dms_failed: 0,
+            unacked_submitters: Vec::new(),
+            final_state,
+        }
+    }
+}
+
+pub const DEFAULT_MAX_EXCHANGES_PER_PASS: usize = 5;
+
 const DEFAULT_SLEEP_DURATION: Duration = Duration::seconds(60 * 60 /* One hour */);
 const EXCHANGE_START_THRESHOLD: Duration = Duration::seconds(60 * 60 /* One hour */);
 const EXCHANGE_END_THRESHOLD: Duration = Duration::seconds(60 * 60 /* One hour */);
+const INCREMENTAL_REFRESH_INTERVAL: std::time::Duration =
+    std::time::Duration::from_secs(5 * 60 /* Five minutes */);
 
 impl AssignmentService {
+    #[allow(clippy::too_many_arguments)]
     pub fn create_and_start(
         shutdown: Arc<Notify>,
         http: Arc<Http>,
         exchange_repository: Arc<ExchangeRepository>,
         submission_repository: Arc<SubmissionRepository>,
         played_game_repository: Arc<PlayedGameRepository>,
+        assignment_repository: Arc<AssignmentRepository>,
+        ack_repository: Arc<AckRepository>,
+        guild_settings_repository: Arc<GuildSettingsRepository>,
+        backfill_repository: Arc<BackfillRepository>,
+        max_exchanges_per_pass: usize,
+        link_previewer: Option<LinkPreviewer>,
+        incremental_assignments: bool,
     ) {
         let service = AssignmentService {
+            sender: Arc::new(CircuitBreakingSender::new(
+                http,
+                CircuitBreaker::new(DEFAULT_FAILURE_THRESHOLD, DEFAULT_COOLDOWN),
+                shutdown.clone(),
+            )),
             shutdown,
-            http,
             exchange_repository,
             submission_repository,
             played_game_repository,
+            assignment_repository,
+            ack_repository,
+            guild_settings_repository,
+            backfill_repository,
+            max_exchanges_per_pass,
+            link_previewer,
+            incremental_networks: incremental_assignments.then(HashMap::new),
         };
 
         service.start();
@@ -59,6 +184,7 @@ impl AssignmentService {
 
                 let mut exchange_events = self.exchange_repository.subscribe();
                 let shutdown_notify = self.shutdown.clone();
+                let mut incremental_refresh = tokio::time::interval(INCREMENTAL_REFRESH_INTERVAL);
 
                 loop {
                     let sleep_duration = {
@@ -82,20 +208,48 @@ impl AssignmentService {
                             break
                         }
 
+                        _ = incremental_refresh.tick(), if self.incremental_networks.is_some() => {
+                            select! {
+                                _ = shutdown_notify.notified() => {
+                                    break
+                                }
+
+                                result = self.refresh_incremental_networks() => {
+                                    if let Err(err) = result {
+                                        error!("Could not refresh the incremental assignment networks: {err}");
+                                    }
+                                }
+                            }
+                        }
+
                         _ = tokio::time::sleep(sleep_duration) => {
                             if let Err(err) = self.announce_exchange_submissions_open().await {
                                 error!("Could not announce exchange submissions open: {err}");
                             }
 
-                            if let Err(err) = self.perform_assignments().await {
-                                error!("Could not perform assignments: {err}");
-                            }
-
-                            next_assignments_time = match self.reschedule().await {
-                                Ok(time) => time,
+                            let has_more = match self.perform_assignments().await {
+                                Ok(pass) => {
+                                    for outcome in pass.outcomes {
+                                        info!("Assignment outcome: {outcome:?}");
+                                    }
+                                    pass.has_more
+                                }
                                 Err(err) => {
-                                    error!("Could not reschedule after performing assignments: {err}");
-                                    None
+                                    error!("Could not perform assignments: {err}");
+                                    false
+                                }
+                            };
+
+                            next_assignments_time = if has_more {
+                                info!("More exchanges have ended, continuing with the next pass");
+                                Some(OffsetDateTime::now_utc())
+                            } else {
+                                match self.reschedule().await {
+                                    Ok(time) => time,
+                                    Err(err) => {
+                                        error!("Could not reschedule after performing assignments: {err}");
+                                        None
+                                    }
                                 }
                             };
                         }
@@ -111,7 +265,23 @@ impl AssignmentService {
                                         }
                                     };
                                 },
-                                Err(err) => error!("Error while receiving an exchange event: {err:?}"),
+                                Err(RecvError::Lagged(skipped)) => {
+                                    // Every event means the exchanges changed, so the missed ones
+                                    // are covered by a single reschedule
+                                    warn!("Missed {skipped} exchange events, rescheduling");
+
+                                    next_assignments_time = match self.reschedule().await {
+                                        Ok(time) => time,
+                                        Err(err) => {
+                                            error!("Could not reschedule after missing exchange events: {err}");
+                                            None
+                                        }
+                                    };
+                                },
+                                Err(RecvError::Closed) => {
+                                    error!("Exchange events channel closed, stopping the assignment service");
+                                    break
+                                },
                             }
                         }
                     }
@@ -149,23 +319,9 @@ impl AssignmentService {
                     );
                 }
             } else {
-                {
-                    let message = formatdoc! {
-                        r#"
-                            # Review exchange {name} starts now!
-
-                            **Submit your jam entry using the `/submit <entry link>` command.**
-
-                            The exchange ends on {end_local} your time or {end_utc} UTC. You should submit your entry before this deadline.
-
-                            After the deadline, you will receive a list of entries to play and rate in your DMs.
-                        "#,
-                        name = exchange.display_name,
-                        end_local = format_local(exchange.submissions_end),
-                        end_utc = format_utc(exchange.submissions_end),
-                    };
-                    exchange.channel.say(&self.http, message).await?;
-                };
+                self.sender
+                    .send_to_channel(exchange.channel, submissions_open(&exchange))
+                    .await?;
 
                 if let Err(err) = self
                     .exchange_repository
@@ -184,15 +340,20 @@ impl AssignmentService {
     }
 
     #[tracing::instrument(skip(self))]
-    async fn perform_assignments(&mut self) -> Result<(), Box<dyn Error>> {
+    async fn perform_assignments(&mut self) -> Result<AssignmentPass, Box<dyn Error>> {
         info!("Performing assignments");
 
         let now = OffsetDateTime::now_utc();
-        let ending_exchanges = self
+        let mut ending_exchanges = self
             .exchange_repository
             .get_ending_exchanges(UtcDateTime::from(now))
             .await?;
 
+        let has_more = ending_exchanges.len() > self.max_exchanges_per_pass;
+        ending_exchanges.truncate(self.max_exchanges_per_pass);
+
+        let mut outcomes = Vec::with_capacity(ending_exchanges.len());
+
         for exchange in ending_exchanges {
             let late_period = now - OffsetDateTime::from(exchange.submissions_end);
 
@@ -211,56 +372,111 @@ impl AssignmentService {
                         exchange.id, err
                     );
                 }
+
+                let outcome = ExchangeAssignmentOutcome::without_assignments(
+                    exchange.id,
+                    ExchangeState::MissedByBot,
+                );
+                self.log_outcome(&exchange, &outcome).await;
+                outcomes.push(outcome);
             } else {
-                if let Err(err) = self.perform_assignments_for_exchange(&exchange).await {
-                    error!("Could not perform assignments for exchange {exchange:?}: {err}");
-                    if let Err(err) = self
-                        .exchange_repository
-                        .update_exchange_state(exchange.id, ExchangeState::AssignmentError)
-                        .await
-                    {
-                        warn!(
-                            "Could not set exchange {:?} state to AssignmentError: {}",
-                            exchange.id, err
+                let outcome = match self.perform_assignments_for_exchange(&exchange).await {
+                    Ok(outcome) => outcome,
+                    Err(err) => {
+                        error!("Could not perform assignments for exchange {exchange:?}: {err}");
+                        if let Err(err) = self
+                            .exchange_repository
+                            .update_exchange_state(exchange.id, ExchangeState::AssignmentError)
+                            .await
+                        {
+                            warn!(
+                                "Could not set exchange {:?} state to AssignmentError: {}",
+                                exchange.id, err
+                            );
+                        }
+
+                        let outcome = ExchangeAssignmentOutcome::without_assignments(
+                            exchange.id,
+                            ExchangeState::AssignmentError,
                         );
+                        self.log_outcome(&exchange, &outcome).await;
+                        outcomes.push(outcome);
+                        continue;
                     }
-                    continue;
-                }
+                };
 
+                if let Err(err) = self
+                    .sender
+                    .send_to_channel(exchange.channel, submissions_closed(&exchange))
+                    .await
                 {
-                    let message = formatdoc! {
-                        r#"
-                            # Review exchange {name} has just ended!
-
-                            **You should have received your assignments to play and rate in the DMs.**
+                    warn!("Could not announce that submissions closed: {err}");
+                }
 
-                            If that didn't happen, please contact the moderators.
-                        "#,
-                        name = exchange.display_name,
-                    };
-                    exchange.channel.say(&self.http, message).await?;
-                };
+                if exchange.announce_stats {
+                    if let Err(err) = self
+                        .sender
+                        .send_to_channel(
+                            exchange.channel,
+                            assignment_stats(&exchange, &outcome.stats),
+                        )
+                        .await
+                    {
+                        warn!("Could not post the assignment statistics: {err}");
+                    }
+                }
 
                 if let Err(err) = self
                     .exchange_repository
-                    .update_exchange_state(exchange.id, ExchangeState::AssignmentsSent)
+                    .mark_assignments_sent(exchange.id, UtcDateTime::from(now))
                     .await
                 {
                     warn!(
-                        "Could not set exchange {:?} state to AcceptingSubmissions: {}",
+                        "Could not set exchange {:?} state to AssignmentsSent: {}",
                         exchange.id, err
                     );
                 }
+
+                self.log_outcome(&exchange, &outcome).await;
+                outcomes.push(outcome);
             }
         }
 
-        Ok(())
+        Ok(AssignmentPass { outcomes, has_more })
+    }
+
+    /// Posts the outcome to the guild's assignment log, if it has one.
+    async fn log_outcome(&self, exchange: &Exchange, outcome: &ExchangeAssignmentOutcome) {
+        let settings = match self
+            .guild_settings_repository
+            .get_settings(exchange.guild)
+            .await
+        {
+            Ok(settings) => settings,
+            Err(err) => {
+                warn!(
+                    "Could not get the settings of guild {}: {err}",
+                    exchange.guild
+                );
+                return;
+            }
+        };
+
+        if let Some(channel) = settings.assignment_log_channel {
+            if let Err(err) = self
+                .sender
+                .send_to_channel(channel, assignment_log(exchange, outcome))
+                .await
+            {
+                warn!("Could not log the assignments in channel {channel}: {err}");
+            }
+        }
     }
 
     async fn perform_assignments_for_exchange(
-        &self,
+        &mut self,
         exchange: &Exchange,
-    ) -> Result<(), Box<dyn Error>> {
+    ) -> Result<ExchangeAssignmentOutcome, Box<dyn Error>> {
         let submissions = self
             .submission_repository
             .get_submissions_for_exchange(exchange.id)
@@ -269,25 +485,134 @@ impl AssignmentService {
             .played_game_repository
             .get_played_games_for_exchange(exchange.id)
             .await?;
+        let backfill = self
+            .backfill_repository
+            .get_backfill_entries(exchange.id)
+            .await?;
 
-        let mut network = AssignmentNetwork::build(exchange, submissions, &played_games);
+        let unacked_submitters = match self
+            .ack_repository
+            .get_unacked_submitters(exchange.id)
+            .await
+        {
+            Ok(unacked) => {
+                if !unacked.is_empty() {
+                    warn!(
+                        "Submitters of exchange {} never confirmed they can receive DMs, assignments may not reach them: {unacked:?}",
+                        exchange.slug
+                    );
+                }
+                unacked
+            }
+            Err(err) => {
+                warn!("Could not get unacked submitters: {err}");
+                Vec::new()
+            }
+        };
 
-        dinic::solve(&mut network.network);
+        let entries = submissions.len();
+        let cached_network = self
+            .incremental_networks
+            .as_mut()
+            .and_then(|networks| networks.remove(&exchange.id));
+        let network = match cached_network {
+            Some(mut network) => {
+                if !network.sync(exchange, submissions, &played_games, &backfill) {
+                    info!(
+                        "Submissions of exchange {} changed since the last refresh, rebuilt the assignment network",
+                        exchange.slug
+                    );
+                }
+                network
+            }
+            None => {
+                let mut network = AssignmentNetwork::build(exchange, submissions, &played_games);
+                network.add_backfill(&backfill);
+                network
+            }
+        };
+
+        let network = solve_network(network, true).await?;
 
         debug!("Solved network: {network:?}");
+        debug!("Min cut: {:?}", network.network.min_cut());
 
         let assignments = network.get_assignments();
+        let mut backfill_assignments = network.get_backfill_assignments();
+
+        {
+            let records = assignments
+                .iter()
+                .flat_map(|(&user, assignments)| {
+                    assignments
+                        .iter()
+                        .map(move |assignment| (user, assignment.id))
+                })
+                .collect::<Vec<_>>();
+
+            self.assignment_repository
+                .add_assignments(exchange.id, &records)
+                .await?;
+
+            let backfill_records = backfill_assignments
+                .iter()
+                .flat_map(|(&user, links)| links.iter().map(move |link| (user, link.clone())))
+                .collect::<Vec<_>>();
+
+            self.assignment_repository
+                .add_backfill_assignments(exchange.id, &backfill_records)
+                .await?;
+        }
+
+        let mut outcome = ExchangeAssignmentOutcome {
+            stats: AssignmentStats::new(entries, &assignments, &backfill_assignments),
+            unacked_submitters,
+            ..ExchangeAssignmentOutcome::without_assignments(
+                exchange.id,
+                ExchangeState::AssignmentsSent,
+            )
+        };
+
+        if let Some(previewer) = &self.link_previewer {
+            let links = assignments
+                .values()
+                .flatten()
+                .map(|assignment| assignment.link.as_str())
+                .chain(backfill_assignments.values().flatten().map(String::as_str))
+                .collect::<BTreeSet<_>>();
+            previewer.prefetch(links).await;
+        }
 
         for (user, assignments) in assignments {
+            let backfill = backfill_assignments.remove(&user).unwrap_or_default();
+            let sent = self
+                .send_user_assignments(exchange, user, &assignments, &backfill)
+                .await;
+
+            let status = if sent.is_ok() {
+                DeliveryStatus::Delivered
+            } else {
+                DeliveryStatus::Failed
+            };
             if let Err(err) = self
-                .send_user_assignments(exchange, user, &assignments)
+                .assignment_repository
+                .set_delivery_status(exchange.id, user, status)
                 .await
             {
+                warn!("Could not record the assignment delivery status for user {user}: {err}");
+            }
+
+            if let Err(err) = sent {
                 warn!("Could not send assignments to user {user}: {err}");
+                outcome.dms_failed += 1;
             } else {
-                for assignment in &assignments {
-                    let link = &assignment.link;
+                outcome.dms_delivered += 1;
 
+                let links = assignments
+                    .iter()
+                    .map(|assignment| &assignment.link)
+                    .chain(&backfill);
+                for link in links {
                     if let Err(err) = self.played_game_repository.submit(user, link, false).await {
                         warn!(
                             "Could not register an assignment {link} as played for user {user}: {err}"
@@ -297,7 +622,18 @@ impl AssignmentService {
             }
         }
 
-        Ok(())
+        if let Some(admin) = exchange.created_by {
+            // Admins can have DMs closed too, which must not fail the whole exchange
+            if let Err(err) = self
+                .sender
+                .send_dm(admin, admin_summary(exchange, &outcome))
+                .await
+            {
+                warn!("Could not send the assignment summary to admin {admin}: {err}");
+            }
+        }
+
+        Ok(outcome)
     }
 
     async fn send_user_assignments(
@@ -305,43 +641,78 @@ impl AssignmentService {
         exchange: &Exchange,
         user: UserId,
         assignments: &[Submission],
+        backfill: &[String],
     ) -> Result<(), Box<dyn Error>> {
-        let message = if assignments.is_empty() {
-            formatdoc! {
-                r#"
-                    # Could not assign you any entries for {exchange_name}
+        let links = assignments
+            .iter()
+            .map(|assignment| &assignment.link)
+            .chain(backfill);
 
-                    This probably means you have already played all entries for this exchange, or the algorithm could not find a solution.
+        let mut entries = Vec::with_capacity(assignments.len() + backfill.len());
+        for link in links {
+            let title = match &self.link_previewer {
+                Some(previewer) => previewer.title(link).await,
+                None => None,
+            };
 
-                    No actions are needed on your side.
-                "#,
-                exchange_name = exchange.display_name,
-            }
-        } else {
-            let assignments_str = assignments
-                .iter()
-                .map(|assignment| format!("- {}", assignment.link))
-                .collect::<Vec<String>>()
-                .join("\n");
-
-            formatdoc! {
-                r#"
-                   # Here are your assignments
+            entries.push(AssignedEntry {
+                link: link.clone(),
+                title,
+            });
+        }
 
-                   {assignments_str}
+        for message in assignments_dm(exchange, &entries) {
+            self.sender.send_dm(user, message).await?;
+        }
 
-                   You are supposed to play and rate the assignments before the jam ends.
+        Ok(())
+    }
 
-                   If you decide to rate some entries outside of the assignments, you can use the `/played <entry link>` command.
-                   This will make sure these entries won't be assigned to you in the future.
-                "#,
-                assignments_str = assignments_str,
-            }
+    /// Adds the latest submissions to the networks of the exchanges accepting submissions and
+    /// solves them again, starting from the previous minimum cost flow.
+    #[tracing::instrument(skip(self))]
+    async fn refresh_incremental_networks(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(networks) = &mut self.incremental_networks else {
+            return Ok(());
         };
 
-        let channel = user.create_dm_channel(&self.http).await?;
+        let exchanges = self.exchange_repository.get_accepting_exchanges().await?;
+        networks.retain(|id, _| exchanges.iter().any(|exchange| exchange.id == *id));
 
-        channel.say(&self.http, message).await?;
+        for exchange in exchanges {
+            let submissions = self
+                .submission_repository
+                .get_submissions_for_exchange(exchange.id)
+                .await?;
+            let played_games = self
+                .played_game_repository
+                .get_played_games_for_exchange(exchange.id)
+                .await?;
+            let backfill = self
+                .backfill_repository
+                .get_backfill_entries(exchange.id)
+                .await?;
+
+            let network = match networks.remove(&exchange.id) {
+                Some(mut network) => {
+                    if !network.sync(&exchange, submissions, &played_games, &backfill) {
+                        debug!(
+                            "Rebuilt the assignment network of exchange {}",
+                            exchange.slug
+                        );
+                    }
+                    network
+                }
+                None => {
+                    let mut network =
+                        AssignmentNetwork::build(&exchange, submissions, &played_games);
+                    network.add_backfill(&backfill);
+                    network
+                }
+            };
+
+            networks.insert(exchange.id, solve_network(network, false).await?);
+        }
 
         Ok(())
     }
@@ -361,3 +732,566 @@ impl AssignmentService {
         }
     }
 }
+
+/// Solves the network to a minimum cost flow on the blocking thread pool, so that solving a large
+/// exchange doesn't hold up the service loop. The final assignments are always canonicalized, the
+/// networks refreshed while submissions arrive don't need to be.
+async fn solve_network(
+    mut network: AssignmentNetwork,
+    canonicalize: bool,
+) -> Result<AssignmentNetwork, JoinError> {
+    tokio::task::spawn_blocking(move || {
+        dinic::solve(&mut network.network);
+        network.minimize_cost();
+        if canonicalize {
+            network.canonicalize_assignment();
+        }
+        network
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        num::NonZeroU8,
+        sync::{Arc, Mutex},
+    };
+
+    use async_trait::async_trait;
+    use serenity::all::{ChannelId, GuildId, UserId};
+    use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+    use time::{Duration, OffsetDateTime};
+    use tokio::sync::Notify;
+
+    use crate::{
+        message_sender::MessageSender,
+        models::{
+            test_new_exchange, types::UtcDateTime, DeliveryStatus, Exchange, ExchangeState,
+            NewExchange, NewSubmission,
+        },
+        repository::{
+            AckRepository, AssignmentRepository, BackfillRepository, ExchangeRepository,
+            GuildSettingsRepository, PlayedGameRepository, SubmissionRepository,
+        },
+        solver::dinic,
+    };
+
+    use super::{AssignmentPass, AssignmentService, AssignmentStats, ExchangeAssignmentOutcome};
+
+    #[derive(Default)]
+    struct MockSender {
+        unreachable_users: HashSet<UserId>,
+        channel_messages: Mutex<Vec<(ChannelId, String)>>,
+        dms: Mutex<Vec<(UserId, String)>>,
+    }
+
+    #[async_trait]
+    impl MessageSender for MockSender {
+        async fn send_to_channel(
+            &self,
+            channel: ChannelId,
+            message: String,
+        ) -> serenity::Result<()> {
+            self.channel_messages
+                .lock()
+                .unwrap()
+                .push((channel, message));
+            Ok(())
+        }
+
+        async fn send_dm(&self, user: UserId, message: String) -> serenity::Result<()> {
+            if self.unreachable_users.contains(&user) {
+                return Err(serenity::Error::Other("Cannot send messages to this user"));
+            }
+
+            self.dms.lock().unwrap().push((user, message));
+            Ok(())
+        }
+    }
+
+    async fn setup_database() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn assignment_outcome() {
+        let pool = setup_database().await;
+        let exchange_repository = Arc::new(ExchangeRepository::new(pool.clone()));
+        let submission_repository = Arc::new(SubmissionRepository::new(pool.clone()));
+
+        let now = OffsetDateTime::now_utc();
+        let exchange = exchange_repository
+            .create_exchange(NewExchange {
+                guild: GuildId::new(1),
+                channel: ChannelId::new(2),
+                submissions_start: UtcDateTime::from(now - Duration::hours(2)),
+                submissions_end: UtcDateTime::from(now - Duration::minutes(1)),
+                games_per_member: NonZeroU8::new(1).unwrap(),
+                ..test_new_exchange()
+            })
+            .await
+            .unwrap();
+        exchange_repository
+            .update_exchange_state(exchange.id, ExchangeState::AcceptingSubmissions)
+            .await
+            .unwrap();
+
+        for i in 1..=3 {
+            submission_repository
+                .add_or_update_submission(&NewSubmission {
+                    exchange_id: exchange.id,
+                    link: format!("https://itch.io/jam/example-jam/rate/00000{i}"),
+                    submitter: UserId::new(i),
+                    submitted_at: UtcDateTime::from(now - Duration::hours(1)),
+                })
+                .await
+                .unwrap();
+        }
+
+        let ack_repository = Arc::new(AckRepository::new(pool.clone()));
+        for i in 1..=2 {
+            ack_repository
+                .add_ack(exchange.id, UserId::new(i), UtcDateTime::from(now))
+                .await
+                .unwrap();
+        }
+
+        let sender = Arc::new(MockSender {
+            unreachable_users: HashSet::from([UserId::new(3)]),
+            ..Default::default()
+        });
+
+        let assignment_repository = Arc::new(AssignmentRepository::new(pool.clone()));
+
+        let mut service = AssignmentService {
+            shutdown: Arc::new(Notify::new()),
+            sender: sender.clone(),
+            exchange_repository: exchange_repository.clone(),
+            submission_repository,
+            played_game_repository: Arc::new(PlayedGameRepository::new(pool.clone())),
+            assignment_repository: assignment_repository.clone(),
+            ack_repository,
+            guild_settings_repository: Arc::new(GuildSettingsRepository::new(pool.clone())),
+            backfill_repository: Arc::new(BackfillRepository::new(pool.clone())),
+            max_exchanges_per_pass: 5,
+            link_previewer: None,
+            incremental_networks: None,
+        };
+
+        let pass = service.perform_assignments().await.unwrap();
+
+        assert_eq!(
+            pass,
+            AssignmentPass {
+                outcomes: vec![ExchangeAssignmentOutcome {
+                    exchange_id: exchange.id,
+                    stats: AssignmentStats {
+                        reviewers: 3,
+                        entries: 3,
+                        assignments: 3,
+                        backfill_assignments: 0,
+                    },
+                    dms_delivered: 2,
+                    dms_failed: 1,
+                    unacked_submitters: vec![UserId::new(3)],
+                    final_state: ExchangeState::AssignmentsSent,
+                }],
+                has_more: false,
+            }
+        );
+
+        assert_eq!(sender.dms.lock().unwrap().len(), 2);
+        assert_eq!(sender.channel_messages.lock().unwrap().len(), 1);
+
+        let assignments = assignment_repository
+            .get_assignments_for_exchange(exchange.id)
+            .await
+            .unwrap();
+        let count_status = |status| {
+            assignments
+                .iter()
+                .filter(|assignment| assignment.delivered == status)
+                .count()
+        };
+        assert_eq!(count_status(DeliveryStatus::Delivered), 2);
+        assert_eq!(count_status(DeliveryStatus::Failed), 1);
+
+        let exchange = exchange_repository
+            .get_exchange_by_slug(GuildId::new(1), "Test")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(exchange.state, ExchangeState::AssignmentsSent);
+    }
+
+    #[tokio::test]
+    async fn backfill_assignments_are_stored() {
+        let pool = setup_database().await;
+        let exchange_repository = Arc::new(ExchangeRepository::new(pool.clone()));
+        let submission_repository = Arc::new(SubmissionRepository::new(pool.clone()));
+        let backfill_repository = Arc::new(BackfillRepository::new(pool.clone()));
+
+        let now = OffsetDateTime::now_utc();
+        let exchange = exchange_repository
+            .create_exchange(NewExchange {
+                guild: GuildId::new(1),
+                channel: ChannelId::new(2),
+                submissions_start: UtcDateTime::from(now - Duration::hours(2)),
+                submissions_end: UtcDateTime::from(now - Duration::minutes(1)),
+                games_per_member: NonZeroU8::new(2).unwrap(),
+                ..test_new_exchange()
+            })
+            .await
+            .unwrap();
+        exchange_repository
+            .update_exchange_state(exchange.id, ExchangeState::AcceptingSubmissions)
+            .await
+            .unwrap();
+
+        for i in 1..=2 {
+            submission_repository
+                .add_or_update_submission(&NewSubmission {
+                    exchange_id: exchange.id,
+                    link: format!("https://itch.io/jam/example-jam/rate/00000{i}"),
+                    submitter: UserId::new(i),
+                    submitted_at: UtcDateTime::from(now - Duration::hours(1)),
+                })
+                .await
+                .unwrap();
+        }
+        backfill_repository
+            .add_backfill_entries(
+                exchange.id,
+                &["https://itch.io/jam/example-jam/rate/000009".to_string()],
+            )
+            .await
+            .unwrap();
+
+        let assignment_repository = Arc::new(AssignmentRepository::new(pool.clone()));
+
+        let mut service = AssignmentService {
+            shutdown: Arc::new(Notify::new()),
+            sender: Arc::new(MockSender::default()),
+            exchange_repository,
+            submission_repository,
+            played_game_repository: Arc::new(PlayedGameRepository::new(pool.clone())),
+            assignment_repository: assignment_repository.clone(),
+            ack_repository: Arc::new(AckRepository::new(pool.clone())),
+            guild_settings_repository: Arc::new(GuildSettingsRepository::new(pool.clone())),
+            backfill_repository,
+            max_exchanges_per_pass: 5,
+            link_previewer: None,
+            incremental_networks: None,
+        };
+
+        let pass = service.perform_assignments().await.unwrap();
+
+        assert_eq!(
+            pass.outcomes[0].stats,
+            AssignmentStats {
+                reviewers: 2,
+                entries: 2,
+                assignments: 2,
+                backfill_assignments: 2,
+            }
+        );
+
+        let backfill = assignment_repository
+            .get_assignments_for_exchange(exchange.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|assignment| assignment.submission_id.is_none())
+            .map(|assignment| (assignment.reviewer, assignment.link))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            backfill,
+            vec![
+                (
+                    UserId::new(1),
+                    "https://itch.io/jam/example-jam/rate/000009".to_string()
+                ),
+                (
+                    UserId::new(2),
+                    "https://itch.io/jam/example-jam/rate/000009".to_string()
+                ),
+            ]
+        );
+    }
+
+    async fn create_ended_exchange(
+        exchange_repository: &ExchangeRepository,
+        slug: &str,
+        ended_ago: Duration,
+        created_by: Option<UserId>,
+    ) -> Exchange {
+        let now = OffsetDateTime::now_utc();
+        let exchange = exchange_repository
+            .create_exchange(NewExchange {
+                guild: GuildId::new(1),
+                channel: ChannelId::new(2),
+                slug: slug.to_string(),
+                display_name: slug.to_string(),
+                submissions_start: UtcDateTime::from(now - Duration::hours(2)),
+                submissions_end: UtcDateTime::from(now - ended_ago),
+                games_per_member: NonZeroU8::new(1).unwrap(),
+                created_by,
+                ..test_new_exchange()
+            })
+            .await
+            .unwrap();
+        exchange_repository
+            .update_exchange_state(exchange.id, ExchangeState::AcceptingSubmissions)
+            .await
+            .unwrap();
+
+        exchange
+    }
+
+    #[tokio::test]
+    async fn capped_assignment_pass() {
+        let pool = setup_database().await;
+        let exchange_repository = Arc::new(ExchangeRepository::new(pool.clone()));
+
+        let first =
+            create_ended_exchange(&exchange_repository, "First", Duration::minutes(2), None).await;
+        let second =
+            create_ended_exchange(&exchange_repository, "Second", Duration::minutes(1), None).await;
+
+        let mut service = AssignmentService {
+            shutdown: Arc::new(Notify::new()),
+            sender: Arc::new(MockSender::default()),
+            exchange_repository: exchange_repository.clone(),
+            submission_repository: Arc::new(SubmissionRepository::new(pool.clone())),
+            played_game_repository: Arc::new(PlayedGameRepository::new(pool.clone())),
+            assignment_repository: Arc::new(AssignmentRepository::new(pool.clone())),
+            ack_repository: Arc::new(AckRepository::new(pool.clone())),
+            guild_settings_repository: Arc::new(GuildSettingsRepository::new(pool.clone())),
+            backfill_repository: Arc::new(BackfillRepository::new(pool.clone())),
+            max_exchanges_per_pass: 1,
+            link_previewer: None,
+            incremental_networks: None,
+        };
+
+        let pass = service.perform_assignments().await.unwrap();
+        assert_eq!(
+            pass.outcomes
+                .iter()
+                .map(|o| o.exchange_id)
+                .collect::<Vec<_>>(),
+            vec![first.id]
+        );
+        assert!(pass.has_more);
+
+        let pass = service.perform_assignments().await.unwrap();
+        assert_eq!(
+            pass.outcomes
+                .iter()
+                .map(|o| o.exchange_id)
+                .collect::<Vec<_>>(),
+            vec![second.id]
+        );
+        assert!(!pass.has_more);
+    }
+
+    fn service_with_sender(pool: &SqlitePool, sender: Arc<MockSender>) -> AssignmentService {
+        AssignmentService {
+            shutdown: Arc::new(Notify::new()),
+            sender,
+            exchange_repository: Arc::new(ExchangeRepository::new(pool.clone())),
+            submission_repository: Arc::new(SubmissionRepository::new(pool.clone())),
+            played_game_repository: Arc::new(PlayedGameRepository::new(pool.clone())),
+            assignment_repository: Arc::new(AssignmentRepository::new(pool.clone())),
+            ack_repository: Arc::new(AckRepository::new(pool.clone())),
+            guild_settings_repository: Arc::new(GuildSettingsRepository::new(pool.clone())),
+            backfill_repository: Arc::new(BackfillRepository::new(pool.clone())),
+            max_exchanges_per_pass: 5,
+            link_previewer: None,
+            incremental_networks: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn incremental_assignments() {
+        let pool = setup_database().await;
+        let exchange_repository = ExchangeRepository::new(pool.clone());
+        let submission_repository = SubmissionRepository::new(pool.clone());
+
+        let exchange =
+            create_ended_exchange(&exchange_repository, "Test", Duration::minutes(1), None).await;
+        let submit = |i: u64| NewSubmission {
+            exchange_id: exchange.id,
+            link: format!("https://itch.io/jam/example-jam/rate/00000{i}"),
+            submitter: UserId::new(i),
+            submitted_at: UtcDateTime::from(OffsetDateTime::now_utc()),
+        };
+
+        for i in 1..=2 {
+            submission_repository
+                .add_or_update_submission(&submit(i))
+                .await
+                .unwrap();
+        }
+
+        let sender = Arc::new(MockSender::default());
+        let mut service = AssignmentService {
+            incremental_networks: Some(HashMap::new()),
+            ..service_with_sender(&pool, sender.clone())
+        };
+
+        service.refresh_incremental_networks().await.unwrap();
+        assert!(service
+            .incremental_networks
+            .as_ref()
+            .unwrap()
+            .contains_key(&exchange.id));
+
+        // Submitted after the last refresh
+        submission_repository
+            .add_or_update_submission(&submit(3))
+            .await
+            .unwrap();
+
+        let pass = service.perform_assignments().await.unwrap();
+
+        assert_eq!(
+            pass.outcomes[0].stats,
+            AssignmentStats {
+                reviewers: 3,
+                entries: 3,
+                assignments: 3,
+                backfill_assignments: 0,
+            }
+        );
+        assert_eq!(sender.dms.lock().unwrap().len(), 3);
+        assert!(service.incremental_networks.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn refreshed_network_is_minimized() {
+        let pool = setup_database().await;
+        let exchange_repository = ExchangeRepository::new(pool.clone());
+        let submission_repository = SubmissionRepository::new(pool.clone());
+
+        let exchange =
+            create_ended_exchange(&exchange_repository, "Test", Duration::minutes(1), None).await;
+        for i in 1..=5 {
+            submission_repository
+                .add_or_update_submission(&NewSubmission {
+                    exchange_id: exchange.id,
+                    link: format!("https://itch.io/jam/example-jam/rate/00000{i}"),
+                    submitter: UserId::new(i),
+                    submitted_at: UtcDateTime::from(OffsetDateTime::now_utc()),
+                })
+                .await
+                .unwrap();
+        }
+
+        let mut service = AssignmentService {
+            incremental_networks: Some(HashMap::new()),
+            ..service_with_sender(&pool, Arc::new(MockSender::default()))
+        };
+        service.refresh_incremental_networks().await.unwrap();
+
+        // Nothing changed since the refresh, so the deadline pass has nothing left to solve
+        let network = service
+            .incremental_networks
+            .as_mut()
+            .unwrap()
+            .get_mut(&exchange.id)
+            .unwrap();
+        let flows = network.network.flows().clone();
+        dinic::solve(&mut network.network);
+        network.minimize_cost();
+        assert_eq!(network.network.flows(), &flows);
+    }
+
+    #[tokio::test]
+    async fn admin_receives_summary() {
+        let pool = setup_database().await;
+        let exchange_repository = ExchangeRepository::new(pool.clone());
+        let admin = UserId::new(42);
+
+        create_ended_exchange(
+            &exchange_repository,
+            "Test",
+            Duration::minutes(1),
+            Some(admin),
+        )
+        .await;
+
+        let sender = Arc::new(MockSender::default());
+        let mut service = service_with_sender(&pool, sender.clone());
+
+        service.perform_assignments().await.unwrap();
+
+        let dms = sender.dms.lock().unwrap();
+        assert_eq!(dms.len(), 1);
+        assert_eq!(dms[0].0, admin);
+        assert!(dms[0].1.contains("# Assignments for Test were sent"));
+    }
+
+    #[tokio::test]
+    async fn outcome_logged_in_guild_channel() {
+        let pool = setup_database().await;
+        let exchange_repository = ExchangeRepository::new(pool.clone());
+        let exchange =
+            create_ended_exchange(&exchange_repository, "Test", Duration::minutes(1), None).await;
+        GuildSettingsRepository::new(pool.clone())
+            .set_assignment_log_channel(exchange.guild, Some(ChannelId::new(99)))
+            .await
+            .unwrap();
+
+        let sender = Arc::new(MockSender::default());
+        let mut service = service_with_sender(&pool, sender.clone());
+
+        service.perform_assignments().await.unwrap();
+
+        let channel_messages = sender.channel_messages.lock().unwrap();
+        let logged = channel_messages
+            .iter()
+            .filter(|(channel, _)| *channel == ChannelId::new(99))
+            .collect::<Vec<_>>();
+        assert_eq!(logged.len(), 1);
+        assert!(logged[0].1.starts_with("### Test (`Test`)"));
+    }
+
+    #[tokio::test]
+    async fn admin_with_closed_dms() {
+        let pool = setup_database().await;
+        let exchange_repository = ExchangeRepository::new(pool.clone());
+        let admin = UserId::new(42);
+
+        let exchange = create_ended_exchange(
+            &exchange_repository,
+            "Test",
+            Duration::minutes(1),
+            Some(admin),
+        )
+        .await;
+
+        let sender = Arc::new(MockSender {
+            unreachable_users: HashSet::from([admin]),
+            ..Default::default()
+        });
+        let mut service = service_with_sender(&pool, sender.clone());
+
+        let pass = service.perform_assignments().await.unwrap();
+
+        assert_eq!(
+            pass.outcomes,
+            vec![ExchangeAssignmentOutcome::without_assignments(
+                exchange.id,
+                ExchangeState::AssignmentsSent
+            )]
+        );
+        assert!(sender.dms.lock().unwrap().is_empty());
+    }
+}
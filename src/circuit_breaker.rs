@@ -0,0 +1,206 @@
+use std::time::{Duration, Instant};
+
+use tracing::{info, warn};
+
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests go through. Counts consecutive failures.
+    Closed { consecutive_failures: u32 },
+    /// Requests are rejected until the cooldown ends.
+    Open { until: Instant },
+    /// The cooldown ended and a trial request goes through to check whether the outage is over.
+    HalfOpen,
+}
+
+/// Stops hammering a service that keeps failing, e.g. Discord during an outage.
+///
+/// After `failure_threshold` consecutive failures, the circuit opens and requests are rejected
+/// for `cooldown`. Then a single trial request decides whether to close the circuit again
+/// or to wait another cooldown.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    state: CircuitState,
+    failure_threshold: u32,
+    cooldown: Duration,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> CircuitBreaker {
+        CircuitBreaker {
+            state: CircuitState::Closed {
+                consecutive_failures: 0,
+            },
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.state
+    }
+
+    /// When the cooldown of an open circuit ends.
+    pub fn cooldown_ends_at(&self) -> Option<Instant> {
+        match self.state {
+            CircuitState::Open { until } => Some(until),
+            _ => None,
+        }
+    }
+
+    /// Whether a request may be made now.
+    pub fn allow_request(&mut self, now: Instant) -> bool {
+        match self.state {
+            CircuitState::Closed { .. } => true,
+            CircuitState::Open { until } if now >= until => {
+                info!("Cooldown ended, letting a trial request through");
+                self.state = CircuitState::HalfOpen;
+                true
+            }
+            CircuitState::Open { .. } => false,
+            // The trial request is already in flight
+            CircuitState::HalfOpen => false,
+        }
+    }
+
+    pub fn record_success(&mut self) {
+        if !matches!(self.state, CircuitState::Closed { .. }) {
+            info!("Request succeeded, closing the circuit");
+        }
+
+        self.state = CircuitState::Closed {
+            consecutive_failures: 0,
+        };
+    }
+
+    pub fn record_failure(&mut self, now: Instant) {
+        match self.state {
+            CircuitState::Closed {
+                consecutive_failures,
+            } => {
+                let consecutive_failures = consecutive_failures + 1;
+
+                if consecutive_failures >= self.failure_threshold {
+                    warn!(
+                        "{consecutive_failures} consecutive requests failed, pausing requests for {:?}",
+                        self.cooldown
+                    );
+                    self.open(now);
+                } else {
+                    self.state = CircuitState::Closed {
+                        consecutive_failures,
+                    };
+                }
+            }
+            CircuitState::HalfOpen => {
+                warn!(
+                    "Trial request failed, pausing requests for another {:?}",
+                    self.cooldown
+                );
+                self.open(now);
+            }
+            // Requests that started before the circuit opened
+            CircuitState::Open { .. } => {}
+        }
+    }
+
+    fn open(&mut self, now: Instant) {
+        self.state = CircuitState::Open {
+            until: now + self.cooldown,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{CircuitBreaker, CircuitState};
+
+    #[test]
+    fn opens_after_consecutive_failures() {
+        let start = Instant::now();
+        let mut breaker = CircuitBreaker::new(3, Duration::from_secs(60));
+
+        for _ in 0..2 {
+            assert!(breaker.allow_request(start));
+            breaker.record_failure(start);
+        }
+        assert_eq!(
+            breaker.state(),
+            CircuitState::Closed {
+                consecutive_failures: 2
+            }
+        );
+
+        assert!(breaker.allow_request(start));
+        breaker.record_failure(start);
+        assert_eq!(
+            breaker.state(),
+            CircuitState::Open {
+                until: start + Duration::from_secs(60)
+            }
+        );
+        assert!(!breaker.allow_request(start + Duration::from_secs(59)));
+    }
+
+    #[test]
+    fn success_resets_failures() {
+        let start = Instant::now();
+        let mut breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+
+        breaker.record_failure(start);
+        breaker.record_success();
+        breaker.record_failure(start);
+
+        assert!(breaker.allow_request(start));
+        assert_eq!(
+            breaker.state(),
+            CircuitState::Closed {
+                consecutive_failures: 1
+            }
+        );
+    }
+
+    #[test]
+    fn half_open_trial_failure_reopens() {
+        let start = Instant::now();
+        let mut breaker = CircuitBreaker::new(1, Duration::from_secs(60));
+
+        breaker.record_failure(start);
+
+        let after_cooldown = start + Duration::from_secs(60);
+        assert!(breaker.allow_request(after_cooldown));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        // Only one trial request at a time
+        assert!(!breaker.allow_request(after_cooldown));
+
+        breaker.record_failure(after_cooldown);
+        assert_eq!(
+            breaker.state(),
+            CircuitState::Open {
+                until: after_cooldown + Duration::from_secs(60)
+            }
+        );
+    }
+
+    #[test]
+    fn half_open_trial_success_closes() {
+        let start = Instant::now();
+        let mut breaker = CircuitBreaker::new(1, Duration::from_secs(60));
+
+        breaker.record_failure(start);
+        assert!(breaker.allow_request(start + Duration::from_secs(60)));
+
+        breaker.record_success();
+        assert_eq!(
+            breaker.state(),
+            CircuitState::Closed {
+                consecutive_failures: 0
+            }
+        );
+        assert!(breaker.allow_request(start + Duration::from_secs(60)));
+    }
+}
@@ -1,9 +1,13 @@
 use std::{fmt::Display, str::FromStr};
 
+use regex::Regex;
+
 use crate::commands::CommandError;
 
 use super::super::user_err;
 
+/// An exchange slug in its canonical form, without surrounding whitespace.
+#[derive(Debug, PartialEq, Eq)]
 pub struct ExchangeSlug(String);
 
 impl FromStr for ExchangeSlug {
@@ -12,9 +16,9 @@ impl FromStr for ExchangeSlug {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let s = s.trim();
 
-        let is_valid = s
-            .chars()
-            .all(|c| matches!(c, 'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_'));
+        let is_valid = !s.is_empty()
+            && s.chars()
+                .all(|c| matches!(c, 'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_'));
 
         if is_valid {
             Ok(ExchangeSlug(s.to_string()))
@@ -24,6 +28,22 @@ impl FromStr for ExchangeSlug {
     }
 }
 
+impl ExchangeSlug {
+    /// Whether the whole slug matches a guild's slug pattern.
+    pub fn matches_pattern(&self, pattern: &Regex) -> bool {
+        pattern.is_match(&self.0)
+    }
+}
+
+/// Compiles a guild's slug pattern so that it has to match the whole slug.
+///
+/// The pattern must parse on its own, otherwise something like `a)|(b` would only become valid
+/// by closing the group it's wrapped in and escape the anchors.
+pub fn slug_pattern_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    Regex::new(pattern)?;
+    Regex::new(&format!("^(?:{pattern})$"))
+}
+
 impl From<String> for ExchangeSlug {
     fn from(value: String) -> Self {
         ExchangeSlug::from_str(&value).unwrap()
@@ -58,7 +78,7 @@ impl AsRef<str> for ExchangeSlug {
 mod tests {
     use std::str::FromStr;
 
-    use crate::commands::arguments::exchange_slug::ExchangeSlug;
+    use crate::commands::arguments::exchange_slug::{slug_pattern_regex, ExchangeSlug};
 
     #[test]
     fn simple() {
@@ -85,6 +105,19 @@ mod tests {
         assert!(ExchangeSlug::from_str(" AlmostValidButContainsSpaces   ").is_ok());
     }
 
+    #[test]
+    fn surrounding_space_is_trimmed() {
+        let slug = ExchangeSlug::from_str(" foo ").unwrap();
+
+        assert_eq!(slug, ExchangeSlug::from_str("foo").unwrap());
+        assert_eq!(slug.to_string(), "foo");
+    }
+
+    #[test]
+    fn only_spaces() {
+        assert!(ExchangeSlug::from_str("   ").is_err());
+    }
+
     #[test]
     fn space_in_middle() {
         assert!(ExchangeSlug::from_str("Almost ValidBut ContainsSpaces").is_err());
@@ -94,4 +127,40 @@ mod tests {
     fn special_char() {
         assert!(ExchangeSlug::from_str("Foo!Bar").is_err());
     }
+
+    #[test]
+    fn pattern_must_match_whole_slug() {
+        let pattern = slug_pattern_regex("2024[A-Za-z]+").unwrap();
+
+        assert!(ExchangeSlug::from_str("2024Autumn")
+            .unwrap()
+            .matches_pattern(&pattern));
+        assert!(!ExchangeSlug::from_str("Autumn2024Autumn")
+            .unwrap()
+            .matches_pattern(&pattern));
+        assert!(!ExchangeSlug::from_str("2024Autumn-1")
+            .unwrap()
+            .matches_pattern(&pattern));
+    }
+
+    #[test]
+    fn pattern_alternatives_are_anchored() {
+        let pattern = slug_pattern_regex("jam|rerate").unwrap();
+
+        assert!(ExchangeSlug::from_str("jam")
+            .unwrap()
+            .matches_pattern(&pattern));
+        assert!(!ExchangeSlug::from_str("jam2")
+            .unwrap()
+            .matches_pattern(&pattern));
+        assert!(!ExchangeSlug::from_str("my-rerate")
+            .unwrap()
+            .matches_pattern(&pattern));
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        assert!(slug_pattern_regex("2024(").is_err());
+        assert!(slug_pattern_regex("jam)|(.*").is_err());
+    }
 }
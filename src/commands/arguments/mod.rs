@@ -3,7 +3,7 @@ mod human_datetime;
 mod human_duration;
 mod trimmed_string;
 
-pub use exchange_slug::ExchangeSlug;
+pub use exchange_slug::{slug_pattern_regex, ExchangeSlug};
 pub use human_datetime::HumanDateTime;
 pub use human_duration::HumanDuration;
 pub use trimmed_string::TrimmedString;
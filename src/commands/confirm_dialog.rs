@@ -0,0 +1,141 @@
+use poise::{
+    serenity_prelude::{ButtonStyle, CreateActionRow, CreateButton, CreateEmbed},
+    CreateReply, ReplyHandle,
+};
+use time::Duration;
+
+use super::{internal_err, ApplicationContext, CommandError};
+
+const CONFIRM_ID: &str = "confirm";
+const CANCEL_ID: &str = "cancel";
+
+/// How the author answered a confirmation dialog.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Confirmation {
+    Confirmed,
+    Cancelled,
+    TimedOut,
+}
+
+/// Asks the author to confirm a destructive action with Cancel and confirm buttons.
+///
+/// The dialog stays up, so the caller can edit the returned reply to show the result. Only the
+/// author's clicks count, and no click within `timeout` cancels the action.
+pub async fn confirm_dialog<'a>(
+    ctx: ApplicationContext<'a>,
+    content: &str,
+    embed: CreateEmbed,
+    confirm_label: &str,
+    timeout: Duration,
+) -> Result<(ReplyHandle<'a>, Confirmation), CommandError> {
+    let reply = ctx
+        .send(dialog_reply(content, embed, confirm_label, timeout))
+        .await?;
+
+    let interaction = reply
+        .message()
+        .await?
+        .await_component_interaction(ctx.serenity_context())
+        .author_id(ctx.author().id)
+        .timeout(timeout.unsigned_abs())
+        .await;
+
+    let confirmation = decide(
+        interaction
+            .as_ref()
+            .map(|interaction| interaction.data.custom_id.as_str()),
+    )?;
+
+    Ok((reply, confirmation))
+}
+
+fn dialog_reply(
+    content: &str,
+    embed: CreateEmbed,
+    confirm_label: &str,
+    timeout: Duration,
+) -> CreateReply {
+    CreateReply::default()
+        .content(format!(
+            "{content}\n\
+             \n\
+             **If you don't confirm in {timeout}, it will be cancelled automatically.**"
+        ))
+        .embed(embed)
+        .components(vec![CreateActionRow::Buttons(vec![
+            CreateButton::new(CANCEL_ID)
+                .label("Cancel")
+                .style(ButtonStyle::Secondary),
+            CreateButton::new(CONFIRM_ID)
+                .label(confirm_label)
+                .style(ButtonStyle::Primary),
+        ])])
+}
+
+/// Maps the ID of the clicked button, if any, to the author's answer.
+fn decide(custom_id: Option<&str>) -> Result<Confirmation, CommandError> {
+    match custom_id {
+        Some(CONFIRM_ID) => Ok(Confirmation::Confirmed),
+        Some(CANCEL_ID) => Ok(Confirmation::Cancelled),
+        Some(id) => Err(internal_err(format!("Unknown interaction ID: {id}"))),
+        None => Ok(Confirmation::TimedOut),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::{CreateActionRow, CreateEmbed};
+    use time::Duration;
+
+    use crate::commands::CommandError;
+
+    use super::{decide, dialog_reply, Confirmation};
+
+    #[test]
+    fn clicked_buttons() {
+        assert_eq!(decide(Some("confirm")).unwrap(), Confirmation::Confirmed);
+        assert_eq!(decide(Some("cancel")).unwrap(), Confirmation::Cancelled);
+    }
+
+    #[test]
+    fn no_click_times_out() {
+        assert_eq!(decide(None).unwrap(), Confirmation::TimedOut);
+    }
+
+    #[test]
+    fn unknown_button_is_internal_error() {
+        match decide(Some("delete")) {
+            Err(CommandError::Internal { message }) => {
+                assert_eq!(message, "Unknown interaction ID: delete");
+            }
+            result => panic!("Expected an internal error, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn reply_mentions_timeout_and_has_buttons() {
+        let reply = dialog_reply(
+            "# Confirm deletion",
+            CreateEmbed::default().title("Test"),
+            "Delete",
+            Duration::minutes(5),
+        );
+
+        let content = reply.content.unwrap();
+        assert!(content.starts_with("# Confirm deletion\n"));
+        assert!(content.contains("If you don't confirm in 5m, it will be cancelled automatically."));
+        assert_eq!(reply.embeds.len(), 1);
+
+        let components = reply.components.unwrap();
+        match components.as_slice() {
+            [CreateActionRow::Buttons(buttons)] => {
+                let buttons = serde_json::to_value(buttons).unwrap();
+                assert_eq!(buttons[0]["custom_id"], "cancel");
+                assert_eq!(buttons[0]["label"], "Cancel");
+                assert_eq!(buttons[1]["custom_id"], "confirm");
+                assert_eq!(buttons[1]["label"], "Delete");
+            }
+            components => panic!("Expected a single row of buttons, got {components:?}"),
+        }
+    }
+}
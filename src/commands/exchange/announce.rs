@@ -0,0 +1,57 @@
+use poise::CreateReply;
+use serenity::all::{CreateMessage, Mentionable};
+
+use crate::{
+    commands::{internal_err, require_guild, user_err, ApplicationContext, CommandResult},
+    models::ExchangeState,
+    utils::announcements::submissions_open,
+};
+
+/// Re-post the submissions-open announcement, e.g. after fixing the channel permissions.
+#[poise::command(slash_command, rename = "announce")]
+pub async fn announce(
+    ctx: ApplicationContext<'_>,
+    #[description = "Exchange slug"] slug: String,
+) -> CommandResult {
+    let exchange = ctx
+        .data
+        .exchange_repository
+        .get_exchange_by_slug(require_guild(ctx.guild_id())?, &slug)
+        .await
+        .map_err(|err| internal_err(format!("Could not get the exchange: {err}")))?
+        .ok_or(user_err(format!(
+            "Exchange with slug `{slug}` does not exist"
+        )))?;
+
+    if exchange.state != ExchangeState::AcceptingSubmissions {
+        return Err(user_err(format!(
+            "Exchange `{slug}` is not accepting submissions, so there is nothing to announce"
+        )));
+    }
+
+    exchange
+        .channel
+        .send_message(
+            ctx.serenity_context(),
+            CreateMessage::new().content(submissions_open(&exchange)),
+        )
+        .await
+        .map_err(|err| {
+            user_err(format!(
+                "Could not post the announcement in {}: {err}\n\nPlease check the bot's permissions in that channel.",
+                exchange.channel.mention()
+            ))
+        })?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "The announcement was posted in {}.",
+                exchange.channel.mention()
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
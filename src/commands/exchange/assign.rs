@@ -0,0 +1,211 @@
+use poise::CreateReply;
+use serenity::all::{CreateMessage, Mentionable, User, UserId};
+use tracing::warn;
+
+use crate::{
+    commands::{
+        internal_err, require_guild, user_err, ApplicationContext, CommandError, CommandResult,
+    },
+    jam_types::normalize_entry_link,
+    models::{Exchange, Submission},
+    utils::announcements::manual_assignment_dm,
+};
+
+/// Manually assign an entry of the exchange to a member, bypassing the solver.
+#[poise::command(slash_command, rename = "assign")]
+pub async fn assign(
+    ctx: ApplicationContext<'_>,
+    #[description = "Exchange slug"] slug: String,
+    #[description = "The member to assign the entry to"] user: User,
+    #[description = "Entry link"] link: String,
+) -> CommandResult {
+    let exchange = ctx
+        .data
+        .exchange_repository
+        .get_exchange_by_slug(require_guild(ctx.guild_id())?, &slug)
+        .await
+        .map_err(|err| internal_err(format!("Could not get the exchange: {err}")))?
+        .ok_or(user_err(format!(
+            "Exchange with slug `{slug}` does not exist"
+        )))?;
+
+    let submission = ctx
+        .data
+        .submission_repository
+        .get_submission_by_link(exchange.id, &link)
+        .await
+        .map_err(|err| internal_err(format!("Could not get the submission: {err}")))?;
+
+    let link = match check_assignable_submission(&exchange, submission.as_ref(), user.id, &link) {
+        Ok(submission) => {
+            ctx.data
+                .assignment_repository
+                .add_assignments(exchange.id, &[(user.id, submission.id)])
+                .await
+                .map_err(|err| internal_err(format!("Could not record the assignment: {err}")))?;
+
+            submission.link.clone()
+        }
+
+        // Entries from the backfill pool can be assigned too
+        Err(err) => {
+            let backfill = ctx
+                .data
+                .backfill_repository
+                .get_backfill_entries(exchange.id)
+                .await
+                .map_err(|err| internal_err(format!("Could not get the backfill pool: {err}")))?;
+
+            let link = normalize_entry_link(&link);
+            if submission.is_some() || !backfill.contains(&link) {
+                return Err(err);
+            }
+
+            ctx.data
+                .assignment_repository
+                .add_backfill_assignments(exchange.id, &[(user.id, link.clone())])
+                .await
+                .map_err(|err| internal_err(format!("Could not record the assignment: {err}")))?;
+
+            link
+        }
+    };
+
+    let message = manual_assignment_dm(&exchange, &link);
+
+    let reply = match user
+        .direct_message(
+            ctx.serenity_context(),
+            CreateMessage::new().content(message),
+        )
+        .await
+    {
+        Ok(_) => {
+            if let Err(err) = ctx
+                .data
+                .played_game_repository
+                .submit(user.id, &link, false)
+                .await
+            {
+                warn!(
+                    "Could not register an assignment {} as played for user {}: {err}",
+                    link, user.id
+                );
+            }
+
+            format!(
+                "# Assigned!\n\n{} has received `{}` in their DMs.",
+                user.mention(),
+                link
+            )
+        }
+
+        Err(err) => {
+            warn!(
+                "Could not send a manual assignment to user {}: {err}",
+                user.id
+            );
+
+            format!(
+                "# Assignment recorded, but the DM failed\n\nPlease send `{}` to {} yourself.",
+                link,
+                user.mention()
+            )
+        }
+    };
+
+    ctx.send(CreateReply::default().content(reply).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Checks that the submission found for the given link can be assigned to the reviewer.
+fn check_assignable_submission<'a>(
+    exchange: &Exchange,
+    submission: Option<&'a Submission>,
+    reviewer: UserId,
+    link: &str,
+) -> Result<&'a Submission, CommandError> {
+    let submission = submission.ok_or(user_err(format!(
+        "`{}` was not submitted to **{}**",
+        link.trim(),
+        exchange.display_name
+    )))?;
+
+    if submission.submitter == reviewer {
+        return Err(user_err("Members can't be assigned their own entry"));
+    }
+
+    Ok(submission)
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::all::UserId;
+    use time::macros::datetime;
+
+    use crate::models::{
+        test_exchange, types::UtcDateTime, Exchange, ExchangeId, ExchangeState, Submission,
+        SubmissionId,
+    };
+
+    use super::check_assignable_submission;
+
+    fn exchange() -> Exchange {
+        Exchange {
+            state: ExchangeState::AssignmentsSent,
+            ..test_exchange()
+        }
+    }
+
+    fn submission(i: u64) -> Submission {
+        Submission {
+            id: SubmissionId(i),
+            exchange_id: ExchangeId(1),
+            link: format!("https://itch.io/jam/example-jam/rate/00000{i}"),
+            submitter: UserId::new(i),
+            submitted_at: UtcDateTime::assume_utc(datetime!(2024-01-01 12:30:00)),
+            edit_count: 0,
+        }
+    }
+
+    #[test]
+    fn assignable_submission() {
+        let submission = submission(2);
+
+        let assignable = check_assignable_submission(
+            &exchange(),
+            Some(&submission),
+            UserId::new(1),
+            "https://itch.io/jam/example-jam/rate/000002",
+        )
+        .unwrap();
+
+        assert_eq!(assignable.id, SubmissionId(2));
+    }
+
+    #[test]
+    fn link_not_in_exchange() {
+        assert!(check_assignable_submission(
+            &exchange(),
+            None,
+            UserId::new(1),
+            "https://itch.io/jam/example-jam/rate/000003",
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn own_entry() {
+        let submission = submission(1);
+
+        assert!(check_assignable_submission(
+            &exchange(),
+            Some(&submission),
+            UserId::new(1),
+            "https://itch.io/jam/example-jam/rate/000001",
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,162 @@
+use poise::CreateReply;
+
+use crate::{
+    commands::{internal_err, require_guild, user_err, ApplicationContext, CommandResult},
+    jam_types::normalize_entry_link,
+    models::ExchangeState,
+    utils::formatting::MAX_MESSAGE_LENGTH,
+};
+
+/// Manage the entries that top up reviewers when the exchange has too few submissions.
+#[poise::command(slash_command, rename = "backfill")]
+pub async fn backfill(
+    ctx: ApplicationContext<'_>,
+    #[description = "Exchange slug"] slug: String,
+    #[description = "Entry links to add to the pool, separated by spaces"] links: Option<String>,
+    #[description = "Remove all entries from the pool first"] clear: Option<bool>,
+) -> CommandResult {
+    let exchange = ctx
+        .data
+        .exchange_repository
+        .get_exchange_by_slug(require_guild(ctx.guild_id())?, &slug)
+        .await
+        .map_err(|err| internal_err(format!("Could not get the exchange: {err}")))?
+        .ok_or(user_err(format!(
+            "Exchange with slug `{slug}` does not exist"
+        )))?;
+
+    let links = links
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(normalize_entry_link)
+        .collect::<Vec<_>>();
+    let clear = clear.unwrap_or(false);
+
+    if (clear || !links.is_empty())
+        && !matches!(
+            exchange.state,
+            ExchangeState::NotStartedYet | ExchangeState::AcceptingSubmissions
+        )
+    {
+        return Err(user_err(format!(
+            "Exchange `{slug}` is no longer accepting submissions, so its backfill pool can't change"
+        )));
+    }
+
+    if !links.is_empty() {
+        let jam_type = exchange.jam_type.known().ok_or(user_err(format!(
+            "Exchange `{slug}` was created for {} jams, so it can't take new entries",
+            exchange.jam_type.name()
+        )))?;
+
+        if let Some(invalid) = links
+            .iter()
+            .find(|link| !jam_type.validate_entry_link(link, &ctx.data.ludum_dare_event_types))
+        {
+            return Err(user_err(format!(
+                "`{invalid}` is not a valid entry link, it should look like `{}`",
+                jam_type.jam_entry_link_example(&exchange.jam_link)
+            )));
+        }
+    }
+
+    let repository = &ctx.data.backfill_repository;
+
+    if clear {
+        repository
+            .clear_backfill_entries(exchange.id)
+            .await
+            .map_err(|err| internal_err(format!("Could not clear the backfill pool: {err}")))?;
+    }
+
+    repository
+        .add_backfill_entries(exchange.id, &links)
+        .await
+        .map_err(|err| internal_err(format!("Could not add the backfill entries: {err}")))?;
+
+    let pool = repository
+        .get_backfill_entries(exchange.id)
+        .await
+        .map_err(|err| internal_err(format!("Could not get the backfill pool: {err}")))?;
+
+    let content = pool_message(&slug, &pool);
+
+    ctx.send(CreateReply::default().content(content).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+fn pool_message(slug: &str, pool: &[String]) -> String {
+    if pool.is_empty() {
+        return format!("The backfill pool of `{slug}` is empty.");
+    }
+
+    let mut message = format!(
+        "The backfill pool of `{slug}` has {} entries:\n",
+        pool.len()
+    );
+
+    for (index, link) in pool.iter().enumerate() {
+        let line = format!("- <{link}>\n");
+
+        // Room for the note is only needed if there are entries after this one to cut off
+        let left_after = pool.len() - index - 1;
+        let reserved = if left_after > 0 {
+            omitted_note(left_after).len()
+        } else {
+            0
+        };
+
+        if message.len() + line.len() + reserved > MAX_MESSAGE_LENGTH {
+            message += &omitted_note(pool.len() - index);
+            break;
+        }
+
+        message += &line;
+    }
+
+    message
+}
+
+fn omitted_note(omitted: usize) -> String {
+    format!("...and {omitted} more")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pool_message, MAX_MESSAGE_LENGTH};
+
+    fn pool(count: usize) -> Vec<String> {
+        (1..=count)
+            .map(|i| format!("https://itch.io/jam/previous-jam/rate/{i:06}"))
+            .collect()
+    }
+
+    #[test]
+    fn empty_pool() {
+        assert_eq!(
+            pool_message("Test", &[]),
+            "The backfill pool of `Test` is empty."
+        );
+    }
+
+    #[test]
+    fn small_pool() {
+        assert_eq!(
+            pool_message("Test", &pool(2)),
+            "The backfill pool of `Test` has 2 entries:\n\
+             - <https://itch.io/jam/previous-jam/rate/000001>\n\
+             - <https://itch.io/jam/previous-jam/rate/000002>\n"
+        );
+    }
+
+    #[test]
+    fn large_pool_is_truncated() {
+        let message = pool_message("Test", &pool(100));
+
+        assert!(message.len() <= MAX_MESSAGE_LENGTH);
+        assert!(message.starts_with("The backfill pool of `Test` has 100 entries:\n"));
+        assert!(message.ends_with(" more"));
+    }
+}
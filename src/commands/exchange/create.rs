@@ -3,26 +3,62 @@ use std::str::FromStr;
 
 use indoc::formatdoc;
 use poise::serenity_prelude::Mentionable;
-use poise::serenity_prelude::{ButtonStyle, Channel};
+use poise::serenity_prelude::{Channel, ResolvedOption, ResolvedValue};
 use poise::{ChoiceParameter, CreateReply};
-use serenity::all::{Color, CreateActionRow, CreateButton};
+use serenity::all::Color;
 use serenity::builder::CreateEmbed;
+use strum::IntoEnumIterator;
 use time::Duration;
 use time::OffsetDateTime;
 
-use crate::models::{ExchangeState, NewExchange};
+use crate::models::{Exchange, ExchangeJam, ExchangeState, NewExchange};
+use crate::repository::ExchangeRepository;
 use crate::utils::formatting::{format_local, format_utc};
 use crate::{
     commands::{
-        arguments::{ExchangeSlug, HumanDateTime, HumanDuration, TrimmedString},
+        arguments::{
+            slug_pattern_regex, ExchangeSlug, HumanDateTime, HumanDuration, TrimmedString,
+        },
         camel_slug::slugify_camel,
-        internal_err, user_err, CommandResult,
+        confirm_dialog::{confirm_dialog, Confirmation},
+        internal_err, require_guild,
+        submission_channel::submission_channel,
+        user_err, CommandError, CommandResult,
     },
-    jam_types::JamType,
+    jam_types::{JamType, StoredJamType},
 };
 
 use super::super::ApplicationContext;
 
+async fn autocomplete_jam_link(ctx: ApplicationContext<'_>, partial: &str) -> Vec<String> {
+    let jam_type = chosen_jam_type(&ctx.interaction.data.options(), "type");
+
+    jam_link_suggestions(jam_type, partial)
+}
+
+/// Finds the jam type already picked for the `name` option in a partially filled
+/// `/exchange create` interaction.
+fn chosen_jam_type(options: &[ResolvedOption], name: &str) -> Option<JamType> {
+    options.iter().find_map(|option| match &option.value {
+        ResolvedValue::SubCommand(options) | ResolvedValue::SubCommandGroup(options) => {
+            chosen_jam_type(options, name)
+        }
+        ResolvedValue::Integer(index) if option.name == name => {
+            JamType::from_index(usize::try_from(*index).ok()?)
+        }
+        _ => None,
+    })
+}
+
+fn jam_link_suggestions(jam_type: Option<JamType>, partial: &str) -> Vec<String> {
+    match jam_type {
+        Some(jam_type) if jam_type.jam_link_example().starts_with(partial.trim()) => {
+            vec![jam_type.jam_link_example().to_string()]
+        }
+        _ => vec![],
+    }
+}
+
 /// Create a rating exchange.
 #[allow(clippy::too_many_arguments)]
 #[poise::command(slash_command, rename = "create")]
@@ -35,6 +71,7 @@ pub async fn create(
 
     #[rename = "link"]
     #[description = "The jam link. Must correspond to the jam type."]
+    #[autocomplete = "autocomplete_jam_link"]
     jam_link: TrimmedString,
 
     #[description = "The display name of the exchange to use in announcements."]
@@ -50,16 +87,48 @@ pub async fn create(
 
     #[description = "When the exchange starts. Defaults to now."] start: Option<HumanDateTime>,
 
+    #[description = "When the exchange starts relative to now, e.g. `2 hours`. Can't be used with `start`."]
+    start_in: Option<HumanDuration>,
+
     #[description = "Duration of the exchange. Defaults to 24 hours."] duration: Option<
         HumanDuration,
     >,
 
     #[description = "The name of the exchange to use in commands. Must consist only of `A-Za-z0-9_-`."]
     slug: Option<ExchangeSlug>,
+
+    #[description = "Whether to assign games members have already played, e.g. for re-rate rounds. Defaults to false."]
+    ignore_played: Option<bool>,
+
+    #[description = "Whether to hide who submitted what from other members. Defaults to false."]
+    blind: Option<bool>,
+
+    #[description = "Whether to announce each submission in the exchange channel. Defaults to false."]
+    public_confirmations: Option<bool>,
+
+    #[description = "How many times members can change their submission. Defaults to 0, which means unlimited."]
+    #[max = 100]
+    max_submission_edits: Option<u8>,
+
+    #[description = "How old submitters' Discord accounts must be, e.g. `30 days`. Defaults to no limit."]
+    min_account_age: Option<HumanDuration>,
+
+    #[description = "Links of other jams whose entries are accepted too, separated by spaces. For multi-platform jams."]
+    extra_jam_links: Option<TrimmedString>,
+
+    #[description = "How strongly to avoid members reviewing each other's entries. Best effort. Defaults to 0, which means off."]
+    #[max = 10]
+    reciprocal_penalty: Option<u8>,
+
+    #[description = "How long before the end of submissions members can no longer revoke, e.g. `1 hour`. Defaults to no lock."]
+    revoke_lock: Option<HumanDuration>,
+
+    #[description = "Whether to post anonymous assignment statistics in the channel. Defaults to false."]
+    announce_stats: Option<bool>,
 ) -> CommandResult {
     // To validate the jam link, we need to know the jam type. So, we do it here.
     let jam_link = jam_type
-        .normalize_jam_link(jam_link.as_ref())
+        .validate_jam_link(jam_link.as_ref(), &ctx.data.ludum_dare_event_types)
         .ok_or(user_err(format!(
             "Invalid jam link: `{link}`.\nFor {type}, it should look like this: `{link_example}`",
             link = jam_link,
@@ -67,37 +136,57 @@ pub async fn create(
             link_example = jam_type.jam_link_example()
         )))?;
 
-    let slug = slug.unwrap_or_else(|| slugify_camel(display_name.as_ref()).into());
-    if ExchangeSlug::from_str(slug.as_ref()).is_err() {
-        Err(internal_err(&format!(
-            "Auto-generated exchange slug is invalid: `{slug}`."
-        )))?;
-    }
-
-    // TODO: Implement custom GuildChannel argument type.
-    let submission_channel = match channel {
-        Channel::Guild(channel) => channel,
-        channel => {
-            return Err(internal_err(&format!(
-                "Something went wrong, submissions channel should never be `{channel:?}`."
-            )));
+    let extra_jams = resolve_extra_jams(
+        jam_type,
+        &jam_link,
+        extra_jam_links.as_ref().map_or("", |links| links.as_ref()),
+        &ctx.data.ludum_dare_event_types,
+    )?;
+
+    let slug = match slug {
+        Some(slug) => slug,
+        None => {
+            let generated = slugify_camel(display_name.as_ref());
+            ExchangeSlug::from_str(&generated).map_err(|_| {
+                internal_err(&format!(
+                    "Auto-generated exchange slug is invalid: `{generated}`."
+                ))
+            })?
         }
     };
 
+    let submission_channel = submission_channel(channel)?;
+
     let games_per_member = NonZeroU8::new(games_per_member.unwrap_or(5))
         .ok_or(internal_err("Games per member failed to validate"))?;
 
-    let start = start
-        .map(|dt| dt.materialize(OffsetDateTime::now_utc()))
-        .unwrap_or(OffsetDateTime::now_utc());
+    let ignore_played = ignore_played.unwrap_or(false);
+    let blind = blind.unwrap_or(false);
+    let public_confirmations = public_confirmations.unwrap_or(false);
+    let max_submission_edits = max_submission_edits.unwrap_or(0);
+    let min_account_age = min_account_age.map(|d| d.into()).unwrap_or(Duration::ZERO);
+    let reciprocal_penalty = reciprocal_penalty.unwrap_or(0);
+    let revoke_lock = revoke_lock.map(|d| d.into()).unwrap_or(Duration::ZERO);
+    let announce_stats = announce_stats.unwrap_or(false);
+
+    let start = resolve_start(start, start_in, OffsetDateTime::now_utc())?;
 
     let duration = duration.map(|d| d.into()).unwrap_or(Duration::hours(24));
 
     let end = start + duration;
 
-    let guild = ctx.guild_id().ok_or(internal_err(
-        "Exchange create command should only be invoked in guilds",
-    ))?;
+    let guild = require_guild(ctx.guild_id())?;
+
+    {
+        let settings = ctx
+            .data
+            .guild_settings_repository
+            .get_settings(guild)
+            .await
+            .map_err(|err| internal_err(format!("Could not get the server settings: {err}")))?;
+
+        check_slug_pattern(&slug, settings.slug_pattern.as_deref())?;
+    }
 
     {
         let overlapping_exchanges = ctx
@@ -149,119 +238,158 @@ pub async fn create(
         submissions_start: start.into(),
         submissions_end: end.into(),
         games_per_member,
+        ignore_played,
+        blind,
+        public_confirmations,
+        max_submission_edits,
+        min_account_age,
+        extra_jams,
+        reciprocal_penalty,
+        revoke_lock,
+        announce_stats,
+        created_by: Some(ctx.author().id),
+        created_at: OffsetDateTime::now_utc().into(),
     };
 
-    let confirm_timeout = Duration::minutes(5);
-
-    let reply = ctx
-        .send(CreateReply::default()
-                .content(format!(
-                    "# Confirm exchange creation\n\
-                     You can find the details of a review exchange to be created in the embed below. \
-                     If you don't see the embed, check your Discord settings.\n\
-                     \n\
-                     If you need to make an edit, then cancel and use the command again. \
-                     You can press the up arrow key in your message box to quickly bring up the last command.
-                     \n\
-                     **If you don't confirm exchange creation in {confirm_timeout}, it will be cancelled automatically.**"
-                ))
-                .embed(create_new_exchange_embed(&new_exchange, Color::GOLD))
-                .components(vec![
-                    CreateActionRow::Buttons(
-                        vec![
-                            CreateButton::new("cancel").label("Cancel").style(ButtonStyle::Secondary),
-                            CreateButton::new("confirm").label("Create").style(ButtonStyle::Primary),
-                        ]
-                    ),
-                ])
-        )
-        .await?;
-
-    let interaction = reply
-        .message()
-        .await?
-        .await_component_interaction(ctx.serenity_context())
-        .author_id(ctx.author().id)
-        .await;
-
-    if let Some(interaction) = interaction {
-        match interaction.data.custom_id.as_str() {
-            "cancel" => {
-                reply
-                    .edit(
-                        ctx.into(),
-                        CreateReply::default()
-                            .content("# Canceled!")
-                            .components(vec![])
-                            .embed(create_new_exchange_embed(&new_exchange, Color::RED)),
-                    )
-                    .await?;
-            }
+    let (reply, confirmation) = confirm_dialog(
+        ctx,
+        "# Confirm exchange creation\n\
+         You can find the details of a review exchange to be created in the embed below. \
+         If you don't see the embed, check your Discord settings.\n\
+         \n\
+         If you need to make an edit, then cancel and use the command again. \
+         You can press the up arrow key in your message box to quickly bring up the last command.",
+        create_new_exchange_embed(&new_exchange, Color::GOLD),
+        "Create",
+        ctx.data.create_confirm_timeout,
+    )
+    .await?;
+
+    match confirmation {
+        Confirmation::Cancelled => {
+            reply
+                .edit(ctx.into(), cancelled_reply(&new_exchange, "# Canceled!"))
+                .await?;
+        }
 
-            "confirm" => {
-                let creation_result = ctx
-                    .data
-                    .exchange_repository
-                    .create_exchange(NewExchange {
-                        guild,
-                        channel: submission_channel.id,
-                        jam_type,
-                        jam_link: jam_link.to_string(),
-                        slug: slug.to_string(),
-                        display_name: display_name.to_string(),
-                        state: ExchangeState::NotStartedYet,
-                        submissions_start: start.into(),
-                        submissions_end: end.into(),
-                        games_per_member,
-                    })
-                    .await;
-
-                match creation_result {
-                    Ok(_exchange) => {
-                        reply
-                            .edit(
-                                ctx.into(),
-                                CreateReply::default()
-                                    .content("# Exchange created!")
-                                    .components(vec![])
-                                    .embed(create_new_exchange_embed(
-                                        &new_exchange,
-                                        Color::DARK_GREEN,
-                                    )),
-                            )
-                            .await?;
-                    }
-                    Err(err) => {
-                        reply
-                            .edit(
-                                ctx.into(),
-                                CreateReply::default()
-                                    .components(vec![])
-                                    .content(format!("# Could not create exchange!\n{err}")),
-                            )
-                            .await?;
-                    }
-                }
-            }
+        Confirmation::Confirmed => {
+            let created_reply =
+                insert_confirmed_exchange(&ctx.data.exchange_repository, &new_exchange).await;
+            reply.edit(ctx.into(), created_reply).await?;
+        }
 
-            id => {
-                return Err(internal_err(&format!("Unknown interaction ID: {}", id)));
-            }
+        Confirmation::TimedOut => {
+            reply
+                .edit(ctx.into(), timed_out_reply(&new_exchange))
+                .await?;
         }
     }
 
     Ok(())
 }
 
+fn resolve_start(
+    start: Option<HumanDateTime>,
+    start_in: Option<HumanDuration>,
+    now: OffsetDateTime,
+) -> Result<OffsetDateTime, CommandError> {
+    match (start, start_in) {
+        (Some(_), Some(_)) => Err(user_err(
+            "Please specify either `start` or `start_in`, but not both.",
+        )),
+        (Some(start), None) => Ok(start.materialize(now)),
+        (None, Some(start_in)) => Ok(now + Duration::from(start_in)),
+        (None, None) => Ok(now),
+    }
+}
+
+/// Parses the space-separated links of the extra jams, detecting the type of each of them.
+fn resolve_extra_jams(
+    jam_type: JamType,
+    jam_link: &str,
+    extra_jam_links: &str,
+    ludum_dare_event_types: &[String],
+) -> Result<Vec<ExchangeJam>, CommandError> {
+    let mut extra_jams: Vec<ExchangeJam> = vec![];
+
+    for link in extra_jam_links.split_whitespace() {
+        let (extra_jam_type, extra_jam_link) = JamType::iter()
+            .find_map(|extra_jam_type| {
+                let extra_jam_link =
+                    extra_jam_type.validate_jam_link(link, ludum_dare_event_types)?;
+                Some((extra_jam_type, extra_jam_link))
+            })
+            .ok_or(user_err(format!(
+                "Invalid extra jam link: `{link}`.\nIt should look like one of these: {link_examples}",
+                link_examples = JamType::iter()
+                    .map(|jam_type| format!("`{}`", jam_type.jam_link_example()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )))?;
+
+        if extra_jam_type == jam_type && extra_jam_link == jam_link {
+            return Err(user_err(format!(
+                "The extra jam `{extra_jam_link}` is the same as the main one."
+            )));
+        }
+
+        if extra_jams.iter().any(|jam| jam.jam_link == extra_jam_link) {
+            return Err(user_err(format!(
+                "The extra jam `{extra_jam_link}` is listed more than once."
+            )));
+        }
+
+        extra_jams.push(ExchangeJam {
+            jam_type: StoredJamType::Known(extra_jam_type),
+            jam_link: extra_jam_link,
+        });
+    }
+
+    Ok(extra_jams)
+}
+
+/// Inserts the exchange exactly as the author confirmed it and builds the reply telling them
+/// whether it worked.
+async fn insert_confirmed_exchange(
+    repository: &ExchangeRepository,
+    new_exchange: &NewExchange,
+) -> CreateReply {
+    match repository.create_exchange(new_exchange.clone()).await {
+        Ok(_exchange) => CreateReply::default()
+            .content("# Exchange created!")
+            .components(vec![])
+            .embed(create_new_exchange_embed(new_exchange, Color::DARK_GREEN)),
+        Err(err) => CreateReply::default()
+            .components(vec![])
+            .content(format!("# Could not create exchange!\n{err}")),
+    }
+}
+
+fn cancelled_reply(exchange: &NewExchange, title: &str) -> CreateReply {
+    CreateReply::default()
+        .content(title)
+        .components(vec![])
+        .embed(create_new_exchange_embed(exchange, Color::RED))
+}
+
+fn timed_out_reply(exchange: &NewExchange) -> CreateReply {
+    cancelled_reply(exchange, "# Timed out, cancelled!")
+}
+
 fn create_new_exchange_embed(exchange: &NewExchange, color: Color) -> CreateEmbed {
     let exchange_duration = OffsetDateTime::from(exchange.submissions_end)
         - OffsetDateTime::from(exchange.submissions_start);
 
-    CreateEmbed::default()
+    let embed = CreateEmbed::default()
         .title(&exchange.display_name)
         .color(color)
         .field("Jam type", exchange.jam_type.name(), true)
         .field("Jam link", &exchange.jam_link, true)
+        .field(
+            "Extra jams",
+            extra_jams_description(&exchange.extra_jams),
+            false,
+        )
         .field(
             "Submission channel",
             exchange.channel.mention().to_string(),
@@ -285,11 +413,408 @@ fn create_new_exchange_embed(exchange: &NewExchange, color: Color) -> CreateEmbe
             ),
             false,
         )
-        .field("Duration", exchange_duration.to_string(), false)
+        .field("Duration", exchange_duration.to_string(), false);
+
+    settings_fields(embed, exchange.into())
+}
+
+/// The settings shown in the embeds of both new and existing exchanges.
+pub(super) struct ExchangeSettings<'a> {
+    games_per_member: NonZeroU8,
+    slug: &'a str,
+    ignore_played: bool,
+    blind: bool,
+    public_confirmations: bool,
+    max_submission_edits: u8,
+    min_account_age: Duration,
+    reciprocal_penalty: u8,
+    revoke_lock: Duration,
+    announce_stats: bool,
+}
+
+impl<'a> From<&'a NewExchange> for ExchangeSettings<'a> {
+    fn from(exchange: &'a NewExchange) -> Self {
+        ExchangeSettings {
+            games_per_member: exchange.games_per_member,
+            slug: &exchange.slug,
+            ignore_played: exchange.ignore_played,
+            blind: exchange.blind,
+            public_confirmations: exchange.public_confirmations,
+            max_submission_edits: exchange.max_submission_edits,
+            min_account_age: exchange.min_account_age,
+            reciprocal_penalty: exchange.reciprocal_penalty,
+            revoke_lock: exchange.revoke_lock,
+            announce_stats: exchange.announce_stats,
+        }
+    }
+}
+
+impl<'a> From<&'a Exchange> for ExchangeSettings<'a> {
+    fn from(exchange: &'a Exchange) -> Self {
+        ExchangeSettings {
+            games_per_member: exchange.games_per_member,
+            slug: &exchange.slug,
+            ignore_played: exchange.ignore_played,
+            blind: exchange.blind,
+            public_confirmations: exchange.public_confirmations,
+            max_submission_edits: exchange.max_submission_edits,
+            min_account_age: exchange.min_account_age,
+            reciprocal_penalty: exchange.reciprocal_penalty,
+            revoke_lock: exchange.revoke_lock,
+            announce_stats: exchange.announce_stats,
+        }
+    }
+}
+
+/// Adds the fields with the exchange settings to an exchange embed.
+pub(super) fn settings_fields(embed: CreateEmbed, settings: ExchangeSettings) -> CreateEmbed {
+    embed
         .field(
             "Games per member",
-            exchange.games_per_member.to_string(),
+            settings.games_per_member.to_string(),
+            true,
+        )
+        .field("Slug", format!("`{}`", settings.slug), true)
+        .field(
+            "Ignore played games",
+            if settings.ignore_played { "Yes" } else { "No" },
             true,
         )
-        .field("Slug", format!("`{}`", exchange.slug), true)
+        .field("Blind", if settings.blind { "Yes" } else { "No" }, true)
+        .field(
+            "Public confirmations",
+            if settings.public_confirmations {
+                "Yes"
+            } else {
+                "No"
+            },
+            true,
+        )
+        .field(
+            "Submission edits",
+            match settings.max_submission_edits {
+                0 => "Unlimited".to_string(),
+                max_edits => max_edits.to_string(),
+            },
+            true,
+        )
+        .field(
+            "Minimum account age",
+            if settings.min_account_age.is_zero() {
+                "None".to_string()
+            } else {
+                settings.min_account_age.to_string()
+            },
+            true,
+        )
+        .field(
+            "Reciprocal review penalty",
+            match settings.reciprocal_penalty {
+                0 => "Off".to_string(),
+                penalty => penalty.to_string(),
+            },
+            true,
+        )
+        .field(
+            "Revoke lock",
+            if settings.revoke_lock.is_zero() {
+                "None".to_string()
+            } else {
+                format!("{} before the end", settings.revoke_lock)
+            },
+            true,
+        )
+        .field(
+            "Public statistics",
+            if settings.announce_stats { "Yes" } else { "No" },
+            true,
+        )
+}
+
+pub(super) fn extra_jams_description(extra_jams: &[ExchangeJam]) -> String {
+    if extra_jams.is_empty() {
+        return "None".to_string();
+    }
+
+    extra_jams
+        .iter()
+        .map(|jam| format!("{}: {}", jam.jam_type.name(), jam.jam_link))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn check_slug_pattern(slug: &ExchangeSlug, pattern: Option<&str>) -> Result<(), CommandError> {
+    let Some(pattern) = pattern else {
+        return Ok(());
+    };
+
+    let regex = slug_pattern_regex(pattern).map_err(|err| {
+        internal_err(format!(
+            "The server's slug pattern `{pattern}` is invalid: {err}"
+        ))
+    })?;
+
+    if !slug.matches_pattern(&regex) {
+        let message = formatdoc! {
+            r#"
+                # The exchange slug doesn't follow this server's naming convention
+
+                Slug `{slug}` must match the pattern `{pattern}`.
+
+                Pass a conforming slug with the `slug` option.
+            "#,
+        };
+        return Err(user_err(message));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, num::NonZeroU8, str::FromStr};
+
+    use poise::serenity_prelude::{ChannelId, GuildId};
+    use poise::CreateReply;
+    use serenity::all::Color;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use time::macros::datetime;
+
+    use crate::{
+        commands::{
+            arguments::{ExchangeSlug, HumanDateTime, HumanDuration},
+            CommandError,
+        },
+        jam_types::{default_ludum_dare_event_types, JamType, StoredJamType},
+        models::{test_new_exchange, types::UtcDateTime, ExchangeJam, NewExchange},
+        repository::ExchangeRepository,
+    };
+
+    use super::{
+        check_slug_pattern, create_new_exchange_embed, insert_confirmed_exchange,
+        jam_link_suggestions, resolve_extra_jams, resolve_start, timed_out_reply,
+    };
+
+    fn new_exchange() -> NewExchange {
+        NewExchange {
+            guild: GuildId::new(1),
+            channel: ChannelId::new(2),
+            submissions_end: UtcDateTime::assume_utc(datetime!(2024-01-02 12:00:00)),
+            ..test_new_exchange()
+        }
+    }
+
+    #[test]
+    fn timed_out_reply_cancels() {
+        let reply = timed_out_reply(&new_exchange());
+
+        assert_eq!(reply.content.as_deref(), Some("# Timed out, cancelled!"));
+        assert_eq!(reply.embeds.len(), 1);
+        assert!(reply.components.is_some_and(|c| c.is_empty()));
+    }
+
+    /// The values of the embed fields by name.
+    fn embed_fields(reply: &CreateReply) -> BTreeMap<String, String> {
+        let embed = serde_json::to_value(&reply.embeds[0]).unwrap();
+
+        embed["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| {
+                (
+                    field["name"].as_str().unwrap().to_string(),
+                    field["value"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn confirmed_exchange_is_inserted_as_shown() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let repository = ExchangeRepository::new(pool);
+
+        let new_exchange = NewExchange {
+            games_per_member: NonZeroU8::new(7).unwrap(),
+            ..new_exchange()
+        };
+        let shown = embed_fields(
+            &CreateReply::default().embed(create_new_exchange_embed(&new_exchange, Color::GOLD)),
+        );
+
+        let reply = insert_confirmed_exchange(&repository, &new_exchange).await;
+
+        assert_eq!(reply.content.as_deref(), Some("# Exchange created!"));
+        assert_eq!(embed_fields(&reply), shown);
+
+        let created = repository
+            .get_exchange_by_slug(new_exchange.guild, &new_exchange.slug)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(created.display_name, new_exchange.display_name);
+        assert_eq!(created.submissions_start, new_exchange.submissions_start);
+        assert_eq!(created.submissions_end, new_exchange.submissions_end);
+        assert_eq!(
+            shown["Games per member"],
+            created.games_per_member.to_string()
+        );
+        assert_eq!(shown["Slug"], format!("`{}`", created.slug));
+        assert_eq!(shown["Jam link"], created.jam_link);
+    }
+
+    #[test]
+    fn start_defaults_to_now() {
+        let now = datetime!(2024-01-01 12:00:00 UTC);
+
+        assert_eq!(resolve_start(None, None, now).unwrap(), now);
+    }
+
+    #[test]
+    fn absolute_start() {
+        let now = datetime!(2024-01-01 12:00:00 UTC);
+        let start = HumanDateTime::from_str("2024-01-03 15:30 UTC").unwrap();
+
+        assert_eq!(
+            resolve_start(Some(start), None, now).unwrap(),
+            datetime!(2024-01-03 15:30:00 UTC)
+        );
+    }
+
+    #[test]
+    fn relative_start() {
+        let now = datetime!(2024-01-01 12:00:00 UTC);
+        let start_in = HumanDuration::from_str("2 hours 30 minutes").unwrap();
+
+        assert_eq!(
+            resolve_start(None, Some(start_in), now).unwrap(),
+            datetime!(2024-01-01 14:30:00 UTC)
+        );
+    }
+
+    #[test]
+    fn absolute_and_relative_start_are_exclusive() {
+        let now = datetime!(2024-01-01 12:00:00 UTC);
+        let start = HumanDateTime::from_str("15:30 UTC").unwrap();
+        let start_in = HumanDuration::from_str("2h").unwrap();
+
+        assert!(resolve_start(Some(start), Some(start_in), now).is_err());
+    }
+
+    #[test]
+    fn jam_link_suggestion_for_chosen_type() {
+        assert_eq!(
+            jam_link_suggestions(Some(JamType::Itch), ""),
+            vec!["https://itch.io/jam/example-jam".to_string()]
+        );
+        assert_eq!(
+            jam_link_suggestions(Some(JamType::LudumDare), "https://ldjam"),
+            vec!["https://ldjam.com/events/ludum-dare/123456".to_string()]
+        );
+    }
+
+    #[test]
+    fn no_jam_link_suggestion_without_type() {
+        assert!(jam_link_suggestions(None, "").is_empty());
+    }
+
+    #[test]
+    fn no_jam_link_suggestion_for_other_input() {
+        assert!(jam_link_suggestions(Some(JamType::Itch), "https://itch.io/jam/my-jam").is_empty());
+    }
+
+    #[test]
+    fn no_extra_jams() {
+        let extra_jams = resolve_extra_jams(
+            JamType::Itch,
+            "https://itch.io/jam/example-jam",
+            "",
+            &default_ludum_dare_event_types(),
+        );
+
+        assert_eq!(extra_jams.unwrap(), vec![]);
+    }
+
+    #[test]
+    fn extra_jams_are_detected_and_normalized() {
+        let extra_jams = resolve_extra_jams(
+            JamType::Itch,
+            "https://itch.io/jam/example-jam",
+            "https://ldjam.com/events/ludum-dare/56/  https://itch.io/jam/other-jam",
+            &default_ludum_dare_event_types(),
+        );
+
+        assert_eq!(
+            extra_jams.unwrap(),
+            vec![
+                ExchangeJam {
+                    jam_type: StoredJamType::Known(JamType::LudumDare),
+                    jam_link: "https://ldjam.com/events/ludum-dare/56".to_string(),
+                },
+                ExchangeJam {
+                    jam_type: StoredJamType::Known(JamType::Itch),
+                    jam_link: "https://itch.io/jam/other-jam".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_extra_jams() {
+        let main_link = "https://itch.io/jam/example-jam";
+        let event_types = default_ludum_dare_event_types();
+
+        assert!(resolve_extra_jams(JamType::Itch, main_link, "not-a-jam", &event_types).is_err());
+        assert!(resolve_extra_jams(
+            JamType::Itch,
+            main_link,
+            "https://itch.io/jam/example-jam/",
+            &event_types
+        )
+        .is_err());
+        assert!(resolve_extra_jams(
+            JamType::Itch,
+            main_link,
+            "https://itch.io/jam/other-jam https://itch.io/jam/other-jam/",
+            &event_types
+        )
+        .is_err());
+        assert!(resolve_extra_jams(
+            JamType::Itch,
+            main_link,
+            "https://ldjam.com/events/october-challenge/2024",
+            &["ludum-dare".to_string()]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn slug_without_pattern() {
+        assert!(check_slug_pattern(&ExchangeSlug::from_str("Anything").unwrap(), None).is_ok());
+    }
+
+    #[test]
+    fn conforming_slug() {
+        let slug = ExchangeSlug::from_str("2024-Autumn").unwrap();
+
+        assert!(check_slug_pattern(&slug, Some("2024-.+")).is_ok());
+    }
+
+    #[test]
+    fn non_conforming_slug() {
+        let slug = ExchangeSlug::from_str("Autumn").unwrap();
+
+        match check_slug_pattern(&slug, Some("2024-.+")) {
+            Err(CommandError::User { message }) => {
+                assert!(message.contains("`Autumn`"));
+                assert!(message.contains("`2024-.+`"));
+            }
+            result => panic!("Expected a user error, got {result:?}"),
+        }
+    }
 }
@@ -1,6 +1,6 @@
 use poise::CreateReply;
 
-use crate::commands::{internal_err, user_err, ApplicationContext, CommandResult};
+use crate::commands::{internal_err, require_guild, user_err, ApplicationContext, CommandResult};
 
 #[poise::command(slash_command, rename = "delete")]
 pub async fn delete(
@@ -10,12 +10,7 @@ pub async fn delete(
     let deletion_result = ctx
         .data
         .exchange_repository
-        .delete_exchange(
-            ctx.guild_id().ok_or(internal_err(
-                "This command should be executed only in a guild",
-            ))?,
-            &slug,
-        )
+        .delete_exchange(require_guild(ctx.guild_id())?, &slug)
         .await;
 
     match deletion_result {
@@ -0,0 +1,73 @@
+use poise::{ChoiceParameter, CreateReply};
+use serenity::all::CreateAttachment;
+
+use crate::{
+    commands::{internal_err, require_guild, user_err, ApplicationContext, CommandResult},
+    utils::csv::{assignments_csv, submissions_csv},
+};
+
+#[derive(ChoiceParameter, Copy, Clone, Debug)]
+pub enum ExportKind {
+    #[name = "Submissions"]
+    Submissions,
+    #[name = "Assignments"]
+    Assignments,
+}
+
+/// Export exchange submissions or assignments as a CSV file.
+#[poise::command(slash_command, rename = "export")]
+pub async fn export(
+    ctx: ApplicationContext<'_>,
+    #[description = "Exchange slug"] slug: String,
+    #[description = "What to export"] what: ExportKind,
+) -> CommandResult {
+    let exchange = ctx
+        .data
+        .exchange_repository
+        .get_exchange_by_slug(require_guild(ctx.guild_id())?, &slug)
+        .await
+        .map_err(|err| internal_err(format!("Could not get the exchange: {err}")))?
+        .ok_or(user_err(format!(
+            "Exchange with slug `{slug}` does not exist"
+        )))?;
+
+    let (csv, file_name) = match what {
+        ExportKind::Submissions => {
+            let submissions = ctx
+                .data
+                .submission_repository
+                .get_submissions_for_exchange(exchange.id)
+                .await
+                .map_err(|err| internal_err(format!("Could not get the submissions: {err}")))?;
+
+            (
+                submissions_csv(&submissions),
+                format!("{slug}-submissions.csv"),
+            )
+        }
+
+        ExportKind::Assignments => {
+            let assignments = ctx
+                .data
+                .assignment_repository
+                .get_assignments_for_exchange(exchange.id)
+                .await
+                .map_err(|err| internal_err(format!("Could not get the assignments: {err}")))?;
+
+            (
+                assignments_csv(&assignments),
+                format!("{slug}-assignments.csv"),
+            )
+        }
+    };
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!("# {} of {}", what.name(), exchange.display_name))
+            .attachment(CreateAttachment::bytes(csv.into_bytes(), file_name))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
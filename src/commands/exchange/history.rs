@@ -0,0 +1,118 @@
+use poise::CreateReply;
+use serenity::all::{Mentionable, User};
+
+use crate::{
+    commands::{internal_err, require_guild, ApplicationContext, CommandResult},
+    models::SubmissionHistoryEntry,
+    utils::formatting::{format_utc, MAX_MESSAGE_LENGTH},
+};
+
+/// Show which exchanges of this server a member submitted to.
+#[poise::command(slash_command, rename = "history")]
+pub async fn history(
+    ctx: ApplicationContext<'_>,
+    #[description = "The member to show the history of"] user: User,
+) -> CommandResult {
+    let guild_id = require_guild(ctx.guild_id())?;
+
+    let entries = ctx
+        .data
+        .submission_repository
+        .get_submissions_by_user(guild_id, user.id)
+        .await
+        .map_err(|err| internal_err(format!("Could not get the submissions: {err}")))?;
+
+    let message = history_message(&user.mention().to_string(), &entries);
+
+    ctx.send(CreateReply::default().ephemeral(true).content(message))
+        .await?;
+
+    Ok(())
+}
+
+fn history_message(member: &str, entries: &[SubmissionHistoryEntry]) -> String {
+    if entries.is_empty() {
+        return format!("# {member} has not submitted to any exchanges");
+    }
+
+    let mut message = format!("# Submissions of {member}\n");
+
+    for (index, entry) in entries.iter().enumerate() {
+        let line = format!(
+            " - `{slug}`: {link} (submitted {time} UTC)\n",
+            slug = entry.exchange_slug,
+            link = entry.link,
+            time = format_utc(entry.submitted_at),
+        );
+
+        // Room for the note is only needed if there are entries after this one to cut off
+        let left_after = entries.len() - index - 1;
+        let reserved = if left_after > 0 {
+            omitted_note(left_after).len()
+        } else {
+            0
+        };
+
+        if message.len() + line.len() + reserved > MAX_MESSAGE_LENGTH {
+            message += &omitted_note(entries.len() - index);
+            break;
+        }
+
+        message += &line;
+    }
+
+    message
+}
+
+fn omitted_note(omitted: usize) -> String {
+    format!("...and {omitted} more")
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use crate::models::{types::UtcDateTime, SubmissionHistoryEntry};
+
+    use super::{history_message, MAX_MESSAGE_LENGTH};
+
+    fn entry(index: usize) -> SubmissionHistoryEntry {
+        SubmissionHistoryEntry {
+            exchange_slug: format!("Exchange{index}"),
+            link: format!("https://itch.io/jam/example-jam/rate/{index}"),
+            submitted_at: UtcDateTime::assume_utc(datetime!(2024-01-01 12:00:00)),
+        }
+    }
+
+    #[test]
+    fn empty_history() {
+        assert_eq!(
+            history_message("@member", &[]),
+            "# @member has not submitted to any exchanges"
+        );
+    }
+
+    #[test]
+    fn long_history_is_truncated() {
+        let entries = (0..100).map(entry).collect::<Vec<_>>();
+
+        let message = history_message("@member", &entries);
+
+        assert!(message.len() <= MAX_MESSAGE_LENGTH);
+        assert!(message.contains("`Exchange0`"));
+        assert!(message.ends_with("more"));
+    }
+
+    #[test]
+    fn history_filling_the_message_is_not_truncated() {
+        let mut last = entry(1);
+        let unpadded = history_message("@member", &[entry(0), last.clone()]).len();
+        last.link += &"0".repeat(MAX_MESSAGE_LENGTH - unpadded);
+
+        let message = history_message("@member", &[entry(0), last.clone()]);
+
+        assert_eq!(message.len(), MAX_MESSAGE_LENGTH);
+        assert!(message.contains(&last.link));
+        assert!(!message.contains("more"));
+    }
+}
@@ -0,0 +1,115 @@
+use poise::serenity_prelude::Mentionable;
+use poise::CreateReply;
+use serenity::all::Color;
+use serenity::builder::CreateEmbed;
+
+use crate::{
+    commands::{internal_err, require_guild, user_err, ApplicationContext, CommandResult},
+    models::Exchange,
+    utils::formatting::{format_local, format_utc},
+};
+
+use super::create::{extra_jams_description, settings_fields};
+
+#[poise::command(slash_command, rename = "info")]
+pub async fn info(
+    ctx: ApplicationContext<'_>,
+    #[description = "Exchange slug"] slug: String,
+) -> CommandResult {
+    let exchange = ctx
+        .data
+        .exchange_repository
+        .get_exchange_by_slug(require_guild(ctx.guild_id())?, &slug)
+        .await
+        .map_err(|err| internal_err(format!("Could not get the exchange: {err}")))?
+        .ok_or(user_err(format!(
+            "Exchange with slug `{slug}` does not exist"
+        )))?;
+
+    ctx.send(
+        CreateReply::default()
+            .embed(exchange_embed(&exchange))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
+
+fn exchange_embed(exchange: &Exchange) -> CreateEmbed {
+    let embed = CreateEmbed::default()
+        .title(&exchange.display_name)
+        .color(Color::BLUE)
+        .field("Jam type", exchange.jam_type.name(), true)
+        .field("Jam link", &exchange.jam_link, true)
+        .field(
+            "Extra jams",
+            extra_jams_description(&exchange.extra_jams),
+            false,
+        )
+        .field(
+            "Submission channel",
+            exchange.channel.mention().to_string(),
+            false,
+        )
+        .field(
+            "State",
+            format!(
+                "**{label}**\n{description}",
+                label = exchange.state_label(),
+                description = exchange.state.description(),
+            ),
+            false,
+        )
+        .field(
+            "Start",
+            format!(
+                "{local} your time or {utc} UTC",
+                local = format_local(exchange.submissions_start),
+                utc = format_utc(exchange.submissions_start),
+            ),
+            false,
+        )
+        .field(
+            "End",
+            format!(
+                "{local} your time or {utc} UTC",
+                local = format_local(exchange.submissions_end),
+                utc = format_utc(exchange.submissions_end),
+            ),
+            false,
+        );
+
+    settings_fields(embed, exchange.into())
+        .field("Created", creation_description(exchange), false)
+        .field(
+            "Assignments sent",
+            match exchange.assignments_sent_at {
+                Some(sent_at) => format!(
+                    "{local} your time or {utc} UTC",
+                    local = format_local(sent_at),
+                    utc = format_utc(sent_at),
+                ),
+                None => "Not yet".to_string(),
+            },
+            false,
+        )
+}
+
+fn creation_description(exchange: &Exchange) -> String {
+    let by = exchange
+        .created_by
+        .map(|user| format!("by {}", user.mention()));
+    let at = exchange.created_at.map(|created_at| {
+        format!(
+            "at {local} your time or {utc} UTC",
+            local = format_local(created_at),
+            utc = format_utc(created_at),
+        )
+    });
+
+    match (by, at) {
+        (None, None) => "Unknown".to_string(),
+        (by, at) => [by, at].into_iter().flatten().collect::<Vec<_>>().join(" "),
+    }
+}
@@ -0,0 +1,53 @@
+use poise::CreateReply;
+
+use crate::commands::{internal_err, require_guild, ApplicationContext, CommandResult};
+
+#[poise::command(slash_command, rename = "jams")]
+pub async fn jams(ctx: ApplicationContext<'_>) -> CommandResult {
+    let active_jams = ctx
+        .data
+        .exchange_repository
+        .get_active_jams_in_guild(require_guild(ctx.guild_id())?)
+        .await;
+
+    match active_jams {
+        Ok(jams) if jams.is_empty() => {
+            ctx.send(
+                CreateReply::default()
+                    .content("# There are no jams with active exchanges")
+                    .ephemeral(true),
+            )
+            .await?;
+        }
+
+        Ok(jams) => {
+            let list = jams.iter().fold(String::new(), |acc, jam| {
+                let exchanges = if jam.active_exchanges == 1 {
+                    "exchange"
+                } else {
+                    "exchanges"
+                };
+
+                acc + &format!(
+                    " - <{}> - {} active {exchanges}\n",
+                    jam.jam_link, jam.active_exchanges,
+                )
+            });
+
+            ctx.send(
+                CreateReply::default()
+                    .content(&format!("# Jams with active exchanges:\n{list}"))
+                    .ephemeral(true),
+            )
+            .await?;
+        }
+
+        Err(err) => {
+            return Err(internal_err(&format!(
+                "Could not get the active jams: {err}"
+            )));
+        }
+    }
+
+    Ok(())
+}
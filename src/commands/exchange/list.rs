@@ -2,7 +2,7 @@ use poise::CreateReply;
 use time::OffsetDateTime;
 
 use crate::{
-    commands::{internal_err, ApplicationContext, CommandResult},
+    commands::{internal_err, require_guild, ApplicationContext, CommandResult},
     utils::formatting::format_utc,
 };
 
@@ -12,9 +12,7 @@ pub async fn list(ctx: ApplicationContext<'_>) -> CommandResult {
         .data
         .exchange_repository
         .get_upcoming_exchanges_in_guild(
-            ctx.guild_id().ok_or(internal_err(
-                "This command should be executed only in a guild",
-            ))?,
+            require_guild(ctx.guild_id())?,
             OffsetDateTime::now_utc().into(),
         )
         .await;
@@ -32,11 +30,12 @@ pub async fn list(ctx: ApplicationContext<'_>) -> CommandResult {
         Ok(exchanges) => {
             let list = exchanges.iter().fold(String::new(), |acc, exchange| {
                 acc + &format!(
-                    " - **{}** (slug: `{}`) - runs from {} UTC to {} UTC\n",
+                    " - **{}** (slug: `{}`) - runs from {} UTC to {} UTC ({})\n",
                     exchange.display_name,
                     exchange.slug,
                     format_utc(exchange.submissions_start),
                     format_utc(exchange.submissions_end),
+                    exchange.state_label(),
                 )
             });
 
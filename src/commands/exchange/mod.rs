@@ -1,13 +1,33 @@
+mod announce;
+mod assign;
+mod backfill;
 mod create;
 mod delete;
+mod export;
+mod history;
+mod info;
+mod jams;
 mod list;
+mod verify_links;
 
 use super::{user_err, CommandResult, Context};
 
 #[poise::command(
     slash_command,
     guild_only,
-    subcommands("create::create", "list::list", "delete::delete"),
+    subcommands(
+        "create::create",
+        "list::list",
+        "jams::jams",
+        "info::info",
+        "export::export",
+        "assign::assign",
+        "announce::announce",
+        "backfill::backfill",
+        "history::history",
+        "verify_links::verify_links",
+        "delete::delete"
+    ),
     required_permissions = "ADMINISTRATOR",
     default_member_permissions = "ADMINISTRATOR"
 )]
@@ -0,0 +1,190 @@
+use std::time::Duration;
+
+use poise::CreateReply;
+use reqwest::{Client, StatusCode};
+
+use crate::commands::{internal_err, require_guild, user_err, ApplicationContext, CommandResult};
+
+const LINK_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Check that the exchange's jam links can be opened.
+#[poise::command(slash_command, rename = "verify-links")]
+pub async fn verify_links(
+    ctx: ApplicationContext<'_>,
+    #[description = "Exchange slug"] slug: String,
+) -> CommandResult {
+    let exchange = ctx
+        .data
+        .exchange_repository
+        .get_exchange_by_slug(require_guild(ctx.guild_id())?, &slug)
+        .await
+        .map_err(|err| internal_err(format!("Could not get the exchange: {err}")))?
+        .ok_or(user_err(format!(
+            "Exchange with slug `{slug}` does not exist"
+        )))?;
+
+    // The checks can take up to the timeout, longer than Discord waits for a response.
+    ctx.defer_ephemeral().await?;
+
+    let client = Client::builder()
+        .timeout(LINK_CHECK_TIMEOUT)
+        .build()
+        .map_err(|err| internal_err(format!("Could not create an HTTP client: {err}")))?;
+
+    let mut message = format!("# Jam links of {}\n", exchange.display_name);
+    for jam in exchange.accepted_jams() {
+        let status = check_link(&client, &jam.jam_link).await;
+        message += &format!(" - {}: {}\n", jam.jam_link, status.describe());
+    }
+
+    ctx.send(CreateReply::default().ephemeral(true).content(message))
+        .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum LinkStatus {
+    Reachable,
+    NotFound,
+    UnexpectedStatus(StatusCode),
+    Error(String),
+}
+
+impl LinkStatus {
+    fn describe(&self) -> String {
+        match self {
+            LinkStatus::Reachable => "reachable".to_string(),
+            LinkStatus::NotFound => "**not found (404)**".to_string(),
+            LinkStatus::UnexpectedStatus(status) => format!("**unexpected status {status}**"),
+            LinkStatus::Error(err) => format!("**could not be checked: {err}**"),
+        }
+    }
+}
+
+/// Requests the link with HEAD, falling back to GET for servers that don't support HEAD.
+async fn check_link(client: &Client, link: &str) -> LinkStatus {
+    let response = match client.head(link).send().await {
+        Ok(response) if response.status() == StatusCode::METHOD_NOT_ALLOWED => {
+            client.get(link).send().await
+        }
+        response => response,
+    };
+
+    match response {
+        Ok(response) => match response.status() {
+            status if status.is_success() => LinkStatus::Reachable,
+            StatusCode::NOT_FOUND => LinkStatus::NotFound,
+            status => LinkStatus::UnexpectedStatus(status),
+        },
+        Err(err) => LinkStatus::Error(err.without_url().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread,
+    };
+
+    use reqwest::{Client, StatusCode};
+
+    use super::{check_link, LinkStatus};
+
+    /// Answers each request with the status listed for its method and path, or 404.
+    fn mock_server(routes: &'static [(&'static str, &'static str)]) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                reader.read_line(&mut request_line).unwrap();
+
+                // Skip the headers
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap_or_default();
+                let path = parts.next().unwrap_or_default();
+
+                let status = routes
+                    .iter()
+                    .find(|(route, _)| format!("{method} {path}") == *route)
+                    .map(|(_, status)| *status)
+                    .unwrap_or("404 Not Found");
+
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                );
+            }
+        });
+
+        format!("http://{address}")
+    }
+
+    #[tokio::test]
+    async fn reachable_link() {
+        let server = mock_server(&[("HEAD /jam/example-jam", "200 OK")]);
+
+        let status = check_link(&Client::new(), &format!("{server}/jam/example-jam")).await;
+
+        assert_eq!(status, LinkStatus::Reachable);
+    }
+
+    #[tokio::test]
+    async fn missing_link() {
+        let server = mock_server(&[]);
+
+        let status = check_link(&Client::new(), &format!("{server}/jam/typo-jam")).await;
+
+        assert_eq!(status, LinkStatus::NotFound);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_get() {
+        let server = mock_server(&[
+            ("HEAD /jam/example-jam", "405 Method Not Allowed"),
+            ("GET /jam/example-jam", "200 OK"),
+        ]);
+
+        let status = check_link(&Client::new(), &format!("{server}/jam/example-jam")).await;
+
+        assert_eq!(status, LinkStatus::Reachable);
+    }
+
+    #[tokio::test]
+    async fn unexpected_status() {
+        let server = mock_server(&[("HEAD /jam/example-jam", "503 Service Unavailable")]);
+
+        let status = check_link(&Client::new(), &format!("{server}/jam/example-jam")).await;
+
+        assert_eq!(
+            status,
+            LinkStatus::UnexpectedStatus(StatusCode::SERVICE_UNAVAILABLE)
+        );
+    }
+
+    #[tokio::test]
+    async fn unreachable_server() {
+        let address = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap()
+        };
+
+        let status = check_link(&Client::new(), &format!("http://{address}/jam")).await;
+
+        assert!(matches!(status, LinkStatus::Error(_)));
+    }
+}
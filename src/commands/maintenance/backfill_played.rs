@@ -0,0 +1,27 @@
+use poise::CreateReply;
+
+use crate::commands::{internal_err, require_guild, ApplicationContext, CommandResult};
+
+/// Mark entries assigned in past exchanges as played, so they aren't assigned again.
+#[poise::command(slash_command, rename = "backfill_played")]
+pub async fn backfill_played(ctx: ApplicationContext<'_>) -> CommandResult {
+    let guild = require_guild(ctx.guild_id())?;
+
+    let inserted = ctx
+        .data
+        .played_game_repository
+        .backfill_from_assignments(guild)
+        .await
+        .map_err(|err| internal_err(format!("Could not backfill played games: {err}")))?;
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!(
+                "# Backfill complete\n\nRecorded {inserted} previously assigned entries as played."
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,14 @@
+mod backfill_played;
+
+use super::{user_err, CommandResult, Context};
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands("backfill_played::backfill_played"),
+    required_permissions = "ADMINISTRATOR",
+    default_member_permissions = "ADMINISTRATOR"
+)]
+pub async fn maintenance(_ctx: Context<'_>) -> CommandResult {
+    Err(user_err("The `/maintenance` command is not supported yet"))
+}
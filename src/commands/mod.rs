@@ -1,17 +1,31 @@
 mod camel_slug;
+mod confirm_dialog;
+mod submission_channel;
 
 mod arguments;
 mod exchange;
+mod maintenance;
+mod mystatus;
 mod played;
+mod ready;
 mod revoke;
+mod settings;
 mod submit;
+mod whoami;
+
+use poise::serenity_prelude::GuildId;
 
 use crate::BotState;
 
 pub use exchange::exchange;
+pub use maintenance::maintenance;
+pub use mystatus::mystatus;
 pub use played::played;
+pub use ready::ready;
 pub use revoke::revoke;
+pub use settings::settings;
 pub use submit::submit;
+pub use whoami::whoami;
 
 type CommandResult = Result<(), CommandError>;
 type Context<'a> = poise::Context<'a, BotState, CommandError>;
@@ -38,3 +52,31 @@ fn internal_err(message: impl Into<String>) -> CommandError {
         message: message.into(),
     }
 }
+
+pub const GUILD_ONLY_MESSAGE: &str = "Sorry, but you can only run this command in a server.";
+
+/// Guild-only commands should always have a guild, but if one still runs elsewhere, the member
+/// is told where to use it rather than getting an internal error.
+fn require_guild(guild_id: Option<GuildId>) -> Result<GuildId, CommandError> {
+    guild_id.ok_or_else(|| user_err(GUILD_ONLY_MESSAGE))
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::GuildId;
+
+    use super::{require_guild, CommandError, GUILD_ONLY_MESSAGE};
+
+    #[test]
+    fn guild_is_required() {
+        assert_eq!(
+            require_guild(Some(GuildId::new(1))).unwrap(),
+            GuildId::new(1)
+        );
+
+        match require_guild(None) {
+            Err(CommandError::User { message }) => assert_eq!(message, GUILD_ONLY_MESSAGE),
+            other => panic!("Expected a user error, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use indoc::formatdoc;
+use poise::CreateReply;
+use time::OffsetDateTime;
+
+use crate::{
+    commands::{internal_err, require_guild, user_err, ApplicationContext, CommandResult},
+    models::{types::UtcDateTime, ExchangeState},
+};
+
+/// Numbers explaining how many entries a member can be assigned in an exchange.
+#[derive(Debug, PartialEq, Eq)]
+struct MemberStatus {
+    entries: u32,
+    played: u32,
+    has_submitted: bool,
+    ignore_played: bool,
+}
+
+impl MemberStatus {
+    /// Entries that can still be assigned to the member: everything except their own entry
+    /// and, unless the exchange ignores them, the ones they have already played. Only
+    /// submitters get assignments.
+    fn assignable(&self) -> u32 {
+        if !self.has_submitted {
+            return 0;
+        }
+
+        let played = if self.ignore_played { 0 } else { self.played };
+
+        self.entries.saturating_sub(1).saturating_sub(played)
+    }
+}
+
+#[poise::command(slash_command, rename = "mystatus")]
+pub async fn mystatus(ctx: ApplicationContext<'_>) -> CommandResult {
+    let user = ctx.author().id;
+
+    let exchange = {
+        let guild_id = require_guild(ctx.guild_id())?;
+        let now = UtcDateTime::from(OffsetDateTime::now_utc());
+
+        ctx.data
+            .exchange_repository
+            .get_exchange_in_window(guild_id, ctx.channel_id(), now)
+            .await
+            .map_err(|err| internal_err(format!("Could not get exchanges: {err}")))?
+            .ok_or(user_err(
+                "# There are no currently active exchanges in this channel",
+            ))?
+    };
+
+    let submission = ctx
+        .data
+        .submission_repository
+        .get_submission_by_submitter(exchange.id, user)
+        .await
+        .map_err(|err| internal_err(format!("Could not get your submission: {err}")))?;
+
+    let status = MemberStatus {
+        entries: ctx
+            .data
+            .submission_repository
+            .count_submissions(exchange.id)
+            .await
+            .map_err(|err| internal_err(format!("Could not count submissions: {err}")))?,
+        played: ctx
+            .data
+            .played_game_repository
+            .count_played_entries(exchange.id, user)
+            .await
+            .map_err(|err| internal_err(format!("Could not count played entries: {err}")))?,
+        has_submitted: submission.is_some(),
+        ignore_played: exchange.ignore_played,
+    };
+
+    let submission_line = match &submission {
+        Some(submission) => format!("Your submission: `{}`", submission.link),
+        None => "You haven't submitted an entry.".to_string(),
+    };
+
+    let assignments_line = if exchange.state == ExchangeState::AssignmentsSent {
+        let received = ctx
+            .data
+            .assignment_repository
+            .count_assignments_for_reviewer(exchange.id, user)
+            .await
+            .map_err(|err| internal_err(format!("Could not count assignments: {err}")))?;
+
+        format!("You received {received} assignments.")
+    } else {
+        "Assignments haven't been sent yet.".to_string()
+    };
+
+    let message = formatdoc! {
+        r#"
+            # Your status in {name}
+
+            {submission_line}
+
+             - Entries submitted: {entries}
+             - Entries you have already played: {played}
+             - Entries that can be assigned to you: {assignable}
+
+            {assignments_line}
+        "#,
+        name = exchange.display_name,
+        entries = status.entries,
+        played = status.played,
+        assignable = status.assignable(),
+    };
+
+    ctx.send(CreateReply::default().ephemeral(true).content(message))
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemberStatus;
+
+    #[test]
+    fn assignable_excludes_own_and_played() {
+        let status = MemberStatus {
+            entries: 10,
+            played: 3,
+            has_submitted: true,
+            ignore_played: false,
+        };
+
+        assert_eq!(status.assignable(), 6);
+    }
+
+    #[test]
+    fn assignable_without_submission() {
+        let status = MemberStatus {
+            entries: 10,
+            played: 3,
+            has_submitted: false,
+            ignore_played: false,
+        };
+
+        assert_eq!(status.assignable(), 0);
+    }
+
+    #[test]
+    fn assignable_never_negative() {
+        let status = MemberStatus {
+            entries: 1,
+            played: 0,
+            has_submitted: true,
+            ignore_played: false,
+        };
+
+        assert_eq!(status.assignable(), 0);
+    }
+
+    #[test]
+    fn assignable_ignoring_played() {
+        let status = MemberStatus {
+            entries: 10,
+            played: 3,
+            has_submitted: true,
+            ignore_played: true,
+        };
+
+        assert_eq!(status.assignable(), 9);
+    }
+}
@@ -16,7 +16,8 @@ pub async fn played(
 ) -> CommandResult {
     let user = ctx.author().id;
 
-    if JamType::iter().all(|jam_type| !jam_type.validate_entry_link(&link)) {
+    let event_types = &ctx.data.ludum_dare_event_types;
+    if JamType::iter().all(|jam_type| !jam_type.validate_entry_link(&link, event_types)) {
         return Err(user_err(
             "Invalid entry link, does not match any of known jams",
         ));
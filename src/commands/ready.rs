@@ -0,0 +1,45 @@
+use indoc::formatdoc;
+use poise::CreateReply;
+use time::OffsetDateTime;
+
+use crate::{
+    commands::{internal_err, require_guild, user_err, ApplicationContext, CommandResult},
+    models::types::UtcDateTime,
+};
+
+/// Confirm that you can receive your assignments in DMs.
+#[poise::command(slash_command, rename = "ready")]
+pub async fn ready(ctx: ApplicationContext<'_>) -> CommandResult {
+    let guild_id = require_guild(ctx.guild_id())?;
+    let now = UtcDateTime::from(OffsetDateTime::now_utc());
+
+    let exchange = ctx
+        .data
+        .exchange_repository
+        .get_running_exchange(guild_id, ctx.channel_id(), now)
+        .await
+        .map_err(|err| internal_err(format!("Could not get exchanges: {err}")))?
+        .ok_or(user_err(
+            "# There are no currently active exchanges in this channel",
+        ))?;
+
+    ctx.data
+        .ack_repository
+        .add_ack(exchange.id, ctx.author().id, now)
+        .await
+        .map_err(|err| internal_err(format!("Could not record your acknowledgement: {err}")))?;
+
+    let message = formatdoc! {
+        r#"
+            # You're ready!
+
+            Make sure your DMs stay open for members of this server, so you can receive your assignments for {name}.
+        "#,
+        name = exchange.display_name,
+    };
+
+    ctx.send(CreateReply::default().ephemeral(true).content(message))
+        .await?;
+
+    Ok(())
+}
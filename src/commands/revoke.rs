@@ -4,16 +4,21 @@ use time::OffsetDateTime;
 use tracing::debug;
 
 use crate::{
-    commands::{internal_err, user_err, ApplicationContext, CommandResult},
-    models::types::UtcDateTime,
+    commands::{
+        internal_err, require_guild, user_err, ApplicationContext, CommandError, CommandResult,
+    },
+    models::{types::UtcDateTime, Exchange},
+    utils::formatting::{format_local, format_utc},
 };
 
 #[poise::command(slash_command, rename = "revoke")]
 pub async fn revoke(ctx: ApplicationContext<'_>) -> CommandResult {
+    let now = OffsetDateTime::now_utc();
+
     let exchange = {
-        let guild_id = ctx.guild_id().ok_or(internal_err("Guild id not found"))?;
+        let guild_id = require_guild(ctx.guild_id())?;
         let channel_id = ctx.channel_id();
-        let now = UtcDateTime::from(OffsetDateTime::now_utc());
+        let now = UtcDateTime::from(now);
 
         debug!("Guild ID: {guild_id}, channel ID: {channel_id}, now: {now:?}");
 
@@ -44,6 +49,8 @@ pub async fn revoke(ctx: ApplicationContext<'_>) -> CommandResult {
         }
     };
 
+    check_revoke_lock(&exchange, now)?;
+
     let user = ctx.author().id;
 
     match ctx
@@ -79,3 +86,67 @@ pub async fn revoke(ctx: ApplicationContext<'_>) -> CommandResult {
         ))),
     }
 }
+
+fn check_revoke_lock(exchange: &Exchange, now: OffsetDateTime) -> Result<(), CommandError> {
+    if exchange.revoke_lock.is_zero() {
+        return Ok(());
+    }
+
+    let submissions_end = OffsetDateTime::from(exchange.submissions_end);
+    if now >= submissions_end - exchange.revoke_lock {
+        let message = formatdoc! {
+            r#"
+                # Submissions to {name} can no longer be revoked
+
+                Revoking is locked during the last {lock} before submissions end at {end_local} (your local time) / {end_utc} UTC.
+            "#,
+            name = exchange.display_name,
+            lock = exchange.revoke_lock,
+            end_local = format_local(exchange.submissions_end),
+            end_utc = format_utc(exchange.submissions_end),
+        };
+        return Err(user_err(message));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{macros::datetime, Duration};
+
+    use crate::models::{test_exchange, types::UtcDateTime, Exchange};
+
+    use super::check_revoke_lock;
+
+    fn exchange(revoke_lock: Duration) -> Exchange {
+        Exchange {
+            display_name: "Test Exchange".to_string(),
+            submissions_end: UtcDateTime::assume_utc(datetime!(2024-01-02 12:00:00)),
+            revoke_lock,
+            ..test_exchange()
+        }
+    }
+
+    #[test]
+    fn revoke_allowed_before_lock() {
+        let exchange = exchange(Duration::hours(1));
+
+        assert!(check_revoke_lock(&exchange, datetime!(2024-01-02 10:59:59 UTC)).is_ok());
+    }
+
+    #[test]
+    fn revoke_locked_near_deadline() {
+        let exchange = exchange(Duration::hours(1));
+
+        assert!(check_revoke_lock(&exchange, datetime!(2024-01-02 11:00:00 UTC)).is_err());
+        assert!(check_revoke_lock(&exchange, datetime!(2024-01-02 11:59:00 UTC)).is_err());
+    }
+
+    #[test]
+    fn no_lock_by_default() {
+        let exchange = exchange(Duration::ZERO);
+
+        assert!(check_revoke_lock(&exchange, datetime!(2024-01-02 11:59:59 UTC)).is_ok());
+    }
+}
@@ -0,0 +1,35 @@
+use poise::CreateReply;
+use serenity::all::{Channel, Mentionable};
+
+use crate::commands::{
+    internal_err, require_guild, submission_channel::assignment_log_channel, ApplicationContext,
+    CommandResult,
+};
+
+/// Log the outcome of each assignment pass in a channel or thread, e.g. one only moderators see.
+#[poise::command(slash_command, rename = "assignment_log")]
+pub async fn assignment_log(
+    ctx: ApplicationContext<'_>,
+    #[description = "The channel or thread to log assignments in. Leave empty to stop logging."]
+    channel: Option<Channel>,
+) -> CommandResult {
+    let guild = require_guild(ctx.guild_id())?;
+
+    let channel = channel.map(assignment_log_channel).transpose()?;
+
+    ctx.data
+        .guild_settings_repository
+        .set_assignment_log_channel(guild, channel.as_ref().map(|channel| channel.id))
+        .await
+        .map_err(|err| internal_err(format!("Could not update the settings: {err}")))?;
+
+    let message = match channel {
+        Some(channel) => format!("# Assignments will be logged in {}", channel.mention()),
+        None => "# Assignments will no longer be logged".to_string(),
+    };
+
+    ctx.send(CreateReply::default().content(message).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use poise::CreateReply;
+
+use crate::commands::{internal_err, require_guild, ApplicationContext, CommandResult};
+
+/// Allow or forbid bot accounts to submit entries in this server.
+#[poise::command(slash_command, rename = "bot_submissions")]
+pub async fn bot_submissions(
+    ctx: ApplicationContext<'_>,
+    #[description = "Whether bot accounts can submit entries"] allow: bool,
+) -> CommandResult {
+    let guild = require_guild(ctx.guild_id())?;
+
+    ctx.data
+        .guild_settings_repository
+        .set_allow_bot_submissions(guild, allow)
+        .await
+        .map_err(|err| internal_err(format!("Could not update the settings: {err}")))?;
+
+    let message = if allow {
+        "# Bot accounts can now submit entries"
+    } else {
+        "# Bot accounts can no longer submit entries"
+    };
+
+    ctx.send(CreateReply::default().content(message).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,20 @@
+mod assignment_log;
+mod bot_submissions;
+mod slug_pattern;
+
+use super::{user_err, CommandResult, Context};
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    subcommands(
+        "bot_submissions::bot_submissions",
+        "slug_pattern::slug_pattern",
+        "assignment_log::assignment_log"
+    ),
+    required_permissions = "ADMINISTRATOR",
+    default_member_permissions = "ADMINISTRATOR"
+)]
+pub async fn settings(_ctx: Context<'_>) -> CommandResult {
+    Err(user_err("The `/settings` command is not supported yet"))
+}
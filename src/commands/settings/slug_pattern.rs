@@ -0,0 +1,45 @@
+use poise::CreateReply;
+
+use crate::commands::{
+    arguments::slug_pattern_regex, internal_err, require_guild, user_err, ApplicationContext,
+    CommandResult,
+};
+
+/// Require exchange slugs in this server to match a pattern, e.g. to start with the year.
+#[poise::command(slash_command, rename = "slug_pattern")]
+pub async fn slug_pattern(
+    ctx: ApplicationContext<'_>,
+    #[description = "A regular expression slugs must match as a whole, e.g. `2024-.+`. Leave empty to remove."]
+    pattern: Option<String>,
+) -> CommandResult {
+    let guild = require_guild(ctx.guild_id())?;
+
+    let pattern = pattern
+        .as_deref()
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty());
+
+    if let Some(pattern) = pattern {
+        slug_pattern_regex(pattern).map_err(|err| {
+            user_err(format!(
+                "Invalid slug pattern `{pattern}`:\n```\n{err}\n```"
+            ))
+        })?;
+    }
+
+    ctx.data
+        .guild_settings_repository
+        .set_slug_pattern(guild, pattern)
+        .await
+        .map_err(|err| internal_err(format!("Could not update the settings: {err}")))?;
+
+    let message = match pattern {
+        Some(pattern) => format!("# Exchange slugs now have to match `{pattern}`"),
+        None => "# Exchange slugs no longer have to match a pattern".to_string(),
+    };
+
+    ctx.send(CreateReply::default().content(message).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
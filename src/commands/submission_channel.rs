@@ -0,0 +1,208 @@
+use poise::serenity_prelude::{Channel, ChannelType, GuildChannel};
+
+use super::{user_err, CommandError};
+
+/// What kind of channel an admin has picked for an exchange.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelClass {
+    /// A text-based guild channel where the bot can post and members can submit.
+    Suitable,
+    Category,
+    Voice,
+    Stage,
+    Forum,
+    NotInGuild,
+    Other,
+}
+
+/// What the picked channel is going to be used for, to explain why it was rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelPurpose {
+    Exchange,
+    AssignmentLog,
+}
+
+impl ChannelPurpose {
+    fn description(&self) -> &'static str {
+        match self {
+            ChannelPurpose::Exchange => "an exchange",
+            ChannelPurpose::AssignmentLog => "the assignment log",
+        }
+    }
+}
+
+impl ChannelClass {
+    fn rejection_message(&self, purpose: ChannelPurpose) -> Option<String> {
+        use ChannelClass::*;
+
+        let purpose = purpose.description();
+        match self {
+            Suitable => None,
+            Category => Some(format!("A category can't be used for {purpose}. Please pick a text channel inside it.")),
+            Voice => Some(format!("A voice channel can't be used for {purpose}. Please pick a text channel.")),
+            Stage => Some(format!("A stage channel can't be used for {purpose}. Please pick a text channel.")),
+            Forum => Some(format!("A forum can't be used for {purpose}. Please pick a text channel or a thread in the forum.")),
+            NotInGuild => Some(format!("The channel for {purpose} must be a channel in this server.")),
+            Other => Some(format!("This kind of channel can't be used for {purpose}. Please pick a text channel.")),
+        }
+    }
+}
+
+pub fn classify_channel(channel: &Channel) -> ChannelClass {
+    match channel {
+        Channel::Guild(channel) => classify_channel_type(channel.kind),
+        _ => ChannelClass::NotInGuild,
+    }
+}
+
+fn classify_channel_type(kind: ChannelType) -> ChannelClass {
+    use ChannelType::*;
+
+    match kind {
+        Text | News | PublicThread | PrivateThread | NewsThread => ChannelClass::Suitable,
+        Category => ChannelClass::Category,
+        Voice => ChannelClass::Voice,
+        Stage => ChannelClass::Stage,
+        Forum => ChannelClass::Forum,
+        Private | GroupDm => ChannelClass::NotInGuild,
+        _ => ChannelClass::Other,
+    }
+}
+
+/// Extracts the guild channel to run an exchange in, rejecting unsuitable channels.
+pub fn submission_channel(channel: Channel) -> Result<GuildChannel, CommandError> {
+    suitable_channel(channel, ChannelPurpose::Exchange)
+}
+
+/// Extracts the guild channel to log assignments in, rejecting unsuitable channels.
+pub fn assignment_log_channel(channel: Channel) -> Result<GuildChannel, CommandError> {
+    suitable_channel(channel, ChannelPurpose::AssignmentLog)
+}
+
+fn suitable_channel(
+    channel: Channel,
+    purpose: ChannelPurpose,
+) -> Result<GuildChannel, CommandError> {
+    if let Some(message) = classify_channel(&channel).rejection_message(purpose) {
+        return Err(user_err(message));
+    }
+
+    match channel {
+        Channel::Guild(channel) => Ok(channel),
+        _ => Err(user_err(
+            ChannelClass::NotInGuild
+                .rejection_message(purpose)
+                .expect("Only suitable channels have no rejection message"),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::{Channel, ChannelType, GuildChannel, PrivateChannel};
+
+    use super::{
+        assignment_log_channel, classify_channel, submission_channel, ChannelClass, ChannelPurpose,
+    };
+
+    fn guild_channel(kind: ChannelType) -> Channel {
+        let mut channel = GuildChannel::default();
+        channel.kind = kind;
+        Channel::Guild(channel)
+    }
+
+    #[test]
+    fn text_channel() {
+        assert_eq!(
+            classify_channel(&guild_channel(ChannelType::Text)),
+            ChannelClass::Suitable
+        );
+        assert!(submission_channel(guild_channel(ChannelType::Text)).is_ok());
+    }
+
+    #[test]
+    fn news_channel() {
+        assert_eq!(
+            classify_channel(&guild_channel(ChannelType::News)),
+            ChannelClass::Suitable
+        );
+    }
+
+    #[test]
+    fn thread() {
+        assert_eq!(
+            classify_channel(&guild_channel(ChannelType::PublicThread)),
+            ChannelClass::Suitable
+        );
+    }
+
+    #[test]
+    fn category() {
+        assert_eq!(
+            classify_channel(&guild_channel(ChannelType::Category)),
+            ChannelClass::Category
+        );
+        assert!(submission_channel(guild_channel(ChannelType::Category)).is_err());
+    }
+
+    #[test]
+    fn voice_channel() {
+        assert_eq!(
+            classify_channel(&guild_channel(ChannelType::Voice)),
+            ChannelClass::Voice
+        );
+        assert!(submission_channel(guild_channel(ChannelType::Voice)).is_err());
+    }
+
+    #[test]
+    fn stage_channel() {
+        assert_eq!(
+            classify_channel(&guild_channel(ChannelType::Stage)),
+            ChannelClass::Stage
+        );
+        assert!(submission_channel(guild_channel(ChannelType::Stage)).is_err());
+    }
+
+    #[test]
+    fn forum() {
+        assert_eq!(
+            classify_channel(&guild_channel(ChannelType::Forum)),
+            ChannelClass::Forum
+        );
+        assert!(submission_channel(guild_channel(ChannelType::Forum)).is_err());
+    }
+
+    #[test]
+    fn private_channel() {
+        let channel = Channel::Private(PrivateChannel::default());
+
+        assert_eq!(classify_channel(&channel), ChannelClass::NotInGuild);
+        assert!(submission_channel(channel).is_err());
+    }
+
+    #[test]
+    fn directory() {
+        assert_eq!(
+            classify_channel(&guild_channel(ChannelType::Directory)),
+            ChannelClass::Other
+        );
+    }
+
+    #[test]
+    fn rejection_names_purpose() {
+        assert_eq!(
+            ChannelClass::Voice
+                .rejection_message(ChannelPurpose::Exchange)
+                .unwrap(),
+            "A voice channel can't be used for an exchange. Please pick a text channel."
+        );
+        assert_eq!(
+            ChannelClass::Voice
+                .rejection_message(ChannelPurpose::AssignmentLog)
+                .unwrap(),
+            "A voice channel can't be used for the assignment log. Please pick a text channel."
+        );
+        assert!(assignment_log_channel(guild_channel(ChannelType::Text)).is_ok());
+        assert!(assignment_log_channel(guild_channel(ChannelType::Forum)).is_err());
+    }
+}
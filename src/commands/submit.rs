@@ -1,21 +1,48 @@
 use indoc::formatdoc;
-use poise::CreateReply;
+use poise::{
+    serenity_prelude::{Mentionable, UserId},
+    CreateReply,
+};
 use time::OffsetDateTime;
-use tracing::debug;
+use tracing::{debug, warn};
 
 use crate::{
-    commands::{internal_err, user_err, ApplicationContext, CommandResult},
-    models::{types::UtcDateTime, NewSubmission},
+    commands::{
+        arguments::TrimmedString, internal_err, require_guild, user_err, ApplicationContext,
+        CommandError, CommandResult,
+    },
+    jam_types::{JamType, StoredJamType},
+    models::{
+        types::UtcDateTime, Exchange, ExchangeState, GuildSettings, NewSubmission, Submission,
+    },
+    repository::AddSubmissionError,
     utils::formatting::{format_local, format_utc},
 };
 
+/// Entry links are much shorter than this, anything longer can't be valid.
+const MAX_LINK_LENGTH: usize = 512;
+
 #[poise::command(slash_command, rename = "submit")]
 pub async fn submit(
     ctx: ApplicationContext<'_>,
-    #[description = "Submission link"] link: String,
+    #[description = "Submission link"] link: TrimmedString,
 ) -> CommandResult {
+    let guild_id = require_guild(ctx.guild_id())?;
+
+    check_link_input(link.as_ref())?;
+
+    {
+        let settings = ctx
+            .data
+            .guild_settings_repository
+            .get_settings(guild_id)
+            .await
+            .map_err(|err| internal_err(format!("Could not get guild settings: {err}")))?;
+
+        check_submitter(ctx.author().bot, &settings)?;
+    }
+
     let exchange = {
-        let guild_id = ctx.guild_id().ok_or(internal_err("Guild id not found"))?;
         let channel_id = ctx.channel_id();
         let now = UtcDateTime::from(OffsetDateTime::now_utc());
 
@@ -30,14 +57,14 @@ pub async fn submit(
             Ok(Some(exchange)) => exchange,
 
             Ok(None) => {
-                let message = formatdoc! {
-                    r#"
-                        # There are no currently active exchanges in this channel
-
-                        Check the starting and ending dates of the exchanges and their submission channels.
-                    "#,
-                };
-                return Err(user_err(message));
+                let in_window = ctx
+                    .data
+                    .exchange_repository
+                    .get_exchange_in_window(guild_id, channel_id, now)
+                    .await
+                    .map_err(|err| internal_err(format!("Could not get exchanges: {err}")))?;
+
+                return Err(no_running_exchange_error(in_window.map(|e| e.state)));
             }
 
             Err(err) => {
@@ -51,27 +78,9 @@ pub async fn submit(
         &exchange.slug, exchange.id
     );
 
-    let link = {
-        let jam_type = exchange.jam_type;
-        let jam_link = exchange.jam_link;
-
-        match jam_type.normalize_jam_entry_link(&jam_link, &link) {
-            Some(link) => link,
-            None => {
-                let message = formatdoc! {
-                    r#"
-                        # Your entry link is invalid
+    check_account_age(&exchange, ctx.author().id, OffsetDateTime::now_utc())?;
 
-                        It should look like this: `{example}`.
-
-                        Make sure to use the correct submission page.
-                    "#,
-                    example = jam_type.jam_entry_link_example(&jam_link),
-                };
-                return Err(user_err(message));
-            }
-        }
-    };
+    let link = normalize_submission_link(&exchange, link.as_ref())?;
 
     let submission = NewSubmission {
         exchange_id: exchange.id,
@@ -80,51 +89,65 @@ pub async fn submit(
         submitted_at: UtcDateTime::from(OffsetDateTime::now_utc()),
     };
 
-    let mut message: String = formatdoc! {
-        r#"
-            # Submitted!
-
-            You will receive your assignments in the DMs when the exchange ends: {end_local} your time or {end_utc} UTC.
-        "#,
-        end_local = format_local(exchange.submissions_end),
-        end_utc = format_utc(exchange.submissions_end),
-    };
-
-    if let Ok(Some(conflict)) = ctx
+    let conflict = ctx
         .data
         .submission_repository
         .get_conflicting_submission(&submission)
         .await
-    {
-        if submission.link == conflict.link {
-            let message = formatdoc! {
-                r#"
-                    # Someone else has already submitted this link
+        .map_err(|err| internal_err(format!("Could not get the existing submission: {err}")))?;
 
-                    If you worked in a team, only one team member can submit an entry and get assignments.
-                "#,
-            };
-            return Err(user_err(message));
-        }
+    let change = classify_submission_change(&submission, conflict.as_ref());
+    let is_new = change == SubmissionChange::New;
 
-        if submission.submitter == conflict.submitter {
-            message = formatdoc! {
-                r#"
-                    # Updated your submission
+    let message = match change {
+        SubmissionChange::New => formatdoc! {
+            r#"
+                # Submitted!
 
-                    Previously submitted link: `{old_link}`.
+                You will receive your assignments in the DMs when the exchange ends: {end_local} your time or {end_utc} UTC.
+            "#,
+            end_local = format_local(exchange.submissions_end),
+            end_utc = format_utc(exchange.submissions_end),
+        },
 
-                    New link: `{new_link}`.
+        SubmissionChange::Unchanged => {
+            let message = formatdoc! {
+                r#"
+                    # Your submission is unchanged
+
+                    You have already submitted `{link}`.
 
                     You will receive your assignments in the DMs when the exchange ends: {end_local} your time or {end_utc} UTC.
                 "#,
-                old_link = conflict.link,
-                new_link = submission.link,
+                link = submission.link,
                 end_local = format_local(exchange.submissions_end),
                 end_utc = format_utc(exchange.submissions_end),
             };
+
+            ctx.send(CreateReply::default().ephemeral(true).content(message))
+                .await?;
+            return Ok(());
         }
-    }
+
+        SubmissionChange::Updated { old_link } => formatdoc! {
+            r#"
+                # Updated your submission
+
+                Previously submitted link: `{old_link}`.
+
+                New link: `{new_link}`.
+
+                You will receive your assignments in the DMs when the exchange ends: {end_local} your time or {end_utc} UTC.
+            "#,
+            new_link = submission.link,
+            end_local = format_local(exchange.submissions_end),
+            end_utc = format_utc(exchange.submissions_end),
+        },
+
+        SubmissionChange::TakenBySomeoneElse => {
+            return Err(submission_error(AddSubmissionError::LinkAlreadySubmitted));
+        }
+    };
 
     match ctx
         .data
@@ -135,10 +158,618 @@ pub async fn submit(
         Ok(_) => {
             ctx.send(CreateReply::default().ephemeral(true).content(message))
                 .await?;
+
+            // Only new submissions are announced, edits would repeat the same announcement
+            if is_new && exchange.public_confirmations {
+                let confirmation = public_confirmation(&exchange, submission.submitter);
+                if let Err(err) = exchange
+                    .channel
+                    .say(ctx.serenity_context(), confirmation)
+                    .await
+                {
+                    warn!("Could not send a public submission confirmation: {err}");
+                }
+            }
+
             Ok(())
         }
-        Err(err) => Err(internal_err(format!(
-            "Could not add/update submission: {err}"
-        ))),
+        Err(err) => Err(submission_error(err)),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum SubmissionChange<'a> {
+    New,
+    Unchanged,
+    Updated { old_link: &'a str },
+    TakenBySomeoneElse,
+}
+
+fn classify_submission_change<'a>(
+    submission: &NewSubmission,
+    conflict: Option<&'a Submission>,
+) -> SubmissionChange<'a> {
+    match conflict {
+        None => SubmissionChange::New,
+        Some(conflict) if conflict.submitter != submission.submitter => {
+            SubmissionChange::TakenBySomeoneElse
+        }
+        Some(conflict) if conflict.link == submission.link => SubmissionChange::Unchanged,
+        Some(conflict) => SubmissionChange::Updated {
+            old_link: &conflict.link,
+        },
+    }
+}
+
+fn submission_error(err: AddSubmissionError) -> CommandError {
+    match err {
+        AddSubmissionError::LinkAlreadySubmitted => {
+            let message = formatdoc! {
+                r#"
+                    # Someone else has already submitted this link
+
+                    If you worked in a team, only one team member can submit an entry and get assignments.
+                "#,
+            };
+            user_err(message)
+        }
+
+        AddSubmissionError::EditLimitReached => {
+            let message = formatdoc! {
+                r#"
+                    # You can't edit your submission anymore
+
+                    This exchange limits how many times a submission can be changed, and you have reached the limit.
+                "#,
+            };
+            user_err(message)
+        }
+
+        err => internal_err(format!("Could not add/update submission: {err}")),
+    }
+}
+
+/// Builds the message announcing a submission in the exchange channel.
+/// Blind exchanges don't reveal who submitted the entry.
+fn public_confirmation(exchange: &Exchange, submitter: UserId) -> String {
+    if exchange.blind {
+        format!("An entry was submitted to **{}**!", exchange.display_name)
+    } else {
+        format!(
+            "{} submitted an entry to **{}**!",
+            submitter.mention(),
+            exchange.display_name
+        )
+    }
+}
+
+fn check_submitter(author_is_bot: bool, settings: &GuildSettings) -> Result<(), CommandError> {
+    if author_is_bot && !settings.allow_bot_submissions {
+        return Err(user_err("Bot accounts can't submit entries in this server"));
+    }
+
+    Ok(())
+}
+
+fn check_account_age(
+    exchange: &Exchange,
+    submitter: UserId,
+    now: OffsetDateTime,
+) -> Result<(), CommandError> {
+    if exchange.min_account_age.is_zero() {
+        return Ok(());
+    }
+
+    let created_at =
+        OffsetDateTime::from_unix_timestamp(submitter.created_at().unix_timestamp())
+            .map_err(|err| internal_err(format!("Invalid account creation date: {err}")))?;
+
+    if now - created_at < exchange.min_account_age {
+        let message = formatdoc! {
+            r#"
+                # Your account is too new to submit to this exchange
+
+                Discord accounts must be at least {min_age} old to take part in **{name}**.
+            "#,
+            min_age = exchange.min_account_age,
+            name = exchange.display_name,
+        };
+        return Err(user_err(message));
+    }
+
+    Ok(())
+}
+
+fn check_link_input(link: &str) -> Result<(), CommandError> {
+    if link.is_empty() {
+        return Err(user_err("Please provide the link to your entry"));
+    }
+
+    if link.chars().count() > MAX_LINK_LENGTH {
+        return Err(user_err(format!(
+            "Your entry link is too long, links can be at most {MAX_LINK_LENGTH} characters"
+        )));
+    }
+
+    Ok(())
+}
+
+fn no_running_exchange_error(state_in_window: Option<ExchangeState>) -> CommandError {
+    let message = match state_in_window {
+        Some(ExchangeState::AssignmentsSent) => formatdoc! {
+            r#"
+                # Submissions have closed and assignments were sent
+
+                This exchange was closed early, so it no longer accepts submissions.
+            "#,
+        },
+
+        _ => formatdoc! {
+            r#"
+                # There are no currently active exchanges in this channel
+
+                Check the starting and ending dates of the exchanges and their submission channels.
+            "#,
+        },
+    };
+
+    user_err(message)
+}
+
+/// Normalizes the entry link against every jam the exchange accepts, in order.
+pub(super) fn normalize_submission_link(
+    exchange: &Exchange,
+    link: &str,
+) -> Result<String, CommandError> {
+    if let StoredJamType::Legacy(jam_type) = &exchange.jam_type {
+        return Err(user_err(formatdoc! {
+            r#"
+                # {name} can't accept new submissions
+
+                The exchange was created for `{jam_type}` jams, which are no longer supported by the bot.
+            "#,
+            name = exchange.display_name,
+        }));
+    }
+
+    // Extra jams of unsupported types can't be matched, but the rest still can
+    let accepted_jams = exchange
+        .accepted_jams()
+        .into_iter()
+        .filter_map(|jam| Some((jam.jam_type.known()?, jam.jam_link)))
+        .collect::<Vec<_>>();
+
+    for (jam_type, jam_link) in &accepted_jams {
+        let jam_link = canonical_jam_link(*jam_type, jam_link);
+
+        if let Some(link) = jam_type.normalize_jam_entry_link(&jam_link, link) {
+            return Ok(link);
+        }
+    }
+
+    let examples = accepted_jams
+        .iter()
+        .map(|(jam_type, jam_link)| {
+            let jam_link = canonical_jam_link(*jam_type, jam_link);
+            format!(" - `{}`", jam_type.jam_entry_link_example(&jam_link))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Err(user_err(formatdoc! {
+        r#"
+            # Your entry link is invalid
+
+            It should look like this:
+            {examples}
+
+            Make sure to use the correct submission page.
+        "#,
+    }))
+}
+
+/// Normalizes the jam link stored with an exchange using the current rules.
+///
+/// Exchanges created under older normalization rules may have stored a jam link
+/// in a legacy format, which would otherwise make valid entry links mismatch.
+fn canonical_jam_link(jam_type: JamType, stored_jam_link: &str) -> String {
+    let normalized = stored_jam_link.trim().to_lowercase();
+
+    jam_type
+        .normalize_jam_link(&normalized)
+        .unwrap_or(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::{GuildId, UserId};
+    use time::{macros::datetime, Duration};
+
+    use crate::{
+        commands::{arguments::TrimmedString, CommandError},
+        jam_types::{JamType, StoredJamType},
+        models::{
+            test_exchange, types::UtcDateTime, Exchange, ExchangeId, ExchangeJam, ExchangeState,
+            GuildSettings, NewSubmission, Submission, SubmissionId,
+        },
+        repository::AddSubmissionError,
+    };
+
+    use super::{
+        canonical_jam_link, check_account_age, check_link_input, check_submitter,
+        classify_submission_change, no_running_exchange_error, normalize_submission_link,
+        public_confirmation, submission_error, SubmissionChange, MAX_LINK_LENGTH,
+    };
+
+    fn new_submission(submitter: u64, link: &str) -> NewSubmission {
+        NewSubmission {
+            exchange_id: ExchangeId(1),
+            link: link.to_string(),
+            submitter: UserId::new(submitter),
+            submitted_at: UtcDateTime::assume_utc(datetime!(2024-01-01 12:30:00)),
+        }
+    }
+
+    fn existing_submission(submitter: u64, link: &str) -> Submission {
+        Submission {
+            id: SubmissionId(1),
+            exchange_id: ExchangeId(1),
+            link: link.to_string(),
+            submitter: UserId::new(submitter),
+            submitted_at: UtcDateTime::assume_utc(datetime!(2024-01-01 12:00:00)),
+            edit_count: 0,
+        }
+    }
+
+    fn exchange(blind: bool) -> Exchange {
+        Exchange {
+            display_name: "Test Exchange".to_string(),
+            blind,
+            public_confirmations: true,
+            ..test_exchange()
+        }
+    }
+
+    #[test]
+    fn legacy_stored_jam_link() {
+        let jam_link = canonical_jam_link(JamType::Itch, "https://itch.io/jam/example-jam/");
+
+        assert_eq!(
+            JamType::Itch
+                .normalize_jam_entry_link(&jam_link, "https://itch.io/jam/example-jam/rate/123456"),
+            Some("https://itch.io/jam/example-jam/rate/123456".to_string())
+        );
+    }
+
+    #[test]
+    fn legacy_stored_jam_link_forms() {
+        for stored in [
+            "https://itch.io/jam/example-jam/",
+            "https://itch.io/jam/Example-Jam",
+            "https://itch.io/jam/EXAMPLE-JAM/",
+            "  https://itch.io/jam/example-jam \n",
+        ] {
+            let exchange = Exchange {
+                jam_link: stored.to_string(),
+                ..exchange(false)
+            };
+
+            assert_eq!(
+                normalize_submission_link(&exchange, "https://itch.io/jam/example-jam/rate/123456")
+                    .unwrap(),
+                "https://itch.io/jam/example-jam/rate/123456",
+                "stored jam link {stored:?}"
+            );
+        }
+
+        let jam_link = canonical_jam_link(
+            JamType::LudumDare,
+            "https://ldjam.com/events/Ludum-Dare/49/",
+        );
+        assert_eq!(jam_link, "https://ldjam.com/events/ludum-dare/49");
+    }
+
+    #[test]
+    fn unparsable_stored_jam_link_is_still_normalized() {
+        assert_eq!(
+            canonical_jam_link(JamType::Itch, "  https://Example.com/Some-Jam \n"),
+            "https://example.com/some-jam"
+        );
+    }
+
+    #[test]
+    fn current_stored_jam_link() {
+        let jam_link =
+            canonical_jam_link(JamType::LudumDare, "https://ldjam.com/events/ludum-dare/49");
+
+        assert_eq!(
+            JamType::LudumDare.normalize_jam_entry_link(
+                &jam_link,
+                "https://ldjam.com/events/ludum-dare/49/unstable98-exe/"
+            ),
+            Some("https://ldjam.com/events/ludum-dare/49/unstable98-exe".to_string())
+        );
+    }
+
+    fn link_input_error(link: &str) -> String {
+        let link = TrimmedString::from(link);
+
+        match check_link_input(link.as_ref()) {
+            Err(CommandError::User { message }) => message,
+            result => panic!("Expected a user error, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn empty_link_rejected() {
+        assert!(link_input_error("").contains("Please provide the link"));
+    }
+
+    #[test]
+    fn whitespace_only_link_rejected() {
+        assert!(link_input_error(" \t\n ").contains("Please provide the link"));
+    }
+
+    #[test]
+    fn over_length_link_rejected() {
+        let link = format!(
+            "https://itch.io/jam/example-jam/rate/{}",
+            "1".repeat(MAX_LINK_LENGTH)
+        );
+
+        assert!(link_input_error(&link).contains("too long"));
+    }
+
+    #[test]
+    fn length_counts_characters() {
+        // Multi-byte characters count once each
+        let link = "é".repeat(MAX_LINK_LENGTH);
+
+        assert!(check_link_input(&link).is_ok());
+        assert!(check_link_input(&format!("{link}é")).is_err());
+    }
+
+    #[test]
+    fn surrounding_whitespace_trimmed_before_normalization() {
+        let link = TrimmedString::from("  https://itch.io/jam/example-jam/rate/123456 \n");
+
+        assert!(check_link_input(link.as_ref()).is_ok());
+        assert_eq!(
+            normalize_submission_link(&exchange(false), link.as_ref()).unwrap(),
+            "https://itch.io/jam/example-jam/rate/123456"
+        );
+    }
+
+    #[test]
+    fn link_already_submitted_message() {
+        match submission_error(AddSubmissionError::LinkAlreadySubmitted) {
+            CommandError::User { message } => {
+                assert!(message.contains("Someone else has already submitted this link"));
+            }
+            err => panic!("Expected a user error, got {err:?}"),
+        }
+    }
+
+    #[test]
+    fn submitting_to_assigned_exchange_message() {
+        match no_running_exchange_error(Some(ExchangeState::AssignmentsSent)) {
+            CommandError::User { message } => {
+                assert!(message.contains("Submissions have closed and assignments were sent"));
+            }
+            err => panic!("Expected a user error, got {err:?}"),
+        }
+    }
+
+    #[test]
+    fn no_exchange_message() {
+        match no_running_exchange_error(None) {
+            CommandError::User { message } => {
+                assert!(message.contains("There are no currently active exchanges"));
+            }
+            err => panic!("Expected a user error, got {err:?}"),
+        }
+    }
+
+    #[test]
+    fn public_confirmation_names_submitter() {
+        let confirmation = public_confirmation(&exchange(false), UserId::new(42));
+
+        assert_eq!(
+            confirmation,
+            "<@42> submitted an entry to **Test Exchange**!"
+        );
+    }
+
+    #[test]
+    fn blind_public_confirmation_is_anonymous() {
+        let confirmation = public_confirmation(&exchange(true), UserId::new(42));
+
+        assert_eq!(confirmation, "An entry was submitted to **Test Exchange**!");
+        assert!(!confirmation.contains("42"));
+    }
+
+    #[test]
+    fn first_submission() {
+        let submission = new_submission(1, "https://itch.io/jam/example-jam/rate/000001");
+
+        assert_eq!(
+            classify_submission_change(&submission, None),
+            SubmissionChange::New
+        );
+    }
+
+    #[test]
+    fn resubmitting_same_link() {
+        let submission = new_submission(1, "https://itch.io/jam/example-jam/rate/000001");
+        let existing = existing_submission(1, "https://itch.io/jam/example-jam/rate/000001");
+
+        assert_eq!(
+            classify_submission_change(&submission, Some(&existing)),
+            SubmissionChange::Unchanged
+        );
+    }
+
+    #[test]
+    fn updating_link() {
+        let submission = new_submission(1, "https://itch.io/jam/example-jam/rate/000002");
+        let existing = existing_submission(1, "https://itch.io/jam/example-jam/rate/000001");
+
+        assert_eq!(
+            classify_submission_change(&submission, Some(&existing)),
+            SubmissionChange::Updated {
+                old_link: "https://itch.io/jam/example-jam/rate/000001"
+            }
+        );
+    }
+
+    #[test]
+    fn submitting_someone_elses_link() {
+        let submission = new_submission(2, "https://itch.io/jam/example-jam/rate/000001");
+        let existing = existing_submission(1, "https://itch.io/jam/example-jam/rate/000001");
+
+        assert_eq!(
+            classify_submission_change(&submission, Some(&existing)),
+            SubmissionChange::TakenBySomeoneElse
+        );
+    }
+
+    #[test]
+    fn bot_submitter_rejected_by_default() {
+        let settings = GuildSettings::default_for(GuildId::new(2));
+
+        match check_submitter(true, &settings) {
+            Err(CommandError::User { message }) => {
+                assert!(message.contains("Bot accounts can't submit"));
+            }
+            result => panic!("Expected a user error, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn bot_submitter_allowed_by_settings() {
+        let settings = GuildSettings {
+            allow_bot_submissions: true,
+            ..GuildSettings::default_for(GuildId::new(2))
+        };
+
+        assert!(check_submitter(true, &settings).is_ok());
+    }
+
+    #[test]
+    fn human_submitter() {
+        let settings = GuildSettings::default_for(GuildId::new(2));
+
+        assert!(check_submitter(false, &settings).is_ok());
+    }
+
+    #[test]
+    fn account_age_not_required() {
+        // Snowflake from 2016-04-30
+        let submitter = UserId::new(175928847299117063);
+
+        assert!(
+            check_account_age(&exchange(false), submitter, datetime!(2016-04-30 12:00 UTC)).is_ok()
+        );
+    }
+
+    #[test]
+    fn account_old_enough() {
+        let exchange = Exchange {
+            min_account_age: Duration::days(30),
+            ..exchange(false)
+        };
+        let submitter = UserId::new(175928847299117063);
+
+        assert!(check_account_age(&exchange, submitter, datetime!(2016-06-30 12:00 UTC)).is_ok());
+    }
+
+    #[test]
+    fn account_too_new() {
+        let exchange = Exchange {
+            min_account_age: Duration::days(30),
+            ..exchange(false)
+        };
+        let submitter = UserId::new(175928847299117063);
+
+        match check_account_age(&exchange, submitter, datetime!(2016-05-10 12:00 UTC)) {
+            Err(CommandError::User { message }) => {
+                assert!(message.contains("Your account is too new"));
+            }
+            result => panic!("Expected a user error, got {result:?}"),
+        }
+    }
+
+    fn multi_platform_exchange() -> Exchange {
+        Exchange {
+            extra_jams: vec![ExchangeJam {
+                jam_type: StoredJamType::Known(JamType::LudumDare),
+                jam_link: "https://ldjam.com/events/ludum-dare/56".to_string(),
+            }],
+            ..exchange(false)
+        }
+    }
+
+    #[test]
+    fn entry_for_any_accepted_jam() {
+        let exchange = multi_platform_exchange();
+
+        assert_eq!(
+            normalize_submission_link(&exchange, "https://itch.io/jam/example-jam/rate/123456")
+                .unwrap(),
+            "https://itch.io/jam/example-jam/rate/123456"
+        );
+        assert_eq!(
+            normalize_submission_link(&exchange, "https://ldjam.com/events/ludum-dare/56/my-game/")
+                .unwrap(),
+            "https://ldjam.com/events/ludum-dare/56/my-game"
+        );
+    }
+
+    #[test]
+    fn entry_for_other_jam_rejected() {
+        let exchange = multi_platform_exchange();
+
+        let Err(CommandError::User { message }) =
+            normalize_submission_link(&exchange, "https://ldjam.com/events/ludum-dare/55/my-game")
+        else {
+            panic!("Expected a user error");
+        };
+
+        assert!(message.contains("https://itch.io/jam/example-jam/rate/123456"));
+        assert!(message.contains("https://ldjam.com/events/ludum-dare/56/example-game"));
+    }
+
+    #[test]
+    fn legacy_jam_type_rejects_submissions() {
+        let exchange = Exchange {
+            jam_type: StoredJamType::Legacy("GameJolt".to_string()),
+            ..exchange(false)
+        };
+
+        let Err(CommandError::User { message }) =
+            normalize_submission_link(&exchange, "https://itch.io/jam/example-jam/rate/123456")
+        else {
+            panic!("Expected a user error");
+        };
+
+        assert!(message.contains("can't accept new submissions"));
+        assert!(message.contains("`GameJolt`"));
+    }
+
+    #[test]
+    fn legacy_extra_jam_ignored() {
+        let exchange = Exchange {
+            extra_jams: vec![ExchangeJam {
+                jam_type: StoredJamType::Legacy("GameJolt".to_string()),
+                jam_link: "https://gamejolt.com/jams/example".to_string(),
+            }],
+            ..exchange(false)
+        };
+
+        assert_eq!(
+            normalize_submission_link(&exchange, "https://itch.io/jam/example-jam/rate/123456")
+                .unwrap(),
+            "https://itch.io/jam/example-jam/rate/123456"
+        );
     }
 }
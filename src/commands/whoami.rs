@@ -0,0 +1,103 @@
+use indoc::formatdoc;
+use poise::{
+    serenity_prelude::{ChannelId, GuildId},
+    CreateReply,
+};
+use time::OffsetDateTime;
+
+use crate::{
+    commands::{internal_err, require_guild, ApplicationContext, CommandResult},
+    models::{types::UtcDateTime, Exchange},
+    utils::formatting::format_utc,
+};
+
+/// Shows the guild, channel and time the bot uses to look up the running exchange.
+#[poise::command(slash_command, rename = "whoami", guild_only)]
+pub async fn whoami(ctx: ApplicationContext<'_>) -> CommandResult {
+    let guild_id = require_guild(ctx.guild_id())?;
+    let channel_id = ctx.channel_id();
+    let now = OffsetDateTime::now_utc();
+
+    let exchange = ctx
+        .data
+        .exchange_repository
+        .get_running_exchange(guild_id, channel_id, UtcDateTime::from(now))
+        .await
+        .map_err(|err| internal_err(format!("Could not get exchanges: {err}")))?;
+
+    let message = context_summary(guild_id, channel_id, now, exchange.as_ref());
+
+    ctx.send(CreateReply::default().ephemeral(true).content(message))
+        .await?;
+
+    Ok(())
+}
+
+fn context_summary(
+    guild: GuildId,
+    channel: ChannelId,
+    now: OffsetDateTime,
+    exchange: Option<&Exchange>,
+) -> String {
+    let exchange_line = match exchange {
+        Some(exchange) => formatdoc! {
+            r#"
+                Running exchange: `{slug}` ({state})
+                Submissions window: {start} — {end}
+            "#,
+            slug = exchange.slug,
+            state = exchange.state_label(),
+            start = format_utc(exchange.submissions_start),
+            end = format_utc(exchange.submissions_end),
+        },
+        None => "Running exchange: none in this channel\n".to_string(),
+    };
+
+    formatdoc! {
+        r#"
+            # Command context
+
+            Guild: `{guild}`
+            Channel: `{channel}`
+            Current time: {now} UTC
+            {exchange_line}
+        "#,
+        now = format_utc(now),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use poise::serenity_prelude::{ChannelId, GuildId};
+    use time::macros::datetime;
+
+    use crate::{models::test_exchange, utils::formatting::format_utc};
+
+    use super::context_summary;
+
+    #[test]
+    fn summary_with_running_exchange() {
+        let exchange = test_exchange();
+        let now = datetime!(2024-01-01 12:30:00 UTC);
+
+        let summary = context_summary(GuildId::new(2), ChannelId::new(3), now, Some(&exchange));
+
+        assert!(summary.contains("Guild: `2`"));
+        assert!(summary.contains("Channel: `3`"));
+        assert!(summary.contains(&format!("Current time: {} UTC", format_utc(now))));
+        assert!(summary.contains("Running exchange: `Test` (Open for submissions)"));
+        assert!(summary.contains(&format_utc(exchange.submissions_start)));
+        assert!(summary.contains(&format_utc(exchange.submissions_end)));
+    }
+
+    #[test]
+    fn summary_without_running_exchange() {
+        let now = datetime!(2024-01-01 12:30:00 UTC);
+
+        let summary = context_summary(GuildId::new(2), ChannelId::new(4), now, None);
+
+        assert!(summary.contains("Channel: `4`"));
+        assert!(summary.contains("Running exchange: none in this channel"));
+        assert!(!summary.contains("Submissions window"));
+    }
+}
@@ -0,0 +1,133 @@
+use anyhow::bail;
+use sqlx::{
+    migrate::{Migrate, Migrator},
+    query_scalar,
+    sqlite::SqlitePoolOptions,
+    SqlitePool,
+};
+use tracing::info;
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+#[tracing::instrument(skip(url))]
+pub async fn setup_database(url: &str) -> anyhow::Result<SqlitePool> {
+    info!("Connecting to SQLite database at {url}");
+    let pool = SqlitePoolOptions::new().connect(url).await?;
+    info!("Checking the database schema version");
+    check_schema_version(&MIGRATOR, &pool).await?;
+    info!("Running migrations");
+    MIGRATOR.run(&pool).await?;
+    info!("Done!");
+    Ok(pool)
+}
+
+/// Runs the migrations against an empty in-memory database and returns the resulting schema,
+/// so that operators can check what the binary expects without touching a real database.
+pub async fn migrated_schema() -> anyhow::Result<String> {
+    let pool = SqlitePoolOptions::new().connect("sqlite::memory:").await?;
+    MIGRATOR.run(&pool).await?;
+
+    let statements: Vec<String> = query_scalar(
+        r#"
+            SELECT sql FROM sqlite_master
+            WHERE sql IS NOT NULL AND name NOT LIKE 'sqlite_%'
+            ORDER BY type DESC, name
+        "#,
+    )
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(statements
+        .iter()
+        .map(|statement| format!("{statement};\n"))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Refuses to work with a database migrated by a newer version of the bot.
+///
+/// Running an older binary against such a database could silently misinterpret the schema.
+async fn check_schema_version(migrator: &Migrator, pool: &SqlitePool) -> anyhow::Result<()> {
+    let latest_known_version = migrator.iter().map(|m| m.version).max().unwrap_or(0);
+
+    let mut connection = pool.acquire().await?;
+    connection.ensure_migrations_table().await?;
+
+    let latest_applied_version = connection
+        .list_applied_migrations()
+        .await?
+        .iter()
+        .map(|m| m.version)
+        .max()
+        .unwrap_or(0);
+
+    if latest_applied_version > latest_known_version {
+        bail!(
+            "The database schema (version {latest_applied_version}) is newer than the one this \
+             binary supports (version {latest_known_version}). Refusing to start, please upgrade the bot."
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{query, sqlite::SqlitePoolOptions, SqlitePool};
+
+    use super::{check_schema_version, migrated_schema, MIGRATOR};
+
+    async fn setup_database() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        MIGRATOR.run(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn empty_database() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        assert!(check_schema_version(&MIGRATOR, &pool).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn up_to_date_database() {
+        let pool = setup_database().await;
+
+        assert!(check_schema_version(&MIGRATOR, &pool).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn database_ahead_of_binary() {
+        let pool = setup_database().await;
+
+        query(
+            r#"
+                INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+                VALUES (99990101000000, 'from the future', TRUE, X'00', 0)
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let err = check_schema_version(&MIGRATOR, &pool).await.unwrap_err();
+        assert!(err.to_string().contains("99990101000000"));
+    }
+
+    #[tokio::test]
+    async fn printed_schema() {
+        let schema = migrated_schema().await.unwrap();
+
+        assert!(schema.contains("CREATE TABLE exchanges"));
+        assert!(schema.contains("CREATE TABLE submissions"));
+        // Columns added by later migrations are included
+        assert!(schema.contains("created_at"));
+    }
+}
@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use poise::serenity_prelude::{http::Http, HttpBuilder, LightMethod, RatelimitInfo, Ratelimiter};
+use tracing::{info, warn};
+
+/// How the Discord HTTP client is rate limited: through a proxy or in this process.
+#[derive(Debug, Default)]
+pub struct DiscordHttpConfig {
+    /// Sends all Discord API requests through a rate limiting proxy such as
+    /// `twilight-http-proxy` instead of rate limiting them in this process.
+    pub proxy: Option<String>,
+    /// Computes rate limit resets from Discord's absolute timestamps instead of relative delays.
+    /// Helps when the requests take long to reach Discord.
+    pub absolute_ratelimits: bool,
+}
+
+/// Builds the Discord HTTP client with rate limit logging, so that operators can tell
+/// when the bot is being slowed down.
+pub fn build_http(token: &str, config: &DiscordHttpConfig) -> anyhow::Result<Http> {
+    let mut builder = HttpBuilder::new(token);
+
+    if let Some(proxy) = &config.proxy {
+        info!("Sending Discord API requests through {proxy}, which is responsible for rate limits");
+
+        builder = builder.proxy(proxy).ratelimiter_disabled(true);
+    } else {
+        let client = reqwest::Client::builder().use_native_tls().build()?;
+
+        let mut ratelimiter = Ratelimiter::new(client.clone(), bot_token(token));
+        ratelimiter.set_ratelimit_callback(Box::new(log_ratelimit));
+        ratelimiter.set_absolute_ratelimits(config.absolute_ratelimits);
+
+        builder = builder.client(client).ratelimiter(ratelimiter);
+    }
+
+    Ok(builder.build())
+}
+
+/// The rate limiter expects the token with the prefix that the HTTP builder adds on its own.
+fn bot_token(token: &str) -> String {
+    let token = token.trim();
+
+    if token.starts_with("Bot ") {
+        token.to_string()
+    } else {
+        format!("Bot {token}")
+    }
+}
+
+fn log_ratelimit(info: RatelimitInfo) {
+    warn!(
+        "{}",
+        format_ratelimit(
+            info.global,
+            info.method,
+            &info.path,
+            info.limit,
+            info.timeout
+        )
+    );
+}
+
+fn format_ratelimit(
+    global: bool,
+    method: LightMethod,
+    path: &str,
+    limit: i64,
+    timeout: Duration,
+) -> String {
+    let scope = if global { "Global" } else { "Route" };
+
+    format!("{scope} rate limit hit on {method:?} {path} (limit {limit}), waiting {timeout:?}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use poise::serenity_prelude::LightMethod;
+
+    use super::{bot_token, build_http, format_ratelimit, DiscordHttpConfig};
+
+    fn ratelimit_message(global: bool) -> String {
+        format_ratelimit(
+            global,
+            LightMethod::Post,
+            "/channels/123/messages",
+            5,
+            Duration::from_millis(1500),
+        )
+    }
+
+    #[test]
+    fn route_ratelimit_message() {
+        assert_eq!(
+            ratelimit_message(false),
+            "Route rate limit hit on Post /channels/123/messages (limit 5), waiting 1.5s"
+        );
+    }
+
+    #[test]
+    fn global_ratelimit_message() {
+        assert!(ratelimit_message(true).starts_with("Global rate limit hit"));
+    }
+
+    #[test]
+    fn token_prefix() {
+        assert_eq!(bot_token("abc"), "Bot abc");
+        assert_eq!(bot_token("Bot abc"), "Bot abc");
+    }
+
+    #[test]
+    fn builds_with_and_without_proxy() {
+        let http = build_http("abc", &DiscordHttpConfig::default()).unwrap();
+        assert!(http.ratelimiter.is_some());
+
+        let http = build_http(
+            "abc",
+            &DiscordHttpConfig {
+                proxy: Some("http://127.0.0.1:3000".to_string()),
+                absolute_ratelimits: false,
+            },
+        )
+        .unwrap();
+        assert!(http.ratelimiter.is_none());
+    }
+}
@@ -1,8 +1,8 @@
 use lazy_regex::{regex_captures, regex_is_match};
 use poise::ChoiceParameter;
-use strum::EnumIter;
+use strum::{EnumIter, IntoEnumIterator};
 
-#[derive(ChoiceParameter, Copy, Clone, Debug, EnumIter)]
+#[derive(ChoiceParameter, Copy, Clone, Debug, PartialEq, Eq, EnumIter)]
 #[repr(i32)]
 pub enum JamType {
     #[name = "Itch.io jam"]
@@ -11,6 +11,21 @@ pub enum JamType {
     LudumDare,
 }
 
+/// Event types hosted under `https://ldjam.com/events/<type>/<number>` that can be used for exchanges
+/// unless configured otherwise.
+pub const DEFAULT_LUDUM_DARE_EVENT_TYPES: &[&str] = &["ludum-dare", "october-challenge"];
+
+pub fn default_ludum_dare_event_types() -> Vec<String> {
+    DEFAULT_LUDUM_DARE_EVENT_TYPES
+        .iter()
+        .map(|event_type| event_type.to_string())
+        .collect()
+}
+
+fn is_ludum_dare_event_type(event_types: &[String], event_type: &str) -> bool {
+    event_types.iter().any(|allowed| allowed == event_type)
+}
+
 impl JamType {
     pub fn jam_link_example(&self) -> &'static str {
         use JamType::*;
@@ -21,7 +36,32 @@ impl JamType {
         }
     }
 
+    /// Normalizes a jam link of this type. Ludum Dare links of any event type are accepted, since
+    /// the links stored with existing exchanges have to keep normalizing, see
+    /// [`Self::validate_jam_link`] for new ones.
     pub fn normalize_jam_link(&self, link: &str) -> Option<String> {
+        self.parse_jam_link(link)
+            .map(|(link_normalized, _event_type)| link_normalized.to_owned())
+    }
+
+    /// Normalizes a jam link for a new exchange, only accepting the given Ludum Dare event types.
+    pub fn validate_jam_link(
+        &self,
+        link: &str,
+        ludum_dare_event_types: &[String],
+    ) -> Option<String> {
+        let (link_normalized, event_type) = self.parse_jam_link(link)?;
+
+        match event_type {
+            Some(event_type) if !is_ludum_dare_event_type(ludum_dare_event_types, event_type) => {
+                None
+            }
+            _ => Some(link_normalized.to_owned()),
+        }
+    }
+
+    /// Splits a jam link into its normalized form and the Ludum Dare event type, if any.
+    fn parse_jam_link<'a>(&self, link: &'a str) -> Option<(&'a str, Option<&'a str>)> {
         use JamType::*;
 
         match self {
@@ -29,14 +69,16 @@ impl JamType {
                 let (_whole, link_normalized) =
                     regex_captures!(r#"^(https://itch\.io/jam/[a-z0-9_-]+)/?$"#, link)?;
 
-                Some(link_normalized.to_owned())
+                Some((link_normalized, None))
             }
 
             LudumDare => {
-                let (_whole, link_normalized) =
-                    regex_captures!(r#"^(https://ldjam.com/events/ludum-dare/[0-9]+)/?$"#, link)?;
+                let (_whole, link_normalized, event_type) = regex_captures!(
+                    r#"^(https://ldjam\.com/events/([a-z0-9-]+)/[0-9]+)/?$"#,
+                    link
+                )?;
 
-                Some(link_normalized.to_owned())
+                Some((link_normalized, Some(event_type)))
             }
         }
     }
@@ -75,7 +117,24 @@ impl JamType {
         }
     }
 
-    pub fn validate_entry_link(&self, entry_link: &str) -> bool {
+    /// The normalized link of the jam an entry link points into, if it's a jam of this type.
+    fn entry_jam_link<'a>(&self, entry_link: &'a str) -> Option<&'a str> {
+        use JamType::*;
+
+        let (_whole, jam_link) = match self {
+            Itch => regex_captures!(r#"^(https://itch\.io/jam/[a-z0-9_-]+)/rate/"#, entry_link)?,
+            LudumDare => regex_captures!(
+                r#"^(https://ldjam\.com/events/[a-z0-9-]+/[0-9]+)/"#,
+                entry_link
+            )?,
+        };
+
+        Some(jam_link)
+    }
+
+    /// Whether the link points to an entry of a jam of this type, only accepting the given Ludum
+    /// Dare event types.
+    pub fn validate_entry_link(&self, entry_link: &str, ludum_dare_event_types: &[String]) -> bool {
         use JamType::*;
 
         match self {
@@ -86,18 +145,83 @@ impl JamType {
                 )
             }
             LudumDare => {
-                regex_is_match!(
-                    r#"^https://ldjam\.com/events/ludum-dare/[0-9]+/[a-z0-9]+/?"#,
+                match regex_captures!(
+                    r#"^https://ldjam\.com/events/([a-z0-9-]+)/[0-9]+/[a-z0-9]+/?"#,
                     entry_link
-                )
+                ) {
+                    Some((_whole, event_type)) => {
+                        is_ludum_dare_event_type(ludum_dare_event_types, event_type)
+                    }
+                    None => false,
+                }
             }
         }
     }
 }
 
+/// Normalizes a link to a jam of any known type, so that it compares equal to the stored jam links
+/// of exchanges. Links that don't point to a jam are only trimmed.
+pub fn normalize_any_jam_link(link: &str) -> String {
+    let link = link.trim();
+    let lowercase = link.to_lowercase();
+
+    JamType::iter()
+        .find_map(|jam_type| jam_type.normalize_jam_link(&lowercase))
+        .unwrap_or_else(|| link.to_string())
+}
+
+/// Normalizes a link to an entry of any jam of a known type, so that links to the same entry
+/// compare equal no matter their casing or trailing slash. Links that don't point to a jam entry
+/// are only trimmed.
+pub fn normalize_entry_link(link: &str) -> String {
+    let link = link.trim();
+    let lowercase = link.to_lowercase();
+
+    JamType::iter()
+        .find_map(|jam_type| {
+            let jam_link = jam_type.entry_jam_link(&lowercase)?;
+            jam_type.normalize_jam_entry_link(jam_link, &lowercase)
+        })
+        .unwrap_or_else(|| link.to_string())
+}
+
+/// A jam type as stored with an exchange.
+///
+/// Exchanges created with a jam type that this version no longer supports stay readable,
+/// so that they can still be listed and deleted, but they can't accept new submissions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StoredJamType {
+    Known(JamType),
+    Legacy(String),
+}
+
+impl StoredJamType {
+    pub fn known(&self) -> Option<JamType> {
+        match self {
+            StoredJamType::Known(jam_type) => Some(*jam_type),
+            StoredJamType::Legacy(_) => None,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            StoredJamType::Known(jam_type) => jam_type.name().to_string(),
+            StoredJamType::Legacy(name) => format!("{name} (no longer supported)"),
+        }
+    }
+}
+
+impl From<JamType> for StoredJamType {
+    fn from(value: JamType) -> Self {
+        StoredJamType::Known(value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::jam_types::JamType;
+    use crate::jam_types::{
+        default_ludum_dare_event_types, normalize_any_jam_link, normalize_entry_link, JamType,
+    };
 
     #[test]
     fn itch_jam_link_example_is_valid() {
@@ -450,4 +574,106 @@ mod tests {
             )
             .is_none());
     }
+
+    #[test]
+    fn ld_other_event_type_is_valid() {
+        let jam = JamType::LudumDare
+            .normalize_jam_link("https://ldjam.com/events/october-challenge/2024/")
+            .unwrap();
+        assert_eq!(jam, "https://ldjam.com/events/october-challenge/2024");
+
+        assert_eq!(
+            JamType::LudumDare.normalize_jam_entry_link(
+                &jam,
+                "https://ldjam.com/events/october-challenge/2024/some-game/"
+            ),
+            Some("https://ldjam.com/events/october-challenge/2024/some-game".to_string())
+        );
+        assert_eq!(
+            JamType::LudumDare.normalize_jam_entry_link(
+                &jam,
+                "https://ldjam.com/events/october-challenge/2024/results"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn ld_bogus_event_type_is_invalid() {
+        let event_types = default_ludum_dare_event_types();
+
+        assert!(JamType::LudumDare
+            .validate_jam_link("https://ldjam.com/events/not-an-event/56", &event_types)
+            .is_none());
+        assert!(!JamType::LudumDare.validate_entry_link(
+            "https://ldjam.com/events/not-an-event/56/somegame",
+            &event_types
+        ));
+    }
+
+    #[test]
+    fn ld_configured_event_types() {
+        let event_types = vec!["mini-ludum-dare".to_string()];
+
+        assert_eq!(
+            JamType::LudumDare
+                .validate_jam_link("https://ldjam.com/events/mini-ludum-dare/90/", &event_types),
+            Some("https://ldjam.com/events/mini-ludum-dare/90".to_string())
+        );
+        assert!(JamType::LudumDare
+            .validate_jam_link("https://ldjam.com/events/ludum-dare/56", &event_types)
+            .is_none());
+        assert!(JamType::LudumDare.validate_entry_link(
+            "https://ldjam.com/events/mini-ludum-dare/90/somegame",
+            &event_types
+        ));
+    }
+
+    #[test]
+    fn ld_stored_links_normalize_with_any_event_type() {
+        assert_eq!(
+            JamType::LudumDare.normalize_jam_link("https://ldjam.com/events/retired-event/3/"),
+            Some("https://ldjam.com/events/retired-event/3".to_string())
+        );
+    }
+
+    #[test]
+    fn entry_links_normalize_alike() {
+        assert_eq!(
+            normalize_entry_link(" https://Itch.io/jam/Example-Jam/rate/123/ "),
+            "https://itch.io/jam/example-jam/rate/123"
+        );
+        assert_eq!(
+            normalize_entry_link("https://ldjam.com/events/ludum-dare/56/Some-Game/"),
+            "https://ldjam.com/events/ludum-dare/56/some-game"
+        );
+    }
+
+    #[test]
+    fn non_entry_links_are_only_trimmed() {
+        assert_eq!(
+            normalize_entry_link(" https://example.com/Game/ "),
+            "https://example.com/Game/"
+        );
+        assert_eq!(
+            normalize_entry_link("https://ldjam.com/events/ludum-dare/56/results"),
+            "https://ldjam.com/events/ludum-dare/56/results"
+        );
+    }
+
+    #[test]
+    fn jam_links_normalize_alike() {
+        assert_eq!(
+            normalize_any_jam_link(" https://Itch.io/jam/Example-Jam/ "),
+            "https://itch.io/jam/example-jam"
+        );
+        assert_eq!(
+            normalize_any_jam_link("https://ldjam.com/events/ludum-dare/56/"),
+            "https://ldjam.com/events/ludum-dare/56"
+        );
+        assert_eq!(
+            normalize_any_jam_link("https://example.com/Jam/"),
+            "https://example.com/Jam/"
+        );
+    }
 }
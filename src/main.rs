@@ -2,8 +2,12 @@
 //#![forbid(clippy::unwrap_used)] // TODO: Enable this lint
 
 mod assignment_service;
+mod circuit_breaker;
 mod commands;
+mod database;
+mod discord_http;
 mod jam_types;
+mod message_sender;
 mod models;
 mod poise_error_handler;
 mod repository;
@@ -12,16 +16,24 @@ mod utils;
 
 use std::{process::exit, sync::Arc};
 
-use assignment_service::AssignmentService;
+use assignment_service::{AssignmentService, DEFAULT_MAX_EXCHANGES_PER_PASS};
+use database::{migrated_schema, setup_database};
+use discord_http::{build_http, DiscordHttpConfig};
+use jam_types::default_ludum_dare_event_types;
 
 use poise::{serenity_prelude::*, Framework};
 use poise_error_handler::handle_error;
-use repository::{ExchangeRepository, PlayedGameRepository, SubmissionRepository};
+use repository::{
+    AckRepository, AssignmentRepository, BackfillRepository, ExchangeRepository,
+    GuildSettingsRepository, PlayedGameRepository, SubmissionRepository, DEFAULT_EVENT_CAPACITY,
+    DEFAULT_SLOW_QUERY_THRESHOLD,
+};
 use serde::Deserialize;
-use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+use time::Duration;
 use tokio::{select, signal, sync::Notify};
 use tracing::{error, info, info_span, warn, Instrument};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use utils::link_preview::LinkPreviewer;
 
 #[derive(Debug, Deserialize)]
 struct AppConfig {
@@ -29,12 +41,84 @@ struct AppConfig {
     database_url: String,
     register_commands_globally: Option<bool>,
     register_commands_in_guilds: Option<Vec<u64>>,
+    create_confirm_timeout_seconds: Option<u64>,
+    exchange_events_capacity: Option<usize>,
+    slow_query_threshold_ms: Option<u64>,
+    max_exchanges_per_pass: Option<usize>,
+    fetch_entry_titles: Option<bool>,
+    /// Experimental: keep solving the assignments while submissions arrive.
+    incremental_assignments: Option<bool>,
+    discord_http_proxy: Option<String>,
+    discord_absolute_ratelimits: Option<bool>,
+    /// Ludum Dare event types that exchanges can be created for, e.g. `ludum-dare,mini-ludum-dare`.
+    ludum_dare_event_types: Option<Vec<String>>,
+}
+
+const DEFAULT_CREATE_CONFIRM_TIMEOUT: Duration = Duration::minutes(5);
+
+impl AppConfig {
+    fn create_confirm_timeout(&self) -> Duration {
+        // Values that don't fit a duration fall back to the default instead of wrapping around
+        self.create_confirm_timeout_seconds
+            .and_then(|seconds| i64::try_from(seconds).ok())
+            .map(Duration::seconds)
+            .unwrap_or(DEFAULT_CREATE_CONFIRM_TIMEOUT)
+    }
+
+    fn exchange_events_capacity(&self) -> usize {
+        self.exchange_events_capacity
+            .filter(|capacity| *capacity > 0)
+            .unwrap_or(DEFAULT_EVENT_CAPACITY)
+    }
+
+    fn slow_query_threshold(&self) -> std::time::Duration {
+        self.slow_query_threshold_ms
+            .map(std::time::Duration::from_millis)
+            .unwrap_or(DEFAULT_SLOW_QUERY_THRESHOLD)
+    }
+
+    fn discord_http_config(&self) -> DiscordHttpConfig {
+        DiscordHttpConfig {
+            proxy: self.discord_http_proxy.clone(),
+            absolute_ratelimits: self.discord_absolute_ratelimits.unwrap_or(false),
+        }
+    }
+
+    fn ludum_dare_event_types(&self) -> Vec<String> {
+        let event_types = self
+            .ludum_dare_event_types
+            .iter()
+            .flatten()
+            .map(|event_type| event_type.trim().to_lowercase())
+            .filter(|event_type| !event_type.is_empty())
+            .collect::<Vec<_>>();
+
+        if event_types.is_empty() {
+            default_ludum_dare_event_types()
+        } else {
+            event_types
+        }
+    }
+
+    fn max_exchanges_per_pass(&self) -> usize {
+        self.max_exchanges_per_pass
+            .filter(|max| *max > 0)
+            .unwrap_or(DEFAULT_MAX_EXCHANGES_PER_PASS)
+    }
 }
 
 pub struct BotState {
     pub exchange_repository: Arc<ExchangeRepository>,
     pub submission_repository: Arc<SubmissionRepository>,
     pub played_game_repository: Arc<PlayedGameRepository>,
+    pub assignment_repository: Arc<AssignmentRepository>,
+    pub guild_settings_repository: Arc<GuildSettingsRepository>,
+    pub ack_repository: Arc<AckRepository>,
+    pub backfill_repository: Arc<BackfillRepository>,
+    /// How long `/exchange create` waits for the confirmation before cancelling.
+    pub create_confirm_timeout: Duration,
+    /// Ludum Dare event types that exchanges can be created for and entries can be recorded for.
+    pub ludum_dare_event_types: Vec<String>,
 }
 
 #[tracing::instrument]
@@ -57,6 +141,19 @@ async fn main() {
         )
         .init();
 
+    if std::env::args().any(|arg| arg == "--print-schema") {
+        match migrated_schema().await {
+            Ok(schema) => {
+                print!("{schema}");
+                exit(0);
+            }
+            Err(err) => {
+                error!("Could not build the schema: {err}");
+                exit(255);
+            }
+        }
+    }
+
     let app_config = match envy::from_env::<AppConfig>() {
         Ok(config) => config,
         Err(err) => {
@@ -65,6 +162,8 @@ async fn main() {
         }
     };
 
+    let slow_query_threshold = app_config.slow_query_threshold();
+
     let db_pool = match setup_database(&app_config.database_url).await {
         Ok(pool) => pool,
         Err(err) => {
@@ -73,13 +172,68 @@ async fn main() {
         }
     };
 
+    let max_exchanges_per_pass = app_config.max_exchanges_per_pass();
+    let incremental_assignments = app_config.incremental_assignments.unwrap_or(false);
+
+    let http = match build_http(
+        &app_config.discord_bot_token,
+        &app_config.discord_http_config(),
+    ) {
+        Ok(http) => http,
+        Err(err) => {
+            error!("Failed to create the Discord HTTP client: {err}");
+            exit(255);
+        }
+    };
+
+    let link_previewer = if let Some(true) = app_config.fetch_entry_titles {
+        match LinkPreviewer::new() {
+            Ok(previewer) => Some(previewer),
+            Err(err) => {
+                warn!("Could not set up entry title fetching, sending bare links: {err}");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
     let shutdown_notify = Arc::new(Notify::new());
     let assignment_service_shutdown = shutdown_notify.clone();
 
     let app_state = BotState {
-        exchange_repository: Arc::new(ExchangeRepository::new(db_pool.clone())),
-        submission_repository: Arc::new(SubmissionRepository::new(db_pool.clone())),
-        played_game_repository: Arc::new(PlayedGameRepository::new(db_pool.clone())),
+        exchange_repository: Arc::new(
+            ExchangeRepository::with_event_capacity(
+                db_pool.clone(),
+                app_config.exchange_events_capacity(),
+            )
+            .with_slow_query_threshold(slow_query_threshold),
+        ),
+        submission_repository: Arc::new(
+            SubmissionRepository::new(db_pool.clone())
+                .with_slow_query_threshold(slow_query_threshold),
+        ),
+        played_game_repository: Arc::new(
+            PlayedGameRepository::new(db_pool.clone())
+                .with_slow_query_threshold(slow_query_threshold),
+        ),
+        assignment_repository: Arc::new(
+            AssignmentRepository::new(db_pool.clone())
+                .with_slow_query_threshold(slow_query_threshold),
+        ),
+        guild_settings_repository: Arc::new(
+            GuildSettingsRepository::new(db_pool.clone())
+                .with_slow_query_threshold(slow_query_threshold),
+        ),
+        ack_repository: Arc::new(
+            AckRepository::new(db_pool.clone()).with_slow_query_threshold(slow_query_threshold),
+        ),
+        backfill_repository: Arc::new(
+            BackfillRepository::new(db_pool.clone())
+                .with_slow_query_threshold(slow_query_threshold),
+        ),
+        create_confirm_timeout: app_config.create_confirm_timeout(),
+        ludum_dare_event_types: app_config.ludum_dare_event_types(),
     };
 
     let framework = Framework::builder()
@@ -89,6 +243,11 @@ async fn main() {
                 commands::submit(),
                 commands::played(),
                 commands::revoke(),
+                commands::mystatus(),
+                commands::ready(),
+                commands::settings(),
+                commands::maintenance(),
+                commands::whoami(),
             ],
             on_error: |error| Box::pin(handle_error(error)),
             ..Default::default()
@@ -125,6 +284,13 @@ async fn main() {
                         app_state.exchange_repository.clone(),
                         app_state.submission_repository.clone(),
                         app_state.played_game_repository.clone(),
+                        app_state.assignment_repository.clone(),
+                        app_state.ack_repository.clone(),
+                        app_state.guild_settings_repository.clone(),
+                        app_state.backfill_repository.clone(),
+                        max_exchanges_per_pass,
+                        link_previewer,
+                        incremental_assignments,
                     );
 
                     Ok(app_state)
@@ -134,7 +300,7 @@ async fn main() {
         })
         .build();
 
-    let mut client = match ClientBuilder::new(app_config.discord_bot_token, GatewayIntents::empty())
+    let mut client = match ClientBuilder::new_with_http(http, GatewayIntents::empty())
         .framework(framework)
         .await
     {
@@ -161,12 +327,131 @@ async fn main() {
     };
 }
 
-#[tracing::instrument(skip(url))]
-async fn setup_database(url: &str) -> anyhow::Result<SqlitePool> {
-    info!("Connecting to SQLite database at {url}");
-    let pool = SqlitePoolOptions::new().connect(url).await?;
-    info!("Running migrations");
-    sqlx::migrate!("./migrations").run(&pool).await?;
-    info!("Done!");
-    Ok(pool)
+#[cfg(test)]
+mod tests {
+    use time::Duration;
+
+    use crate::AppConfig;
+
+    fn config(create_confirm_timeout_seconds: Option<u64>) -> AppConfig {
+        AppConfig {
+            discord_bot_token: "token".to_string(),
+            database_url: "sqlite::memory:".to_string(),
+            register_commands_globally: None,
+            register_commands_in_guilds: None,
+            create_confirm_timeout_seconds,
+            exchange_events_capacity: None,
+            slow_query_threshold_ms: None,
+            max_exchanges_per_pass: None,
+            fetch_entry_titles: None,
+            incremental_assignments: None,
+            discord_http_proxy: None,
+            discord_absolute_ratelimits: None,
+            ludum_dare_event_types: None,
+        }
+    }
+
+    #[test]
+    fn create_confirm_timeout_default() {
+        assert_eq!(config(None).create_confirm_timeout(), Duration::minutes(5));
+    }
+
+    #[test]
+    fn create_confirm_timeout_configured() {
+        assert_eq!(
+            config(Some(15 * 60)).create_confirm_timeout(),
+            Duration::minutes(15)
+        );
+    }
+
+    #[test]
+    fn create_confirm_timeout_out_of_range() {
+        assert_eq!(
+            config(Some(u64::MAX)).create_confirm_timeout(),
+            Duration::minutes(5)
+        );
+    }
+
+    #[test]
+    fn exchange_events_capacity() {
+        assert_eq!(config(None).exchange_events_capacity(), 128);
+
+        let configured = AppConfig {
+            exchange_events_capacity: Some(16),
+            ..config(None)
+        };
+        assert_eq!(configured.exchange_events_capacity(), 16);
+
+        // A zero-capacity broadcast channel panics on creation
+        let zero = AppConfig {
+            exchange_events_capacity: Some(0),
+            ..config(None)
+        };
+        assert_eq!(zero.exchange_events_capacity(), 128);
+    }
+
+    #[test]
+    fn slow_query_threshold() {
+        assert_eq!(
+            config(None).slow_query_threshold(),
+            std::time::Duration::from_millis(500)
+        );
+
+        let configured = AppConfig {
+            slow_query_threshold_ms: Some(100),
+            ..config(None)
+        };
+        assert_eq!(
+            configured.slow_query_threshold(),
+            std::time::Duration::from_millis(100)
+        );
+    }
+
+    #[test]
+    fn max_exchanges_per_pass() {
+        assert_eq!(config(None).max_exchanges_per_pass(), 5);
+
+        let configured = AppConfig {
+            max_exchanges_per_pass: Some(1),
+            ..config(None)
+        };
+        assert_eq!(configured.max_exchanges_per_pass(), 1);
+
+        // Zero would never make progress
+        let zero = AppConfig {
+            max_exchanges_per_pass: Some(0),
+            ..config(None)
+        };
+        assert_eq!(zero.max_exchanges_per_pass(), 5);
+    }
+
+    #[test]
+    fn ludum_dare_event_types() {
+        assert_eq!(
+            config(None).ludum_dare_event_types(),
+            vec!["ludum-dare", "october-challenge"]
+        );
+
+        let configured = AppConfig {
+            ludum_dare_event_types: Some(vec![
+                "ludum-dare".to_string(),
+                " Mini-Ludum-Dare ".to_string(),
+            ]),
+            ..config(None)
+        };
+        assert_eq!(
+            configured.ludum_dare_event_types(),
+            vec!["ludum-dare", "mini-ludum-dare"]
+        );
+
+        // No event types would make Ludum Dare unusable
+        let empty = AppConfig {
+            ludum_dare_event_types: Some(vec![]),
+            ..config(None)
+        };
+        assert_eq!(
+            empty.ludum_dare_event_types(),
+            vec!["ludum-dare", "october-challenge"]
+        );
+    }
 }
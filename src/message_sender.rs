@@ -0,0 +1,385 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serenity::{
+    all::{ChannelId, UserId},
+    http::{Http, HttpError},
+};
+use tokio::{select, sync::Notify};
+
+use crate::circuit_breaker::{CircuitBreaker, CircuitState};
+
+/// How often a held back message checks whether the trial request closed the circuit.
+const TRIAL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How long a send may take before it's given up on and counted as a failure. Serenity doesn't
+/// time out requests by itself, and a trial request that never finishes would keep the circuit
+/// half-open forever.
+const SEND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Sends bot messages to Discord.
+///
+/// Background services go through this instead of [`Http`] directly so that they can be
+/// exercised in tests without a Discord connection.
+#[async_trait]
+pub trait MessageSender: Send + Sync {
+    async fn send_to_channel(&self, channel: ChannelId, message: String) -> serenity::Result<()>;
+
+    async fn send_dm(&self, user: UserId, message: String) -> serenity::Result<()>;
+}
+
+#[async_trait]
+impl MessageSender for Http {
+    async fn send_to_channel(&self, channel: ChannelId, message: String) -> serenity::Result<()> {
+        channel.say(self, message).await?;
+        Ok(())
+    }
+
+    async fn send_dm(&self, user: UserId, message: String) -> serenity::Result<()> {
+        let channel = user.create_dm_channel(self).await?;
+        channel.say(self, message).await?;
+        Ok(())
+    }
+}
+
+/// Wraps another sender and holds messages back while Discord keeps failing, see
+/// [`CircuitBreaker`]. Held back messages are sent once the cooldown is over, unless the bot
+/// shuts down first.
+pub struct CircuitBreakingSender {
+    inner: Arc<dyn MessageSender>,
+    breaker: Mutex<CircuitBreaker>,
+    shutdown: Arc<Notify>,
+    shutting_down: AtomicBool,
+    send_timeout: Duration,
+}
+
+impl CircuitBreakingSender {
+    pub fn new(
+        inner: Arc<dyn MessageSender>,
+        breaker: CircuitBreaker,
+        shutdown: Arc<Notify>,
+    ) -> CircuitBreakingSender {
+        CircuitBreakingSender {
+            inner,
+            breaker: Mutex::new(breaker),
+            shutdown,
+            shutting_down: AtomicBool::new(false),
+            send_timeout: SEND_TIMEOUT,
+        }
+    }
+
+    async fn guarded(
+        &self,
+        send: impl Future<Output = serenity::Result<()>>,
+    ) -> serenity::Result<()> {
+        loop {
+            if self.shutting_down.load(Ordering::SeqCst) {
+                return Err(serenity::Error::Other(
+                    "Sending was cancelled by the shutdown",
+                ));
+            }
+
+            let retry_at = {
+                let mut breaker = self.breaker.lock().unwrap();
+                let now = Instant::now();
+                if breaker.allow_request(now) {
+                    break;
+                }
+
+                // Another trial request is in flight, its outcome decides how long to wait
+                breaker
+                    .cooldown_ends_at()
+                    .unwrap_or(now + TRIAL_POLL_INTERVAL)
+            };
+
+            select! {
+                _ = self.shutdown.notified() => {
+                    self.shutting_down.store(true, Ordering::SeqCst);
+                }
+
+                _ = tokio::time::sleep_until(retry_at.into()) => {}
+            }
+        }
+
+        let outcome = SendOutcome {
+            breaker: &self.breaker,
+            recorded: false,
+        };
+
+        let Ok(result) = tokio::time::timeout(self.send_timeout, send).await else {
+            outcome.record(false);
+            return Err(serenity::Error::Other("Sending timed out"));
+        };
+
+        // Discord answered, even if it rejected the message, e.g. because of closed DMs
+        outcome.record(!matches!(&result, Err(err) if is_outage(err)));
+
+        result
+    }
+}
+
+/// Records the outcome of a send in the circuit breaker. If the send is dropped before it
+/// finishes, e.g. because its task was cancelled, a trial request counts as failed, so that
+/// the circuit doesn't stay half-open with nothing left to close it.
+struct SendOutcome<'a> {
+    breaker: &'a Mutex<CircuitBreaker>,
+    recorded: bool,
+}
+
+impl SendOutcome<'_> {
+    fn record(mut self, succeeded: bool) {
+        let mut breaker = self.breaker.lock().unwrap();
+        if succeeded {
+            breaker.record_success();
+        } else {
+            breaker.record_failure(Instant::now());
+        }
+
+        self.recorded = true;
+    }
+}
+
+impl Drop for SendOutcome<'_> {
+    fn drop(&mut self) {
+        if self.recorded {
+            return;
+        }
+
+        if let Ok(mut breaker) = self.breaker.lock() {
+            if breaker.state() == CircuitState::HalfOpen {
+                breaker.record_failure(Instant::now());
+            }
+        }
+    }
+}
+
+/// Whether the error means Discord couldn't be reached or is struggling, as opposed to
+/// rejecting this particular message.
+fn is_outage(err: &serenity::Error) -> bool {
+    match err {
+        serenity::Error::Http(HttpError::UnsuccessfulRequest(response)) => {
+            is_outage_status(response.status_code)
+        }
+        serenity::Error::Http(HttpError::Request(_)) => true,
+        _ => false,
+    }
+}
+
+fn is_outage_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+#[async_trait]
+impl MessageSender for CircuitBreakingSender {
+    async fn send_to_channel(&self, channel: ChannelId, message: String) -> serenity::Result<()> {
+        self.guarded(self.inner.send_to_channel(channel, message))
+            .await
+    }
+
+    async fn send_dm(&self, user: UserId, message: String) -> serenity::Result<()> {
+        self.guarded(self.inner.send_dm(user, message)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc,
+        },
+        time::{Duration, Instant},
+    };
+
+    use async_trait::async_trait;
+    use reqwest::StatusCode;
+    use serenity::{
+        all::{ChannelId, UserId},
+        http::HttpError,
+    };
+    use tokio::sync::Notify;
+
+    use crate::circuit_breaker::{CircuitBreaker, CircuitState};
+
+    use super::{is_outage, is_outage_status, CircuitBreakingSender, MessageSender};
+
+    /// Fails the first `failures` sends as if Discord couldn't be reached, then succeeds.
+    struct FlakySender {
+        failures: u32,
+        attempts: AtomicU32,
+    }
+
+    #[async_trait]
+    impl MessageSender for FlakySender {
+        async fn send_to_channel(
+            &self,
+            _channel: ChannelId,
+            _message: String,
+        ) -> serenity::Result<()> {
+            if self.attempts.fetch_add(1, Ordering::SeqCst) < self.failures {
+                Err(transport_error().await)
+            } else {
+                Ok(())
+            }
+        }
+
+        async fn send_dm(&self, _user: UserId, message: String) -> serenity::Result<()> {
+            self.send_to_channel(ChannelId::new(1), message).await
+        }
+    }
+
+    async fn transport_error() -> serenity::Error {
+        let err = reqwest::Client::new()
+            .get("http://[invalid")
+            .send()
+            .await
+            .unwrap_err();
+
+        serenity::Error::Http(HttpError::Request(err))
+    }
+
+    fn state(sender: &CircuitBreakingSender) -> CircuitState {
+        sender.breaker.lock().unwrap().state()
+    }
+
+    #[tokio::test]
+    async fn waits_out_cooldown_and_recovers() {
+        let flaky = Arc::new(FlakySender {
+            failures: 3,
+            attempts: AtomicU32::new(0),
+        });
+        let cooldown = Duration::from_millis(50);
+        let sender = CircuitBreakingSender::new(
+            flaky.clone(),
+            CircuitBreaker::new(2, cooldown),
+            Arc::new(Notify::new()),
+        );
+
+        // Two failures open the circuit
+        for _ in 0..2 {
+            assert!(sender.send_dm(UserId::new(1), "Hi".into()).await.is_err());
+        }
+        assert!(matches!(state(&sender), CircuitState::Open { .. }));
+
+        // While open, the message waits for the cooldown and is then sent as the trial, which
+        // fails and opens the circuit again
+        let start = Instant::now();
+        assert!(sender.send_dm(UserId::new(1), "Hi".into()).await.is_err());
+        assert!(start.elapsed() >= cooldown);
+        assert_eq!(flaky.attempts.load(Ordering::SeqCst), 3);
+        assert!(matches!(state(&sender), CircuitState::Open { .. }));
+
+        // The next trial succeeds and closes the circuit
+        let start = Instant::now();
+        assert!(sender.send_dm(UserId::new(1), "Hi".into()).await.is_ok());
+        assert!(start.elapsed() >= cooldown);
+        assert_eq!(
+            state(&sender),
+            CircuitState::Closed {
+                consecutive_failures: 0
+            }
+        );
+        assert!(sender
+            .send_to_channel(ChannelId::new(2), "Hi".into())
+            .await
+            .is_ok());
+        assert_eq!(flaky.attempts.load(Ordering::SeqCst), 5);
+    }
+
+    /// Never finishes sending, like a request Discord never answers.
+    struct HangingSender;
+
+    #[async_trait]
+    impl MessageSender for HangingSender {
+        async fn send_to_channel(
+            &self,
+            _channel: ChannelId,
+            _message: String,
+        ) -> serenity::Result<()> {
+            std::future::pending().await
+        }
+
+        async fn send_dm(&self, _user: UserId, _message: String) -> serenity::Result<()> {
+            std::future::pending().await
+        }
+    }
+
+    /// A sender whose circuit is open with the cooldown already over, so the next send is a trial.
+    fn sender_before_trial(inner: Arc<dyn MessageSender>) -> CircuitBreakingSender {
+        let mut breaker = CircuitBreaker::new(1, Duration::ZERO);
+        breaker.record_failure(Instant::now());
+
+        CircuitBreakingSender::new(inner, breaker, Arc::new(Notify::new()))
+    }
+
+    #[tokio::test]
+    async fn cancelled_trial_reopens_circuit() {
+        let sender = sender_before_trial(Arc::new(HangingSender));
+
+        let trial = sender.send_dm(UserId::new(1), "Hi".into());
+        assert!(tokio::time::timeout(Duration::from_millis(20), trial)
+            .await
+            .is_err());
+
+        assert!(matches!(state(&sender), CircuitState::Open { .. }));
+    }
+
+    #[tokio::test]
+    async fn hanging_trial_times_out() {
+        let sender = CircuitBreakingSender {
+            send_timeout: Duration::from_millis(20),
+            ..sender_before_trial(Arc::new(HangingSender))
+        };
+
+        assert!(sender.send_dm(UserId::new(1), "Hi".into()).await.is_err());
+        assert!(matches!(state(&sender), CircuitState::Open { .. }));
+    }
+
+    #[tokio::test]
+    async fn shutdown_cancels_waiting() {
+        let flaky = Arc::new(FlakySender {
+            failures: 1,
+            attempts: AtomicU32::new(0),
+        });
+        let shutdown = Arc::new(Notify::new());
+        let sender = Arc::new(CircuitBreakingSender::new(
+            flaky.clone(),
+            CircuitBreaker::new(1, Duration::from_secs(60)),
+            shutdown.clone(),
+        ));
+
+        assert!(sender.send_dm(UserId::new(1), "Hi".into()).await.is_err());
+
+        let waiting = tokio::spawn({
+            let sender = sender.clone();
+            async move { sender.send_dm(UserId::new(1), "Hi".into()).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        shutdown.notify_waiters();
+
+        assert!(waiting.await.unwrap().is_err());
+        // Later messages don't wait for a shutdown that already happened
+        assert!(sender.send_dm(UserId::new(1), "Hi".into()).await.is_err());
+        assert_eq!(flaky.attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn only_unreachable_discord_is_an_outage() {
+        assert!(is_outage(&transport_error().await));
+        assert!(!is_outage(&serenity::Error::Other(
+            "Cannot send messages to this user"
+        )));
+
+        assert!(is_outage_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_outage_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_outage_status(StatusCode::FORBIDDEN));
+        assert!(!is_outage_status(StatusCode::NOT_FOUND));
+    }
+}
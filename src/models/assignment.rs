@@ -0,0 +1,23 @@
+use poise::serenity_prelude::UserId;
+
+use super::{exchange::ExchangeId, submission::SubmissionId};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Assignment {
+    pub exchange_id: ExchangeId,
+    pub reviewer: UserId,
+    /// `None` for entries from the exchange's backfill pool.
+    pub submission_id: Option<SubmissionId>,
+    pub link: String,
+    pub delivered: DeliveryStatus,
+}
+
+/// Whether the DM with the assignment reached the reviewer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeliveryStatus {
+    /// The assignment was stored but no DM was attempted yet.
+    Pending,
+    Delivered,
+    /// Discord rejected the DM, e.g. because the reviewer doesn't accept DMs from server members.
+    Failed,
+}
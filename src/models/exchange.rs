@@ -1,12 +1,14 @@
 use std::num::NonZeroU8;
 
-use poise::serenity_prelude::{ChannelId, GuildId};
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use strum::EnumIter;
+use time::Duration;
 
-use crate::jam_types::JamType;
+use crate::jam_types::{JamType, StoredJamType};
 
 use super::types::UtcDateTime;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct ExchangeId(pub u64);
 
 #[derive(Debug)]
@@ -14,7 +16,7 @@ pub struct Exchange {
     pub id: ExchangeId,
     pub guild: GuildId,
     pub channel: ChannelId,
-    pub jam_type: JamType,
+    pub jam_type: StoredJamType,
     pub jam_link: String,
     pub slug: String,
     pub display_name: String,
@@ -22,9 +24,31 @@ pub struct Exchange {
     pub submissions_start: UtcDateTime,
     pub submissions_end: UtcDateTime,
     pub games_per_member: NonZeroU8,
+    pub ignore_played: bool,
+    pub blind: bool,
+    pub public_confirmations: bool,
+    /// How many times members can change their submitted link. 0 means unlimited.
+    pub max_submission_edits: u8,
+    /// Submitters' Discord accounts must be at least this old. Zero disables the check.
+    pub min_account_age: Duration,
+    /// Jams on other platforms whose entries are accepted alongside the main one.
+    pub extra_jams: Vec<ExchangeJam>,
+    /// How strongly to bias the solver against members reviewing each other's entries. This is a
+    /// heuristic, so reciprocal assignments can remain even when avoidable. Zero disables it.
+    pub reciprocal_penalty: u8,
+    /// How long before the end of submissions members can no longer revoke. Zero disables the lock.
+    pub revoke_lock: Duration,
+    /// Whether to post anonymous assignment statistics in the channel after the assignments.
+    pub announce_stats: bool,
+    /// The admin who created the exchange. Unknown for exchanges created before it was recorded.
+    pub created_by: Option<UserId>,
+    /// When the exchange was created. Unknown for exchanges created before it was recorded.
+    pub created_at: Option<UtcDateTime>,
+    /// When the assignments were sent, if they were.
+    pub assignments_sent_at: Option<UtcDateTime>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct NewExchange {
     pub guild: GuildId,
     pub channel: ChannelId,
@@ -36,9 +60,60 @@ pub struct NewExchange {
     pub submissions_start: UtcDateTime,
     pub submissions_end: UtcDateTime,
     pub games_per_member: NonZeroU8,
+    pub ignore_played: bool,
+    pub blind: bool,
+    pub public_confirmations: bool,
+    /// How many times members can change their submitted link. 0 means unlimited.
+    pub max_submission_edits: u8,
+    /// Submitters' Discord accounts must be at least this old. Zero disables the check.
+    pub min_account_age: Duration,
+    /// Jams on other platforms whose entries are accepted alongside the main one.
+    pub extra_jams: Vec<ExchangeJam>,
+    /// How strongly to bias the solver against members reviewing each other's entries. This is a
+    /// heuristic, so reciprocal assignments can remain even when avoidable. Zero disables it.
+    pub reciprocal_penalty: u8,
+    /// How long before the end of submissions members can no longer revoke. Zero disables the lock.
+    pub revoke_lock: Duration,
+    /// Whether to post anonymous assignment statistics in the channel after the assignments.
+    pub announce_stats: bool,
+    /// The admin who created the exchange. Unknown for exchanges created before it was recorded.
+    pub created_by: Option<UserId>,
+    pub created_at: UtcDateTime,
+}
+
+/// A jam whose entries can be submitted to an exchange.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExchangeJam {
+    pub jam_type: StoredJamType,
+    pub jam_link: String,
+}
+
+/// A jam that has active exchanges in a guild.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JamSummary {
+    pub jam_link: String,
+    pub active_exchanges: u32,
 }
 
-#[derive(Clone, Copy, Debug)]
+impl Exchange {
+    pub fn state_label(&self) -> &'static str {
+        self.state.label()
+    }
+
+    /// All jams accepted by the exchange, starting with the main one.
+    pub fn accepted_jams(&self) -> Vec<ExchangeJam> {
+        let main = ExchangeJam {
+            jam_type: self.jam_type.clone(),
+            jam_link: self.jam_link.clone(),
+        };
+
+        std::iter::once(main)
+            .chain(self.extra_jams.iter().cloned())
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
 pub enum ExchangeState {
     NotStartedYet,
     AcceptingSubmissions,
@@ -46,3 +121,143 @@ pub enum ExchangeState {
     MissedByBot,
     AssignmentError,
 }
+
+impl ExchangeState {
+    /// A short user-facing name of the state.
+    pub fn label(&self) -> &'static str {
+        use ExchangeState::*;
+
+        match self {
+            NotStartedYet => "Not started",
+            AcceptingSubmissions => "Open for submissions",
+            AssignmentsSent => "Assignments sent",
+            MissedByBot => "Missed (bot was offline)",
+            AssignmentError => "Error — contact admins",
+        }
+    }
+
+    /// A user-facing explanation of the state.
+    pub fn description(&self) -> &'static str {
+        use ExchangeState::*;
+
+        match self {
+            NotStartedYet => "Submissions will open when the exchange starts.",
+            AcceptingSubmissions => "Members can submit their entries until the exchange ends.",
+            AssignmentsSent => "Submissions are closed and the assignments were sent to members.",
+            MissedByBot => "The bot was offline when the exchange was supposed to start or end.",
+            AssignmentError => "Something went wrong while assigning entries to members.",
+        }
+    }
+}
+
+/// Exchanges for tests to start from. Tests override only the fields they care about.
+#[cfg(test)]
+mod fixtures {
+    use std::num::NonZeroU8;
+
+    use poise::serenity_prelude::{ChannelId, GuildId};
+    use time::{macros::datetime, Duration};
+
+    use crate::jam_types::{JamType, StoredJamType};
+
+    use super::{Exchange, ExchangeId, ExchangeState, NewExchange, UtcDateTime};
+
+    /// An Itch exchange accepting submissions, with every optional feature turned off.
+    pub fn test_exchange() -> Exchange {
+        Exchange {
+            id: ExchangeId(1),
+            guild: GuildId::new(2),
+            channel: ChannelId::new(3),
+            jam_type: StoredJamType::Known(JamType::Itch),
+            jam_link: "https://itch.io/jam/example-jam".to_string(),
+            slug: "Test".to_string(),
+            display_name: "Test".to_string(),
+            state: ExchangeState::AcceptingSubmissions,
+            submissions_start: UtcDateTime::assume_utc(datetime!(2024-01-01 12:00:00)),
+            submissions_end: UtcDateTime::assume_utc(datetime!(2024-01-01 13:00:00)),
+            games_per_member: NonZeroU8::new(5).unwrap(),
+            ignore_played: false,
+            blind: false,
+            public_confirmations: false,
+            max_submission_edits: 0,
+            min_account_age: Duration::ZERO,
+            extra_jams: Vec::new(),
+            reciprocal_penalty: 0,
+            revoke_lock: Duration::ZERO,
+            announce_stats: false,
+            created_by: None,
+            created_at: None,
+            assignments_sent_at: None,
+        }
+    }
+
+    /// The not yet started counterpart of [`test_exchange`].
+    pub fn test_new_exchange() -> NewExchange {
+        NewExchange {
+            guild: GuildId::new(2),
+            channel: ChannelId::new(3),
+            jam_type: JamType::Itch,
+            jam_link: "https://itch.io/jam/example-jam".to_string(),
+            slug: "Test".to_string(),
+            display_name: "Test".to_string(),
+            state: ExchangeState::NotStartedYet,
+            submissions_start: UtcDateTime::assume_utc(datetime!(2024-01-01 12:00:00)),
+            submissions_end: UtcDateTime::assume_utc(datetime!(2024-01-01 13:00:00)),
+            games_per_member: NonZeroU8::new(5).unwrap(),
+            ignore_played: false,
+            blind: false,
+            public_confirmations: false,
+            max_submission_edits: 0,
+            min_account_age: Duration::ZERO,
+            extra_jams: Vec::new(),
+            reciprocal_penalty: 0,
+            revoke_lock: Duration::ZERO,
+            announce_stats: false,
+            created_by: None,
+            created_at: UtcDateTime::assume_utc(datetime!(2024-01-01 00:00:00)),
+        }
+    }
+}
+
+#[cfg(test)]
+pub use fixtures::{test_exchange, test_new_exchange};
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use strum::IntoEnumIterator;
+
+    use super::ExchangeState;
+
+    #[test]
+    fn state_labels() {
+        assert_eq!(ExchangeState::NotStartedYet.label(), "Not started");
+        assert_eq!(
+            ExchangeState::AcceptingSubmissions.label(),
+            "Open for submissions"
+        );
+        assert_eq!(ExchangeState::AssignmentsSent.label(), "Assignments sent");
+        assert_eq!(
+            ExchangeState::MissedByBot.label(),
+            "Missed (bot was offline)"
+        );
+        assert_eq!(
+            ExchangeState::AssignmentError.label(),
+            "Error — contact admins"
+        );
+    }
+
+    #[test]
+    fn states_are_distinguishable() {
+        let labels = ExchangeState::iter()
+            .map(|state| state.label())
+            .collect::<HashSet<_>>();
+        let descriptions = ExchangeState::iter()
+            .map(|state| state.description())
+            .collect::<HashSet<_>>();
+
+        assert_eq!(labels.len(), ExchangeState::iter().count());
+        assert_eq!(descriptions.len(), ExchangeState::iter().count());
+    }
+}
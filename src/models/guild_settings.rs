@@ -0,0 +1,24 @@
+use poise::serenity_prelude::{ChannelId, GuildId};
+
+/// Per-guild configuration set by the guild admins.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GuildSettings {
+    pub guild: GuildId,
+    pub allow_bot_submissions: bool,
+    /// A regex exchange slugs must match as a whole, e.g. to require a year prefix.
+    pub slug_pattern: Option<String>,
+    /// A private channel or thread where the outcome of each assignment pass is logged.
+    pub assignment_log_channel: Option<ChannelId>,
+}
+
+impl GuildSettings {
+    /// Settings for a guild that hasn't configured anything yet.
+    pub fn default_for(guild: GuildId) -> GuildSettings {
+        GuildSettings {
+            guild,
+            allow_bot_submissions: false,
+            slug_pattern: None,
+            assignment_log_channel: None,
+        }
+    }
+}
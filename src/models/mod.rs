@@ -1,9 +1,15 @@
+mod assignment;
 mod exchange;
+mod guild_settings;
 mod played_game;
 mod submission;
 
 pub mod types;
 
-pub use exchange::{Exchange, ExchangeId, ExchangeState, NewExchange};
+pub use assignment::{Assignment, DeliveryStatus};
+#[cfg(test)]
+pub use exchange::{test_exchange, test_new_exchange};
+pub use exchange::{Exchange, ExchangeId, ExchangeJam, ExchangeState, JamSummary, NewExchange};
+pub use guild_settings::GuildSettings;
 pub use played_game::{PlayedGame, PlayedGameId};
-pub use submission::{NewSubmission, Submission, SubmissionId};
+pub use submission::{NewSubmission, Submission, SubmissionHistoryEntry, SubmissionId};
@@ -2,7 +2,7 @@ use poise::serenity_prelude::UserId;
 
 use super::{exchange::ExchangeId, types::UtcDateTime};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct SubmissionId(pub u64);
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -12,6 +12,7 @@ pub struct Submission {
     pub link: String,
     pub submitter: UserId,
     pub submitted_at: UtcDateTime,
+    pub edit_count: u32,
 }
 
 // TODO: Find a way to avoid such copy-paste
@@ -22,3 +23,11 @@ pub struct NewSubmission {
     pub submitter: UserId,
     pub submitted_at: UtcDateTime,
 }
+
+/// A member's submission along with the exchange it was made to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubmissionHistoryEntry {
+    pub exchange_slug: String,
+    pub link: String,
+    pub submitted_at: UtcDateTime,
+}
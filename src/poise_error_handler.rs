@@ -1,7 +1,10 @@
 use poise::{Context, CreateReply, FrameworkError};
 use tracing::{error, warn};
 
-use crate::{commands::CommandError, BotState};
+use crate::{
+    commands::{CommandError, GUILD_ONLY_MESSAGE},
+    BotState,
+};
 
 pub async fn handle_error(error: poise::FrameworkError<'_, BotState, CommandError>) {
     use FrameworkError::*;
@@ -101,7 +104,7 @@ pub async fn handle_error(error: poise::FrameworkError<'_, BotState, CommandErro
         }
 
         GuildOnly { ctx, .. } => {
-            reply_with_error(ctx, "Sorry, but you can only run this command in a server.").await;
+            reply_with_error(ctx, GUILD_ONLY_MESSAGE).await;
         }
 
         DmOnly { ctx, .. } => {
@@ -0,0 +1,160 @@
+use poise::serenity_prelude::UserId;
+use sqlx::{query, query_scalar, Pool, Sqlite};
+
+use crate::models::{types::UtcDateTime, ExchangeId};
+
+use super::conversion::DBConvertible;
+use super::slow_query::{TimedQuery, DEFAULT_SLOW_QUERY_THRESHOLD};
+
+/// Stores members' acknowledgements that they can receive assignments in their DMs.
+pub struct AckRepository {
+    pool: Pool<Sqlite>,
+    slow_query_threshold: std::time::Duration,
+}
+
+impl AckRepository {
+    pub fn new(pool: Pool<Sqlite>) -> AckRepository {
+        AckRepository {
+            pool,
+            slow_query_threshold: DEFAULT_SLOW_QUERY_THRESHOLD,
+        }
+    }
+
+    /// Sets how long a query may take before it's logged as slow.
+    pub fn with_slow_query_threshold(self, slow_query_threshold: std::time::Duration) -> Self {
+        Self {
+            slow_query_threshold,
+            ..self
+        }
+    }
+
+    pub async fn add_ack(
+        &self,
+        exchange_id: ExchangeId,
+        member: UserId,
+        acked_at: UtcDateTime,
+    ) -> Result<(), anyhow::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let exchange_id = exchange_id.to_db()?;
+        let member = member.to_db()?;
+        let acked_at = acked_at.to_db()?;
+        query!(
+            r#"
+                INSERT INTO acks (exchange_id, member, acked_at)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (exchange_id, member) DO NOTHING
+            "#,
+            exchange_id,
+            member,
+            acked_at,
+        )
+        .execute(&mut *transaction)
+        .timed("add_ack", self.slow_query_threshold)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Members who submitted an entry to the exchange but never acknowledged DMs.
+    pub async fn get_unacked_submitters(
+        &self,
+        exchange_id: ExchangeId,
+    ) -> Result<Vec<UserId>, anyhow::Error> {
+        let submitters = {
+            let exchange_id = exchange_id.to_db()?;
+
+            query_scalar!(
+                r#"
+                    SELECT submissions.submitter FROM submissions
+                    LEFT JOIN acks
+                        ON acks.exchange_id = submissions.exchange_id
+                        AND acks.member = submissions.submitter
+                    WHERE submissions.exchange_id = $1 AND acks.id IS NULL
+                    ORDER BY submissions.submitter
+                "#,
+                exchange_id,
+            )
+            .fetch_all(&self.pool)
+            .timed("get_unacked_submitters", self.slow_query_threshold)
+            .await?
+        };
+
+        Ok(submitters
+            .iter()
+            .map(UserId::from_db)
+            .collect::<Result<Vec<UserId>, _>>()?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serenity::all::UserId;
+    use sqlx::{query, sqlite::SqlitePoolOptions, SqlitePool};
+    use time::macros::datetime;
+
+    use crate::{
+        models::{types::UtcDateTime, ExchangeId},
+        repository::AckRepository,
+    };
+
+    async fn setup_database() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn submitted_but_not_acked() {
+        let pool = setup_database().await;
+        let repository = AckRepository::new(pool.clone());
+
+        {
+            let mut transaction = pool.begin().await.unwrap();
+
+            query!(
+                r#"
+                    INSERT INTO exchanges (id, guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member) 
+                    VALUES (1, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test', 'Test', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5),
+                           (4, 5, 6, 'Itch', 'https://itch.io/jam/example-jam-2', 'Test2', 'Test 2', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5);
+
+                    INSERT INTO submissions (id, exchange_id, link, submitter, submitted_at)
+                    VALUES (1, 1, 'https://itch.io/jam/example-jam/rate/000001', 7, '2024-01-01T00:01:00.000000000Z'),
+                           (2, 1, 'https://itch.io/jam/example-jam/rate/000002', 8, '2024-01-01T00:01:00.000000000Z'),
+                           (3, 1, 'https://itch.io/jam/example-jam/rate/000003', 9, '2024-01-01T00:01:00.000000000Z'),
+                           (4, 4, 'https://itch.io/jam/example-jam-2/rate/000004', 8, '2024-01-01T00:01:00.000000000Z');
+                "#
+            ).execute(&mut *transaction).await.unwrap();
+
+            transaction.commit().await.unwrap();
+        };
+
+        let acked_at = UtcDateTime::assume_utc(datetime!(2024-01-01 00:02:00));
+        repository
+            .add_ack(ExchangeId(1), UserId::new(7), acked_at)
+            .await
+            .unwrap();
+        // Acks for other exchanges don't count.
+        repository
+            .add_ack(ExchangeId(4), UserId::new(8), acked_at)
+            .await
+            .unwrap();
+        // Acking twice is fine.
+        repository
+            .add_ack(ExchangeId(1), UserId::new(7), acked_at)
+            .await
+            .unwrap();
+
+        let unacked = repository
+            .get_unacked_submitters(ExchangeId(1))
+            .await
+            .unwrap();
+
+        assert_eq!(unacked, vec![UserId::new(8), UserId::new(9)]);
+    }
+}
@@ -0,0 +1,549 @@
+use poise::serenity_prelude::UserId;
+use sqlx::{query, query_as, query_scalar, Pool, Sqlite};
+
+use crate::models::{Assignment, DeliveryStatus, ExchangeId, SubmissionId};
+
+use super::conversion::{DBConvertible, DBFromConversionError, DBToConversionError};
+use super::slow_query::{TimedQuery, DEFAULT_SLOW_QUERY_THRESHOLD};
+
+pub struct AssignmentRepository {
+    pool: Pool<Sqlite>,
+    slow_query_threshold: std::time::Duration,
+}
+
+impl AssignmentRepository {
+    pub fn new(pool: Pool<Sqlite>) -> AssignmentRepository {
+        AssignmentRepository {
+            pool,
+            slow_query_threshold: DEFAULT_SLOW_QUERY_THRESHOLD,
+        }
+    }
+
+    /// Sets how long a query may take before it's logged as slow.
+    pub fn with_slow_query_threshold(self, slow_query_threshold: std::time::Duration) -> Self {
+        Self {
+            slow_query_threshold,
+            ..self
+        }
+    }
+
+    pub async fn add_assignments(
+        &self,
+        exchange_id: ExchangeId,
+        assignments: &[(UserId, SubmissionId)],
+    ) -> Result<(), anyhow::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let exchange_id = exchange_id.to_db()?;
+        for (reviewer, submission_id) in assignments {
+            let reviewer = reviewer.to_db()?;
+            let submission_id = submission_id.to_db()?;
+
+            query!(
+                r#"
+                    INSERT INTO assignments (exchange_id, reviewer, submission_id)
+                    VALUES ($1, $2, $3)
+                    ON CONFLICT (reviewer, submission_id) DO NOTHING
+                "#,
+                exchange_id,
+                reviewer,
+                submission_id,
+            )
+            .execute(&mut *transaction)
+            .timed("add_assignments", self.slow_query_threshold)
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Records assignments of entries from the exchange's backfill pool, by link.
+    pub async fn add_backfill_assignments(
+        &self,
+        exchange_id: ExchangeId,
+        assignments: &[(UserId, String)],
+    ) -> Result<(), anyhow::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let exchange_id = exchange_id.to_db()?;
+        for (reviewer, link) in assignments {
+            let reviewer = reviewer.to_db()?;
+
+            query!(
+                r#"
+                    INSERT INTO assignments (exchange_id, reviewer, backfill_entry_id)
+                    SELECT $1, $2, id FROM backfill_entries
+                    WHERE exchange_id = $1 AND link = $3
+                    ON CONFLICT (reviewer, backfill_entry_id) DO NOTHING
+                "#,
+                exchange_id,
+                reviewer,
+                link,
+            )
+            .execute(&mut *transaction)
+            .timed("add_backfill_assignments", self.slow_query_threshold)
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn count_assignments_for_reviewer(
+        &self,
+        exchange_id: ExchangeId,
+        reviewer: UserId,
+    ) -> Result<u32, anyhow::Error> {
+        let count = {
+            let exchange_id = exchange_id.to_db()?;
+            let reviewer = reviewer.to_db()?;
+
+            query_scalar!(
+                r#"
+                    SELECT COUNT(*) AS "count!: i64" FROM assignments
+                    WHERE exchange_id = $1 AND reviewer = $2
+                "#,
+                exchange_id,
+                reviewer,
+            )
+            .fetch_one(&self.pool)
+            .timed("count_assignments_for_reviewer", self.slow_query_threshold)
+            .await?
+        };
+
+        Ok(count.try_into()?)
+    }
+
+    pub async fn get_assignments_for_exchange(
+        &self,
+        exchange_id: ExchangeId,
+    ) -> Result<Vec<Assignment>, anyhow::Error> {
+        let assignments = {
+            let exchange_id = exchange_id.to_db()?;
+
+            query_as!(
+                SqlAssignment,
+                r#"
+                    SELECT
+                        assignments.exchange_id,
+                        assignments.reviewer,
+                        assignments.submission_id,
+                        COALESCE(submissions.link, backfill_entries.link) AS "link!: String",
+                        assignments.delivered
+                    FROM assignments
+                    LEFT JOIN submissions ON submissions.id = assignments.submission_id
+                    LEFT JOIN backfill_entries ON backfill_entries.id = assignments.backfill_entry_id
+                    WHERE assignments.exchange_id = $1
+                    ORDER BY
+                        assignments.reviewer,
+                        assignments.submission_id IS NULL,
+                        assignments.submission_id,
+                        assignments.backfill_entry_id
+                "#,
+                exchange_id,
+            )
+            .fetch_all(&self.pool)
+            .timed("get_assignments_for_exchange", self.slow_query_threshold)
+            .await?
+            .iter()
+            .map(Assignment::from_db)
+            .collect::<Result<Vec<Assignment>, _>>()?
+        };
+
+        Ok(assignments)
+    }
+
+    /// Records whether the DM with all of the reviewer's assignments in the exchange was delivered.
+    pub async fn set_delivery_status(
+        &self,
+        exchange_id: ExchangeId,
+        reviewer: UserId,
+        status: DeliveryStatus,
+    ) -> Result<(), anyhow::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        {
+            let exchange_id = exchange_id.to_db()?;
+            let reviewer = reviewer.to_db()?;
+            let status = status.to_db()?;
+
+            query!(
+                r#"
+                    UPDATE assignments
+                    SET delivered = $1
+                    WHERE exchange_id = $2 AND reviewer = $3
+                "#,
+                status,
+                exchange_id,
+                reviewer,
+            )
+            .execute(&mut *transaction)
+            .timed("set_delivery_status", self.slow_query_threshold)
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Returns the number of deleted assignments.
+    pub async fn delete_assignments_for_exchange(
+        &self,
+        exchange_id: ExchangeId,
+    ) -> Result<u64, anyhow::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let deleted = {
+            let exchange_id = exchange_id.to_db()?;
+
+            query!(
+                r#"
+                    DELETE FROM assignments
+                    WHERE exchange_id = $1
+                "#,
+                exchange_id,
+            )
+            .execute(&mut *transaction)
+            .timed("delete_assignments_for_exchange", self.slow_query_threshold)
+            .await?
+            .rows_affected()
+        };
+
+        transaction.commit().await?;
+
+        Ok(deleted)
+    }
+}
+
+#[derive(Debug)]
+pub struct SqlAssignment {
+    exchange_id: i64,
+    reviewer: i64,
+    submission_id: Option<i64>,
+    link: String,
+    delivered: String,
+}
+
+impl DBConvertible for Assignment {
+    type DBType = SqlAssignment;
+
+    fn to_db(&self) -> Result<Self::DBType, DBToConversionError> {
+        Ok(SqlAssignment {
+            exchange_id: self.exchange_id.to_db()?,
+            reviewer: self.reviewer.to_db()?,
+            submission_id: self.submission_id.map(|id| id.to_db()).transpose()?,
+            link: self.link.clone(),
+            delivered: self.delivered.to_db()?,
+        })
+    }
+
+    fn from_db(value: &Self::DBType) -> Result<Self, DBFromConversionError> {
+        Ok(Assignment {
+            exchange_id: ExchangeId::from_db(&value.exchange_id)?,
+            reviewer: UserId::from_db(&value.reviewer)?,
+            submission_id: value
+                .submission_id
+                .as_ref()
+                .map(SubmissionId::from_db)
+                .transpose()?,
+            link: value.link.clone(),
+            delivered: DeliveryStatus::from_db(&value.delivered)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serenity::all::UserId;
+    use sqlx::{query, sqlite::SqlitePoolOptions, SqlitePool};
+
+    use crate::{
+        models::{Assignment, DeliveryStatus, ExchangeId, SubmissionId},
+        repository::AssignmentRepository,
+    };
+
+    async fn setup_database() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        {
+            let mut transaction = pool.begin().await.unwrap();
+
+            query!(
+                r#"
+                    INSERT INTO exchanges (id, guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member) 
+                    VALUES (1, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test', 'Test', 'AssignmentsSent', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5),
+                           (4, 5, 6, 'Itch', 'https://itch.io/jam/example-jam-2', 'Test2', 'Test 2', 'AssignmentsSent', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5);
+
+                    INSERT INTO submissions (id, exchange_id, link, submitter, submitted_at)
+                    VALUES (1, 4, 'https://itch.io/jam/example-jam-2/rate/000004', 7, '2024-01-01T00:01:00.000000000Z'),
+                           (2, 4, 'https://itch.io/jam/example-jam-2/rate/000005', 8, '2024-01-01T00:01:00.000000000Z'),
+                           (3, 1, 'https://itch.io/jam/example-jam/rate/000003', 9, '2024-01-01T00:01:00.000000000Z'),
+                           (4, 1, 'https://itch.io/jam/example-jam/rate/000006', 10, '2024-01-01T00:01:00.000000000Z');
+                "#
+            ).execute(&mut *transaction).await.unwrap();
+
+            transaction.commit().await.unwrap();
+        };
+
+        pool
+    }
+
+    #[tokio::test]
+    async fn no_assignments() {
+        let pool = setup_database().await;
+        let repository = AssignmentRepository::new(pool.clone());
+
+        repository
+            .add_assignments(
+                ExchangeId(4),
+                &[
+                    (UserId::new(7), SubmissionId(2)),
+                    (UserId::new(8), SubmissionId(1)),
+                ],
+            )
+            .await
+            .unwrap();
+
+        let assignments = repository
+            .get_assignments_for_exchange(ExchangeId(1))
+            .await
+            .unwrap();
+
+        assert!(assignments.is_empty());
+    }
+
+    #[tokio::test]
+    async fn multiple_assignments() {
+        let pool = setup_database().await;
+        let repository = AssignmentRepository::new(pool.clone());
+
+        repository
+            .add_assignments(
+                ExchangeId(4),
+                &[
+                    (UserId::new(8), SubmissionId(1)),
+                    (UserId::new(7), SubmissionId(2)),
+                ],
+            )
+            .await
+            .unwrap();
+        repository
+            .add_assignments(
+                ExchangeId(1),
+                &[
+                    (UserId::new(9), SubmissionId(4)),
+                    (UserId::new(10), SubmissionId(3)),
+                ],
+            )
+            .await
+            .unwrap();
+
+        let assignments = repository
+            .get_assignments_for_exchange(ExchangeId(4))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            assignments,
+            vec![
+                Assignment {
+                    exchange_id: ExchangeId(4),
+                    reviewer: UserId::new(7),
+                    submission_id: Some(SubmissionId(2)),
+                    link: "https://itch.io/jam/example-jam-2/rate/000005".to_string(),
+                    delivered: DeliveryStatus::Pending,
+                },
+                Assignment {
+                    exchange_id: ExchangeId(4),
+                    reviewer: UserId::new(8),
+                    submission_id: Some(SubmissionId(1)),
+                    link: "https://itch.io/jam/example-jam-2/rate/000004".to_string(),
+                    delivered: DeliveryStatus::Pending,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn duplicate_assignments() {
+        let pool = setup_database().await;
+        let repository = AssignmentRepository::new(pool.clone());
+
+        repository
+            .add_assignments(ExchangeId(4), &[(UserId::new(7), SubmissionId(2))])
+            .await
+            .unwrap();
+        repository
+            .add_assignments(ExchangeId(4), &[(UserId::new(7), SubmissionId(2))])
+            .await
+            .unwrap();
+
+        let assignments = repository
+            .get_assignments_for_exchange(ExchangeId(4))
+            .await
+            .unwrap();
+
+        assert_eq!(assignments.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn backfill_assignments() {
+        let pool = setup_database().await;
+        let repository = AssignmentRepository::new(pool.clone());
+
+        query!(
+            r#"
+                INSERT INTO backfill_entries (id, exchange_id, link)
+                VALUES (1, 4, 'https://itch.io/jam/example-jam-2/rate/000009');
+            "#
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        repository
+            .add_assignments(ExchangeId(4), &[(UserId::new(7), SubmissionId(2))])
+            .await
+            .unwrap();
+        repository
+            .add_backfill_assignments(
+                ExchangeId(4),
+                &[
+                    (
+                        UserId::new(7),
+                        "https://itch.io/jam/example-jam-2/rate/000009".to_string(),
+                    ),
+                    // Not in the pool
+                    (
+                        UserId::new(8),
+                        "https://itch.io/jam/example-jam-2/rate/000010".to_string(),
+                    ),
+                ],
+            )
+            .await
+            .unwrap();
+
+        let assignments = repository
+            .get_assignments_for_exchange(ExchangeId(4))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            assignments,
+            vec![
+                Assignment {
+                    exchange_id: ExchangeId(4),
+                    reviewer: UserId::new(7),
+                    submission_id: Some(SubmissionId(2)),
+                    link: "https://itch.io/jam/example-jam-2/rate/000005".to_string(),
+                    delivered: DeliveryStatus::Pending,
+                },
+                Assignment {
+                    exchange_id: ExchangeId(4),
+                    reviewer: UserId::new(7),
+                    submission_id: None,
+                    link: "https://itch.io/jam/example-jam-2/rate/000009".to_string(),
+                    delivered: DeliveryStatus::Pending,
+                },
+            ]
+        );
+        assert_eq!(
+            repository
+                .count_assignments_for_reviewer(ExchangeId(4), UserId::new(7))
+                .await
+                .unwrap(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn delivery_status() {
+        let pool = setup_database().await;
+        let repository = AssignmentRepository::new(pool.clone());
+
+        repository
+            .add_assignments(
+                ExchangeId(4),
+                &[
+                    (UserId::new(7), SubmissionId(2)),
+                    (UserId::new(8), SubmissionId(1)),
+                ],
+            )
+            .await
+            .unwrap();
+
+        repository
+            .set_delivery_status(ExchangeId(4), UserId::new(7), DeliveryStatus::Delivered)
+            .await
+            .unwrap();
+        repository
+            .set_delivery_status(ExchangeId(4), UserId::new(8), DeliveryStatus::Failed)
+            .await
+            .unwrap();
+
+        let statuses = repository
+            .get_assignments_for_exchange(ExchangeId(4))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|assignment| (assignment.reviewer, assignment.delivered))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            statuses,
+            vec![
+                (UserId::new(7), DeliveryStatus::Delivered),
+                (UserId::new(8), DeliveryStatus::Failed),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_assignments() {
+        let pool = setup_database().await;
+        let repository = AssignmentRepository::new(pool.clone());
+
+        repository
+            .add_assignments(
+                ExchangeId(4),
+                &[
+                    (UserId::new(7), SubmissionId(2)),
+                    (UserId::new(8), SubmissionId(1)),
+                ],
+            )
+            .await
+            .unwrap();
+        repository
+            .add_assignments(ExchangeId(1), &[(UserId::new(9), SubmissionId(4))])
+            .await
+            .unwrap();
+
+        let deleted = repository
+            .delete_assignments_for_exchange(ExchangeId(4))
+            .await
+            .unwrap();
+
+        assert_eq!(deleted, 2);
+        assert!(repository
+            .get_assignments_for_exchange(ExchangeId(4))
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            repository
+                .get_assignments_for_exchange(ExchangeId(1))
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use sqlx::{query, query_scalar, Pool, Sqlite};
+
+use crate::models::ExchangeId;
+
+use super::conversion::DBConvertible;
+use super::slow_query::{TimedQuery, DEFAULT_SLOW_QUERY_THRESHOLD};
+
+/// Stores the entries from outside an exchange that top up reviewers who can't get enough
+/// entries of the exchange itself.
+pub struct BackfillRepository {
+    pool: Pool<Sqlite>,
+    slow_query_threshold: std::time::Duration,
+}
+
+impl BackfillRepository {
+    pub fn new(pool: Pool<Sqlite>) -> BackfillRepository {
+        BackfillRepository {
+            pool,
+            slow_query_threshold: DEFAULT_SLOW_QUERY_THRESHOLD,
+        }
+    }
+
+    /// Sets how long a query may take before it's logged as slow.
+    pub fn with_slow_query_threshold(self, slow_query_threshold: std::time::Duration) -> Self {
+        Self {
+            slow_query_threshold,
+            ..self
+        }
+    }
+
+    /// Adds the links to the exchange's backfill pool. Returns how many of them were new.
+    pub async fn add_backfill_entries(
+        &self,
+        exchange_id: ExchangeId,
+        links: &[String],
+    ) -> Result<u64, anyhow::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let exchange_id = exchange_id.to_db()?;
+        let mut added = 0;
+        for link in links {
+            added += query!(
+                r#"
+                    INSERT INTO backfill_entries (exchange_id, link)
+                    VALUES ($1, $2)
+                    ON CONFLICT (exchange_id, link) DO NOTHING
+                "#,
+                exchange_id,
+                link,
+            )
+            .execute(&mut *transaction)
+            .timed("add_backfill_entries", self.slow_query_threshold)
+            .await?
+            .rows_affected();
+        }
+
+        transaction.commit().await?;
+
+        Ok(added)
+    }
+
+    pub async fn get_backfill_entries(
+        &self,
+        exchange_id: ExchangeId,
+    ) -> Result<Vec<String>, anyhow::Error> {
+        let exchange_id = exchange_id.to_db()?;
+
+        let links = query_scalar!(
+            r#"
+                SELECT link FROM backfill_entries
+                WHERE exchange_id = $1
+                ORDER BY id
+            "#,
+            exchange_id,
+        )
+        .fetch_all(&self.pool)
+        .timed("get_backfill_entries", self.slow_query_threshold)
+        .await?;
+
+        Ok(links)
+    }
+
+    /// Empties the exchange's backfill pool. Returns how many entries were removed.
+    pub async fn clear_backfill_entries(
+        &self,
+        exchange_id: ExchangeId,
+    ) -> Result<u64, anyhow::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let exchange_id = exchange_id.to_db()?;
+        let removed = query!(
+            r#"
+                DELETE FROM backfill_entries
+                WHERE exchange_id = $1
+            "#,
+            exchange_id,
+        )
+        .execute(&mut *transaction)
+        .timed("clear_backfill_entries", self.slow_query_threshold)
+        .await?
+        .rows_affected();
+
+        transaction.commit().await?;
+
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use sqlx::{query, sqlite::SqlitePoolOptions, SqlitePool};
+
+    use crate::{models::ExchangeId, repository::BackfillRepository};
+
+    async fn setup_database() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        query!(
+            r#"
+                INSERT INTO exchanges (id, guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member) 
+                VALUES (1, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test', 'Test', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5),
+                       (4, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test2', 'Test 2', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5);
+            "#
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        pool
+    }
+
+    fn links(entries: &[u64]) -> Vec<String> {
+        entries
+            .iter()
+            .map(|entry| format!("https://itch.io/jam/previous-jam/rate/00000{entry}"))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn backfill_pool() {
+        let pool = setup_database().await;
+        let repository = BackfillRepository::new(pool);
+
+        assert_eq!(
+            repository
+                .add_backfill_entries(ExchangeId(1), &links(&[1, 2]))
+                .await
+                .unwrap(),
+            2
+        );
+        // Already pooled links are skipped
+        assert_eq!(
+            repository
+                .add_backfill_entries(ExchangeId(1), &links(&[2, 3]))
+                .await
+                .unwrap(),
+            1
+        );
+        repository
+            .add_backfill_entries(ExchangeId(4), &links(&[4]))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            repository
+                .get_backfill_entries(ExchangeId(1))
+                .await
+                .unwrap(),
+            links(&[1, 2, 3])
+        );
+
+        assert_eq!(
+            repository
+                .clear_backfill_entries(ExchangeId(1))
+                .await
+                .unwrap(),
+            3
+        );
+        assert!(repository
+            .get_backfill_entries(ExchangeId(1))
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            repository
+                .get_backfill_entries(ExchangeId(4))
+                .await
+                .unwrap(),
+            links(&[4])
+        );
+    }
+}
@@ -5,8 +5,10 @@ use thiserror::Error;
 use time::{format_description::well_known::Iso8601, OffsetDateTime};
 
 use crate::{
-    jam_types::JamType,
-    models::{types::UtcDateTime, ExchangeId, ExchangeState, PlayedGameId, SubmissionId},
+    jam_types::{JamType, StoredJamType},
+    models::{
+        types::UtcDateTime, DeliveryStatus, ExchangeId, ExchangeState, PlayedGameId, SubmissionId,
+    },
 };
 
 pub trait DBConvertible: Sized {
@@ -147,6 +149,29 @@ impl DBConvertible for ExchangeState {
     }
 }
 
+impl DBConvertible for DeliveryStatus {
+    type DBType = String;
+
+    fn to_db(&self) -> Result<Self::DBType, DBToConversionError> {
+        Ok(match self {
+            DeliveryStatus::Pending => "Pending",
+            DeliveryStatus::Delivered => "Delivered",
+            DeliveryStatus::Failed => "Failed",
+        }
+        .to_string())
+    }
+
+    fn from_db(value: &Self::DBType) -> Result<Self, DBFromConversionError> {
+        match value.as_str() {
+            "Pending" => Ok(DeliveryStatus::Pending),
+            "Delivered" => Ok(DeliveryStatus::Delivered),
+            "Failed" => Ok(DeliveryStatus::Failed),
+
+            unknown => Err(DBFromConversionError::NoSuchVariant(unknown.to_string())),
+        }
+    }
+}
+
 impl DBConvertible for JamType {
     type DBType = String;
 
@@ -168,6 +193,26 @@ impl DBConvertible for JamType {
     }
 }
 
+/// Jam types that are no longer supported are read as [`StoredJamType::Legacy`].
+impl DBConvertible for StoredJamType {
+    type DBType = String;
+
+    fn to_db(&self) -> Result<Self::DBType, DBToConversionError> {
+        match self {
+            StoredJamType::Known(jam_type) => jam_type.to_db(),
+            StoredJamType::Legacy(name) => Ok(name.clone()),
+        }
+    }
+
+    fn from_db(value: &Self::DBType) -> Result<Self, DBFromConversionError> {
+        match JamType::from_db(value) {
+            Ok(jam_type) => Ok(StoredJamType::Known(jam_type)),
+            Err(DBFromConversionError::NoSuchVariant(name)) => Ok(StoredJamType::Legacy(name)),
+            Err(err) => Err(err),
+        }
+    }
+}
+
 impl DBConvertible for NonZeroU8 {
     type DBType = i64;
 
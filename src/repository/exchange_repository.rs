@@ -1,20 +1,26 @@
-use std::num::NonZeroU8;
+use std::{collections::HashMap, num::NonZeroU8};
 
-use poise::serenity_prelude::{ChannelId, GuildId};
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
 use sqlx::{query, query_as, query_scalar, Pool, Sqlite};
+use time::Duration;
 use tokio::sync::broadcast::{Receiver, Sender};
 use tracing::warn;
 
 use crate::{
-    jam_types::JamType,
-    models::{types::UtcDateTime, Exchange, ExchangeId, ExchangeState, NewExchange},
+    jam_types::{normalize_any_jam_link, StoredJamType},
+    models::{
+        types::UtcDateTime, Exchange, ExchangeId, ExchangeJam, ExchangeState, JamSummary,
+        NewExchange,
+    },
 };
 
 use super::conversion::{DBConvertible, DBFromConversionError};
+use super::slow_query::{TimedQuery, DEFAULT_SLOW_QUERY_THRESHOLD};
 
 #[derive(Debug)]
 pub struct ExchangeRepository {
     pool: Pool<Sqlite>,
+    slow_query_threshold: std::time::Duration,
     events: Sender<ExchangeStorageEvent>,
 }
 
@@ -23,11 +29,31 @@ pub enum ExchangeStorageEvent {
     ExchangesUpdated,
 }
 
+pub const DEFAULT_EVENT_CAPACITY: usize = 128;
+
 impl ExchangeRepository {
     pub fn new(pool: Pool<Sqlite>) -> ExchangeRepository {
+        Self::with_event_capacity(pool, DEFAULT_EVENT_CAPACITY)
+    }
+
+    /// Creates the repository with an events channel holding up to `event_capacity` events.
+    ///
+    /// Subscribers that fall behind by more than that receive `RecvError::Lagged` and miss the
+    /// oldest events. Since all events only signal that exchanges changed, a lagging subscriber
+    /// should treat the lag as one more `ExchangesUpdated`.
+    pub fn with_event_capacity(pool: Pool<Sqlite>, event_capacity: usize) -> ExchangeRepository {
         ExchangeRepository {
             pool,
-            events: tokio::sync::broadcast::channel(128).0,
+            slow_query_threshold: DEFAULT_SLOW_QUERY_THRESHOLD,
+            events: tokio::sync::broadcast::channel(event_capacity).0,
+        }
+    }
+
+    /// Sets how long a query may take before it's logged as slow.
+    pub fn with_slow_query_threshold(self, slow_query_threshold: std::time::Duration) -> Self {
+        Self {
+            slow_query_threshold,
+            ..self
         }
     }
 
@@ -38,10 +64,21 @@ impl ExchangeRepository {
             let guild = exchange.guild.to_db()?;
             let channel = exchange.channel.to_db()?;
             let jam_type = exchange.jam_type.to_db()?;
+            let slug = exchange.slug.trim();
             let state = ExchangeState::NotStartedYet.to_db()?;
             let submissions_start = exchange.submissions_start.to_db()?;
             let submissions_end = exchange.submissions_end.to_db()?;
             let games_per_member = exchange.games_per_member.to_db()?;
+            let ignore_played = exchange.ignore_played;
+            let blind = exchange.blind;
+            let public_confirmations = exchange.public_confirmations;
+            let max_submission_edits = exchange.max_submission_edits;
+            let min_account_age_seconds = exchange.min_account_age.whole_seconds();
+            let reciprocal_penalty = exchange.reciprocal_penalty;
+            let revoke_lock_seconds = exchange.revoke_lock.whole_seconds();
+            let announce_stats = exchange.announce_stats;
+            let created_by = exchange.created_by.map(|user| user.to_db()).transpose()?;
+            let created_at = exchange.created_at.to_db()?;
 
             query_as!(
                 SqlExchange,
@@ -56,8 +93,18 @@ impl ExchangeRepository {
                     state,
                     submissions_start,
                     submissions_end,
-                    games_per_member)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                    games_per_member,
+                    ignore_played,
+                    blind,
+                    public_confirmations,
+                    max_submission_edits,
+                    min_account_age_seconds,
+                    reciprocal_penalty,
+                    revoke_lock_seconds,
+                    announce_stats,
+                    created_by,
+                    created_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
                 RETURNING
                     id AS "id!",
                     guild AS "guild!",
@@ -69,29 +116,71 @@ impl ExchangeRepository {
                     state AS "state!",
                     submissions_start AS "submissions_start!",
                     submissions_end AS "submissions_end!",
-                    games_per_member AS "games_per_member!"
+                    games_per_member AS "games_per_member!",
+                    ignore_played AS "ignore_played!",
+                    blind AS "blind!",
+                    public_confirmations AS "public_confirmations!",
+                    max_submission_edits AS "max_submission_edits!",
+                    min_account_age_seconds AS "min_account_age_seconds!",
+                    reciprocal_penalty AS "reciprocal_penalty!",
+                    revoke_lock_seconds AS "revoke_lock_seconds!",
+                    announce_stats AS "announce_stats!",
+                    created_by,
+                    created_at,
+                    assignments_sent_at
                 "#,
                 guild,
                 channel,
                 jam_type,
                 exchange.jam_link,
-                exchange.slug,
+                slug,
                 exchange.display_name,
                 state,
                 submissions_start,
                 submissions_end,
                 games_per_member,
+                ignore_played,
+                blind,
+                public_confirmations,
+                max_submission_edits,
+                min_account_age_seconds,
+                reciprocal_penalty,
+                revoke_lock_seconds,
+                announce_stats,
+                created_by,
+                created_at,
             )
             .fetch_one(&mut *transaction)
+            .timed("create_exchange", self.slow_query_threshold)
             .await?
         };
 
+        let mut extra_jams = Vec::with_capacity(exchange.extra_jams.len());
+        for jam in &exchange.extra_jams {
+            let jam = jam.to_db()?;
+
+            query!(
+                r#"
+                INSERT INTO exchange_jams (exchange_id, jam_type, jam_link)
+                VALUES ($1, $2, $3)
+                "#,
+                created_exchange.id,
+                jam.jam_type,
+                jam.jam_link,
+            )
+            .execute(&mut *transaction)
+            .timed("create_exchange_jam", self.slow_query_threshold)
+            .await?;
+
+            extra_jams.push(jam);
+        }
+
         transaction.commit().await?;
 
         // Don't care if it actually gets received
         let _ = self.events.send(ExchangeStorageEvent::ExchangesUpdated);
 
-        Ok(Exchange::from_db(&created_exchange)?)
+        Ok(Exchange::from_db(&(created_exchange, extra_jams))?)
     }
 
     pub async fn get_overlapping_exchanges(
@@ -102,13 +191,12 @@ impl ExchangeRepository {
         start: UtcDateTime,
         end: UtcDateTime,
     ) -> Result<Vec<Exchange>, anyhow::Error> {
-        let mut transaction = self.pool.begin().await?;
-
         let overlapping_exchanges = {
             let guild = guild.to_db()?;
             let channel = channel.to_db()?;
             let start = start.to_db()?;
             let end = end.to_db()?;
+            let slug = slug.trim();
 
             query_as!(
                 SqlExchange,
@@ -123,18 +211,12 @@ impl ExchangeRepository {
                 end,
                 slug,
             )
-            .fetch_all(&mut *transaction)
+            .fetch_all(&self.pool)
+            .timed("get_overlapping_exchanges", self.slow_query_threshold)
             .await?
         };
 
-        transaction.commit().await?;
-
-        let overlapping_exchanges: Result<Vec<Exchange>, DBFromConversionError> =
-            overlapping_exchanges
-                .iter()
-                .map(Exchange::from_db)
-                .collect();
-        Ok(overlapping_exchanges?)
+        self.load_exchanges(overlapping_exchanges).await
     }
 
     pub async fn get_running_exchange(
@@ -143,8 +225,6 @@ impl ExchangeRepository {
         channel: ChannelId,
         date: UtcDateTime,
     ) -> Result<Option<Exchange>, anyhow::Error> {
-        let mut transaction = self.pool.begin().await?;
-
         let running_exchange = {
             let guild = guild.to_db()?;
             let channel = channel.to_db()?;
@@ -166,15 +246,73 @@ impl ExchangeRepository {
                 date,
                 accepting_submissions,
             )
-            .fetch_optional(&mut *transaction)
+            .fetch_optional(&self.pool)
+            .timed("get_running_exchange", self.slow_query_threshold)
             .await?
         };
 
-        transaction.commit().await?;
+        self.load_exchange(running_exchange).await
+    }
+
+    /// Like [`Self::get_running_exchange`], but ignores the exchange state. Only meant for
+    /// explaining to members why they can't submit, never for accepting submissions.
+    pub async fn get_exchange_in_window(
+        &self,
+        guild: GuildId,
+        channel: ChannelId,
+        date: UtcDateTime,
+    ) -> Result<Option<Exchange>, anyhow::Error> {
+        let exchange = {
+            let guild = guild.to_db()?;
+            let channel = channel.to_db()?;
+            let date = date.to_db()?;
 
-        Ok(running_exchange
-            .map(|e| Exchange::from_db(&e))
-            .transpose()?)
+            query_as!(
+                SqlExchange,
+                r#"
+                SELECT * FROM exchanges
+                WHERE guild = $1 
+                    AND channel = $2 
+                    AND submissions_start <= $3 
+                    AND submissions_end >= $3
+                ORDER BY submissions_start DESC
+                LIMIT 1
+                "#,
+                guild,
+                channel,
+                date,
+            )
+            .fetch_optional(&self.pool)
+            .timed("get_exchange_in_window", self.slow_query_threshold)
+            .await?
+        };
+
+        self.load_exchange(exchange).await
+    }
+
+    pub async fn get_exchange_by_slug(
+        &self,
+        guild: GuildId,
+        slug: &str,
+    ) -> Result<Option<Exchange>, anyhow::Error> {
+        let exchange = {
+            let guild = guild.to_db()?;
+
+            query_as!(
+                SqlExchange,
+                r#"
+                SELECT * FROM exchanges
+                WHERE guild = $1 AND slug = $2
+                "#,
+                guild,
+                slug,
+            )
+            .fetch_optional(&self.pool)
+            .timed("get_exchange_by_slug", self.slow_query_threshold)
+            .await?
+        };
+
+        self.load_exchange(exchange).await
     }
 
     pub async fn get_upcoming_exchanges_in_guild(
@@ -182,8 +320,6 @@ impl ExchangeRepository {
         guild: GuildId,
         after: UtcDateTime,
     ) -> Result<Vec<Exchange>, anyhow::Error> {
-        let mut transaction = self.pool.begin().await?;
-
         let upcoming_exchanges = {
             let guild = guild.to_db()?;
             let after = after.to_db()?;
@@ -198,23 +334,92 @@ impl ExchangeRepository {
                 guild,
                 after,
             )
-            .fetch_all(&mut *transaction)
+            .fetch_all(&self.pool)
+            .timed("get_upcoming_exchanges_in_guild", self.slow_query_threshold)
             .await?
         };
 
-        transaction.commit().await?;
+        self.load_exchanges(upcoming_exchanges).await
+    }
+
+    /// All exchanges of the guild that accept entries of the jam, either as their main jam or as
+    /// an extra one, regardless of their state.
+    pub async fn get_exchanges_by_jam_link(
+        &self,
+        guild: GuildId,
+        jam_link: &str,
+    ) -> Result<Vec<Exchange>, anyhow::Error> {
+        let exchanges = {
+            let guild = guild.to_db()?;
+            let jam_link = normalize_any_jam_link(jam_link);
+
+            query_as!(
+                SqlExchange,
+                r#"
+                SELECT * FROM exchanges
+                WHERE guild = $1 AND (
+                    jam_link = $2
+                    OR id IN (SELECT exchange_id FROM exchange_jams WHERE jam_link = $2)
+                )
+                ORDER BY submissions_start, display_name
+                "#,
+                guild,
+                jam_link,
+            )
+            .fetch_all(&self.pool)
+            .timed("get_exchanges_by_jam_link", self.slow_query_threshold)
+            .await?
+        };
 
-        let upcoming_exchanges: Result<Vec<Exchange>, DBFromConversionError> =
-            upcoming_exchanges.iter().map(Exchange::from_db).collect();
-        Ok(upcoming_exchanges?)
+        self.load_exchanges(exchanges).await
+    }
+
+    pub async fn get_active_jams_in_guild(
+        &self,
+        guild: GuildId,
+    ) -> Result<Vec<JamSummary>, anyhow::Error> {
+        let jams = {
+            let guild = guild.to_db()?;
+            let not_started_yet = ExchangeState::NotStartedYet.to_db()?;
+            let accepting_submissions = ExchangeState::AcceptingSubmissions.to_db()?;
+
+            query!(
+                r#"
+                SELECT jam_link AS "jam_link!", COUNT(*) AS "active_exchanges!: i64"
+                FROM (
+                    SELECT jam_link, guild, state FROM exchanges
+                    UNION ALL
+                    SELECT exchange_jams.jam_link, exchanges.guild, exchanges.state
+                    FROM exchange_jams
+                    JOIN exchanges ON exchanges.id = exchange_jams.exchange_id
+                )
+                WHERE guild = $1 AND state IN ($2, $3)
+                GROUP BY jam_link
+                ORDER BY jam_link
+                "#,
+                guild,
+                not_started_yet,
+                accepting_submissions,
+            )
+            .fetch_all(&self.pool)
+            .timed("get_active_jams_in_guild", self.slow_query_threshold)
+            .await?
+        };
+
+        jams.into_iter()
+            .map(|jam| {
+                Ok(JamSummary {
+                    jam_link: jam.jam_link,
+                    active_exchanges: jam.active_exchanges.try_into()?,
+                })
+            })
+            .collect()
     }
 
     pub async fn get_starting_exchanges(
         &self,
         date: UtcDateTime,
     ) -> Result<Vec<Exchange>, anyhow::Error> {
-        let mut transaction = self.pool.begin().await?;
-
         let starting_exchanges = {
             let not_started_yet = ExchangeState::NotStartedYet.to_db()?;
             let date = date.to_db()?;
@@ -229,23 +434,18 @@ impl ExchangeRepository {
                 not_started_yet,
                 date,
             )
-            .fetch_all(&mut *transaction)
+            .fetch_all(&self.pool)
+            .timed("get_starting_exchanges", self.slow_query_threshold)
             .await?
         };
 
-        transaction.commit().await?;
-
-        let starting_exchanges: Result<Vec<Exchange>, DBFromConversionError> =
-            starting_exchanges.iter().map(Exchange::from_db).collect();
-        Ok(starting_exchanges?)
+        self.load_exchanges(starting_exchanges).await
     }
 
     pub async fn get_ending_exchanges(
         &self,
         date: UtcDateTime,
     ) -> Result<Vec<Exchange>, anyhow::Error> {
-        let mut transaction = self.pool.begin().await?;
-
         let ending_exchanges = {
             let accepting_submissions = ExchangeState::AcceptingSubmissions.to_db()?;
             let date = date.to_db()?;
@@ -260,22 +460,38 @@ impl ExchangeRepository {
                 accepting_submissions,
                 date,
             )
-            .fetch_all(&mut *transaction)
+            .fetch_all(&self.pool)
+            .timed("get_ending_exchanges", self.slow_query_threshold)
             .await?
         };
 
-        transaction.commit().await?;
+        self.load_exchanges(ending_exchanges).await
+    }
+
+    pub async fn get_accepting_exchanges(&self) -> Result<Vec<Exchange>, anyhow::Error> {
+        let accepting_exchanges = {
+            let accepting_submissions = ExchangeState::AcceptingSubmissions.to_db()?;
 
-        let ending_exchanges: Result<Vec<Exchange>, DBFromConversionError> =
-            ending_exchanges.iter().map(Exchange::from_db).collect();
-        Ok(ending_exchanges?)
+            query_as!(
+                SqlExchange,
+                r#"
+                SELECT * FROM exchanges
+                WHERE state = $1
+                ORDER BY submissions_end, guild
+                "#,
+                accepting_submissions,
+            )
+            .fetch_all(&self.pool)
+            .timed("get_accepting_exchanges", self.slow_query_threshold)
+            .await?
+        };
+
+        self.load_exchanges(accepting_exchanges).await
     }
 
     pub async fn get_closest_exchange_end_or_start_date(
         &self,
     ) -> Result<Option<UtcDateTime>, anyhow::Error> {
-        let mut transaction = self.pool.begin().await?;
-
         let upcoming_exchange_date = {
             let not_started_yet = ExchangeState::NotStartedYet.to_db()?;
             let accepting_submissions = ExchangeState::AcceptingSubmissions.to_db()?;
@@ -293,12 +509,14 @@ impl ExchangeRepository {
                 not_started_yet,
                 accepting_submissions,
             )
-            .fetch_optional(&mut *transaction)
+            .fetch_optional(&self.pool)
+            .timed(
+                "get_closest_exchange_end_or_start_date",
+                self.slow_query_threshold,
+            )
             .await?
         };
 
-        transaction.commit().await?;
-
         match upcoming_exchange_date {
             Some(date) => Ok(Some(UtcDateTime::from_db(&date)?)),
             None => Ok(None),
@@ -322,6 +540,35 @@ impl ExchangeRepository {
             exchange_id,
         )
         .execute(&mut *transaction)
+        .timed("update_exchange_state", self.slow_query_threshold)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Moves the exchange to `AssignmentsSent` and records when that happened.
+    pub async fn mark_assignments_sent(
+        &self,
+        exchange_id: ExchangeId,
+        sent_at: UtcDateTime,
+    ) -> Result<(), anyhow::Error> {
+        let mut transaction = self.pool.begin().await?;
+        let state = ExchangeState::AssignmentsSent.to_db()?;
+        let sent_at = sent_at.to_db()?;
+        let exchange_id = exchange_id.to_db()?;
+
+        query!(
+            r#"
+            UPDATE exchanges SET state = $1, assignments_sent_at = $2 WHERE id = $3
+            "#,
+            state,
+            sent_at,
+            exchange_id,
+        )
+        .execute(&mut *transaction)
+        .timed("mark_assignments_sent", self.slow_query_threshold)
         .await?;
 
         transaction.commit().await?;
@@ -339,6 +586,7 @@ impl ExchangeRepository {
             slug,
         )
         .execute(&mut *transaction)
+        .timed("delete_exchange", self.slow_query_threshold)
         .await?;
 
         transaction.commit().await?;
@@ -357,6 +605,64 @@ impl ExchangeRepository {
     pub fn subscribe(&self) -> Receiver<ExchangeStorageEvent> {
         self.events.subscribe()
     }
+
+    /// Completes the fetched exchanges with their extra jams, which are stored separately.
+    async fn load_exchanges(
+        &self,
+        exchanges: Vec<SqlExchange>,
+    ) -> Result<Vec<Exchange>, anyhow::Error> {
+        if exchanges.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // SQLite can't bind a list, so the IDs are passed as a JSON array
+        let exchange_ids = format!(
+            "[{}]",
+            exchanges
+                .iter()
+                .map(|exchange| exchange.id.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+
+        let jam_rows = query!(
+            r#"
+            SELECT exchange_id, jam_type, jam_link FROM exchange_jams
+            WHERE exchange_id IN (SELECT value FROM json_each($1))
+            ORDER BY id
+            "#,
+            exchange_ids,
+        )
+        .fetch_all(&self.pool)
+        .timed("get_exchange_jams", self.slow_query_threshold)
+        .await?;
+
+        let mut extra_jams: HashMap<i64, Vec<SqlExchangeJam>> = HashMap::new();
+        for row in jam_rows {
+            extra_jams
+                .entry(row.exchange_id)
+                .or_default()
+                .push(SqlExchangeJam {
+                    jam_type: row.jam_type,
+                    jam_link: row.jam_link,
+                });
+        }
+
+        exchanges
+            .into_iter()
+            .map(|exchange| {
+                let jams = extra_jams.remove(&exchange.id).unwrap_or_default();
+                Ok(Exchange::from_db(&(exchange, jams))?)
+            })
+            .collect()
+    }
+
+    async fn load_exchange(
+        &self,
+        exchange: Option<SqlExchange>,
+    ) -> Result<Option<Exchange>, anyhow::Error> {
+        Ok(self.load_exchanges(Vec::from_iter(exchange)).await?.pop())
+    }
 }
 
 pub struct SqlExchange {
@@ -371,13 +677,54 @@ pub struct SqlExchange {
     submissions_start: String,
     submissions_end: String,
     games_per_member: i64,
+    ignore_played: i64,
+    blind: i64,
+    public_confirmations: i64,
+    max_submission_edits: i64,
+    min_account_age_seconds: i64,
+    reciprocal_penalty: i64,
+    revoke_lock_seconds: i64,
+    announce_stats: i64,
+    created_by: Option<i64>,
+    created_at: Option<String>,
+    assignments_sent_at: Option<String>,
 }
 
+pub struct SqlExchangeJam {
+    jam_type: String,
+    jam_link: String,
+}
+
+impl DBConvertible for ExchangeJam {
+    type DBType = SqlExchangeJam;
+
+    fn to_db(&self) -> Result<Self::DBType, super::conversion::DBToConversionError> {
+        Ok(SqlExchangeJam {
+            jam_type: self.jam_type.to_db()?,
+            jam_link: self.jam_link.clone(),
+        })
+    }
+
+    fn from_db(value: &Self::DBType) -> Result<Self, super::conversion::DBFromConversionError> {
+        Ok(ExchangeJam {
+            jam_type: StoredJamType::from_db(&value.jam_type)?,
+            jam_link: value.jam_link.clone(),
+        })
+    }
+}
+
+/// An exchange row along with its extra jams.
 impl DBConvertible for Exchange {
-    type DBType = SqlExchange;
+    type DBType = (SqlExchange, Vec<SqlExchangeJam>);
 
     fn to_db(&self) -> Result<Self::DBType, super::conversion::DBToConversionError> {
-        Ok(SqlExchange {
+        let extra_jams = self
+            .extra_jams
+            .iter()
+            .map(ExchangeJam::to_db)
+            .collect::<Result<_, _>>()?;
+
+        let exchange = SqlExchange {
             id: self.id.to_db()?,
             guild: self.guild.to_db()?,
             channel: self.channel.to_db()?,
@@ -389,15 +736,33 @@ impl DBConvertible for Exchange {
             submissions_start: self.submissions_start.to_db()?,
             submissions_end: self.submissions_end.to_db()?,
             games_per_member: self.games_per_member.to_db()?,
-        })
+            ignore_played: if self.ignore_played { 1 } else { 0 },
+            blind: if self.blind { 1 } else { 0 },
+            public_confirmations: if self.public_confirmations { 1 } else { 0 },
+            max_submission_edits: self.max_submission_edits.into(),
+            min_account_age_seconds: self.min_account_age.whole_seconds(),
+            reciprocal_penalty: self.reciprocal_penalty.into(),
+            revoke_lock_seconds: self.revoke_lock.whole_seconds(),
+            announce_stats: if self.announce_stats { 1 } else { 0 },
+            created_by: self.created_by.map(|user| user.to_db()).transpose()?,
+            created_at: self.created_at.map(|time| time.to_db()).transpose()?,
+            assignments_sent_at: self
+                .assignments_sent_at
+                .map(|time| time.to_db())
+                .transpose()?,
+        };
+
+        Ok((exchange, extra_jams))
     }
 
-    fn from_db(value: &Self::DBType) -> Result<Self, super::conversion::DBFromConversionError> {
+    fn from_db(
+        (value, extra_jams): &Self::DBType,
+    ) -> Result<Self, super::conversion::DBFromConversionError> {
         Ok(Exchange {
             id: ExchangeId::from_db(&value.id)?,
             guild: GuildId::from_db(&value.guild)?,
             channel: ChannelId::from_db(&value.channel)?,
-            jam_type: JamType::from_db(&value.jam_type)?,
+            jam_type: StoredJamType::from_db(&value.jam_type)?,
             jam_link: value.jam_link.clone(),
             slug: value.slug.clone(),
             display_name: value.display_name.clone(),
@@ -405,6 +770,614 @@ impl DBConvertible for Exchange {
             submissions_start: UtcDateTime::from_db(&value.submissions_start)?,
             submissions_end: UtcDateTime::from_db(&value.submissions_end)?,
             games_per_member: NonZeroU8::from_db(&value.games_per_member)?,
+            ignore_played: value.ignore_played > 0,
+            blind: value.blind > 0,
+            public_confirmations: value.public_confirmations > 0,
+            max_submission_edits: value
+                .max_submission_edits
+                .try_into()
+                .map_err(|_| DBFromConversionError::InvalidNumber(value.max_submission_edits))?,
+            min_account_age: Duration::seconds(value.min_account_age_seconds),
+            extra_jams: extra_jams
+                .iter()
+                .map(ExchangeJam::from_db)
+                .collect::<Result<_, _>>()?,
+            reciprocal_penalty: value
+                .reciprocal_penalty
+                .try_into()
+                .map_err(|_| DBFromConversionError::InvalidNumber(value.reciprocal_penalty))?,
+            revoke_lock: Duration::seconds(value.revoke_lock_seconds),
+            announce_stats: value.announce_stats > 0,
+            created_by: value.created_by.as_ref().map(UserId::from_db).transpose()?,
+            created_at: value
+                .created_at
+                .as_ref()
+                .map(UtcDateTime::from_db)
+                .transpose()?,
+            assignments_sent_at: value
+                .assignments_sent_at
+                .as_ref()
+                .map(UtcDateTime::from_db)
+                .transpose()?,
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use serenity::all::{ChannelId, GuildId, UserId};
+    use sqlx::{query, query_scalar, sqlite::SqlitePoolOptions, SqlitePool};
+    use time::macros::datetime;
+    use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+    use crate::{
+        jam_types::{JamType, StoredJamType},
+        models::{
+            test_new_exchange, types::UtcDateTime, ExchangeId, ExchangeJam, ExchangeState,
+            JamSummary, NewExchange,
+        },
+        repository::{ExchangeRepository, ExchangeStorageEvent},
+    };
+
+    async fn setup_database() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    async fn insert_exchange_in_state(pool: &SqlitePool, state: &str) {
+        let mut transaction = pool.begin().await.unwrap();
+
+        query!(
+            r#"
+                INSERT INTO exchanges (id, guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member) 
+                VALUES (1, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test', 'Test', $1, '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5);
+            "#,
+            state,
+        ).execute(&mut *transaction).await.unwrap();
+
+        transaction.commit().await.unwrap();
+    }
+
+    fn new_exchange(display_name: &str) -> NewExchange {
+        NewExchange {
+            display_name: display_name.to_string(),
+            submissions_start: UtcDateTime::assume_utc(datetime!(2024-01-01 00:00:00)),
+            submissions_end: UtcDateTime::assume_utc(datetime!(2024-01-02 00:00:00)),
+            ..test_new_exchange()
+        }
+    }
+
+    #[tokio::test]
+    async fn lagging_subscriber_is_notified() {
+        let pool = setup_database().await;
+        let repository = ExchangeRepository::with_event_capacity(pool.clone(), 1);
+        let mut events = repository.subscribe();
+
+        for slug in ["First", "Second", "Third"] {
+            repository
+                .create_exchange(NewExchange {
+                    slug: slug.to_string(),
+                    ..new_exchange(slug)
+                })
+                .await
+                .unwrap();
+        }
+
+        assert!(matches!(events.recv().await, Err(RecvError::Lagged(2))));
+        assert!(matches!(
+            events.recv().await,
+            Ok(ExchangeStorageEvent::ExchangesUpdated)
+        ));
+        assert!(matches!(events.try_recv(), Err(TryRecvError::Empty)));
+    }
+
+    #[tokio::test]
+    async fn slug_surrounded_by_whitespace_overlaps() {
+        let pool = setup_database().await;
+        let repository = ExchangeRepository::new(pool.clone());
+
+        repository
+            .create_exchange(NewExchange {
+                slug: " foo ".to_string(),
+                ..new_exchange("Foo")
+            })
+            .await
+            .unwrap();
+
+        let stored = repository
+            .get_exchange_by_slug(GuildId::new(2), "foo")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.slug, "foo");
+
+        // Another channel and period, so only the slug can collide
+        let overlapping = repository
+            .get_overlapping_exchanges(
+                GuildId::new(2),
+                ChannelId::new(4),
+                "foo",
+                UtcDateTime::assume_utc(datetime!(2024-02-01 00:00:00)),
+                UtcDateTime::assume_utc(datetime!(2024-02-02 00:00:00)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(overlapping.len(), 1);
+
+        let overlapping = repository
+            .get_overlapping_exchanges(
+                GuildId::new(2),
+                ChannelId::new(4),
+                " foo ",
+                UtcDateTime::assume_utc(datetime!(2024-02-01 00:00:00)),
+                UtcDateTime::assume_utc(datetime!(2024-02-02 00:00:00)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(overlapping.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn unknown_jam_type_is_readable() {
+        let pool = setup_database().await;
+        query!(
+            r#"
+                INSERT INTO exchanges (id, guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member)
+                VALUES (1, 2, 3, 'GameJolt', 'https://gamejolt.com/jams/example', 'Test', 'Test', 'NotStartedYet', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5);
+            "#
+        ).execute(&pool).await.unwrap();
+        query!(
+            r#"
+                INSERT INTO exchange_jams (exchange_id, jam_type, jam_link)
+                VALUES (1, 'Removed', 'https://example.com/jam');
+            "#
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let repository = ExchangeRepository::new(pool.clone());
+
+        let exchange = repository
+            .get_exchange_by_slug(GuildId::new(2), "Test")
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            exchange.jam_type,
+            StoredJamType::Legacy("GameJolt".to_string())
+        );
+        assert_eq!(
+            exchange.extra_jams,
+            vec![ExchangeJam {
+                jam_type: StoredJamType::Legacy("Removed".to_string()),
+                jam_link: "https://example.com/jam".to_string(),
+            }]
+        );
+
+        // Can still be cleaned up
+        assert!(repository
+            .delete_exchange(GuildId::new(2), "Test")
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn accepting_exchanges() {
+        let pool = setup_database().await;
+        let repository = ExchangeRepository::new(pool.clone());
+
+        let accepting = repository
+            .create_exchange(NewExchange {
+                slug: "Accepting".to_string(),
+                ..new_exchange("Accepting")
+            })
+            .await
+            .unwrap();
+        repository
+            .update_exchange_state(accepting.id, ExchangeState::AcceptingSubmissions)
+            .await
+            .unwrap();
+        repository
+            .create_exchange(NewExchange {
+                slug: "Upcoming".to_string(),
+                ..new_exchange("Upcoming")
+            })
+            .await
+            .unwrap();
+
+        let exchanges = repository.get_accepting_exchanges().await.unwrap();
+
+        assert_eq!(
+            exchanges.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![accepting.id]
+        );
+    }
+
+    #[tokio::test]
+    async fn assignments_sent_at() {
+        let pool = setup_database().await;
+        let repository = ExchangeRepository::new(pool.clone());
+
+        let created = repository
+            .create_exchange(new_exchange("Test"))
+            .await
+            .unwrap();
+        assert_eq!(created.assignments_sent_at, None);
+
+        let sent_at = UtcDateTime::assume_utc(datetime!(2024-01-02 00:00:30));
+        repository
+            .mark_assignments_sent(created.id, sent_at)
+            .await
+            .unwrap();
+
+        let exchange = repository
+            .get_exchange_by_slug(GuildId::new(2), "Test")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(exchange.state, ExchangeState::AssignmentsSent);
+        assert_eq!(exchange.assignments_sent_at, Some(sent_at));
+    }
+
+    #[tokio::test]
+    async fn extra_jams_roundtrip() {
+        let pool = setup_database().await;
+        let repository = ExchangeRepository::new(pool.clone());
+
+        let extra_jams = vec![ExchangeJam {
+            jam_type: StoredJamType::Known(JamType::LudumDare),
+            jam_link: "https://ldjam.com/events/ludum-dare/56".to_string(),
+        }];
+
+        repository
+            .create_exchange(NewExchange {
+                extra_jams: extra_jams.clone(),
+                ..new_exchange("Test")
+            })
+            .await
+            .unwrap();
+
+        let exchange = repository
+            .get_exchange_by_slug(GuildId::new(2), "Test")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(exchange.extra_jams, extra_jams);
+    }
+
+    #[tokio::test]
+    async fn creation_audit_roundtrip() {
+        let pool = setup_database().await;
+        let repository = ExchangeRepository::new(pool.clone());
+
+        let created = repository
+            .create_exchange(NewExchange {
+                created_by: Some(UserId::new(42)),
+                created_at: UtcDateTime::assume_utc(datetime!(2023-12-31 18:30:00)),
+                ..new_exchange("Test")
+            })
+            .await
+            .unwrap();
+
+        let exchange = repository
+            .get_exchange_by_slug(GuildId::new(2), "Test")
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(exchange.created_by, Some(UserId::new(42)));
+        assert_eq!(exchange.created_at, created.created_at);
+        assert_eq!(
+            exchange.created_at,
+            Some(UtcDateTime::assume_utc(datetime!(2023-12-31 18:30:00)))
+        );
+    }
+
+    #[tokio::test]
+    async fn exchange_created_before_auditing() {
+        let pool = setup_database().await;
+        insert_exchange_in_state(&pool, "NotStartedYet").await;
+        let repository = ExchangeRepository::new(pool.clone());
+
+        let exchange = repository
+            .get_exchange_by_slug(GuildId::new(2), "Test")
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(exchange.created_by, None);
+        assert_eq!(exchange.created_at, None);
+    }
+
+    #[tokio::test]
+    async fn created_exchange_is_readable() {
+        let pool = setup_database().await;
+        let repository = ExchangeRepository::new(pool.clone());
+
+        let created = repository
+            .create_exchange(new_exchange("Test"))
+            .await
+            .unwrap();
+
+        let by_slug = repository
+            .get_exchange_by_slug(GuildId::new(2), "Test")
+            .await
+            .unwrap();
+        assert_eq!(by_slug.as_ref().map(|e| e.id), Some(created.id));
+
+        let upcoming = repository
+            .get_upcoming_exchanges_in_guild(
+                GuildId::new(2),
+                UtcDateTime::assume_utc(datetime!(2023-12-31 00:00:00)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(upcoming.len(), 1);
+
+        let starting = repository
+            .get_starting_exchanges(UtcDateTime::assume_utc(datetime!(2024-01-01 00:00:00)))
+            .await
+            .unwrap();
+        assert_eq!(starting.len(), 1);
+
+        let closest = repository
+            .get_closest_exchange_end_or_start_date()
+            .await
+            .unwrap();
+        assert_eq!(closest, Some(created.submissions_start));
+    }
+
+    #[tokio::test]
+    async fn failed_write_is_not_committed() {
+        let pool = setup_database().await;
+        let repository = ExchangeRepository::new(pool.clone());
+
+        let extra_jam = ExchangeJam {
+            jam_type: StoredJamType::Known(JamType::LudumDare),
+            jam_link: "https://ldjam.com/events/ludum-dare/56".to_string(),
+        };
+
+        // The exchange row is inserted first, then the second extra jam violates the
+        // uniqueness constraint, which must roll the exchange back too
+        assert!(repository
+            .create_exchange(NewExchange {
+                extra_jams: vec![extra_jam.clone(), extra_jam],
+                ..new_exchange("Test")
+            })
+            .await
+            .is_err());
+
+        let exchanges = query_scalar!("SELECT COUNT(*) FROM exchanges")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(exchanges, 0);
+        let jams = query_scalar!("SELECT COUNT(*) FROM exchange_jams")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(jams, 0);
+    }
+
+    #[tokio::test]
+    async fn running_exchange() {
+        let pool = setup_database().await;
+        let repository = ExchangeRepository::new(pool.clone());
+        insert_exchange_in_state(&pool, "AcceptingSubmissions").await;
+
+        let exchange = repository
+            .get_running_exchange(
+                GuildId::new(2),
+                ChannelId::new(3),
+                UtcDateTime::assume_utc(datetime!(2024-01-01 12:00:00)),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(exchange.map(|e| e.id), Some(ExchangeId(1)));
+    }
+
+    #[tokio::test]
+    async fn running_exchange_excludes_assignments_sent() {
+        let pool = setup_database().await;
+        let repository = ExchangeRepository::new(pool.clone());
+        insert_exchange_in_state(&pool, "AssignmentsSent").await;
+
+        let date = UtcDateTime::assume_utc(datetime!(2024-01-01 12:00:00));
+
+        let running = repository
+            .get_running_exchange(GuildId::new(2), ChannelId::new(3), date)
+            .await
+            .unwrap();
+        assert!(running.is_none());
+
+        let in_window = repository
+            .get_exchange_in_window(GuildId::new(2), ChannelId::new(3), date)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(in_window.state, ExchangeState::AssignmentsSent);
+    }
+
+    #[tokio::test]
+    async fn running_exchange_excludes_other_terminal_states() {
+        for state in ["MissedByBot", "AssignmentError", "NotStartedYet"] {
+            let pool = setup_database().await;
+            let repository = ExchangeRepository::new(pool.clone());
+            insert_exchange_in_state(&pool, state).await;
+
+            let running = repository
+                .get_running_exchange(
+                    GuildId::new(2),
+                    ChannelId::new(3),
+                    UtcDateTime::assume_utc(datetime!(2024-01-01 12:00:00)),
+                )
+                .await
+                .unwrap();
+
+            assert!(running.is_none(), "{state} exchange should not be running");
+        }
+    }
+
+    #[tokio::test]
+    async fn no_active_jams() {
+        let pool = setup_database().await;
+        let repository = ExchangeRepository::new(pool.clone());
+
+        let jams = repository
+            .get_active_jams_in_guild(GuildId::new(2))
+            .await
+            .unwrap();
+
+        assert!(jams.is_empty());
+    }
+
+    #[tokio::test]
+    async fn active_jams_grouped_by_link() {
+        let pool = setup_database().await;
+        let repository = ExchangeRepository::new(pool.clone());
+
+        {
+            let mut transaction = pool.begin().await.unwrap();
+
+            query!(
+                r#"
+                    INSERT INTO exchanges (id, guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member) 
+                    VALUES (1, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test1', 'Test 1', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5),
+                           (2, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test2', 'Test 2', 'NotStartedYet', '2024-01-03T00:00:00.000000000Z', '2024-01-04T00:00:00.000000000Z', 5),
+                           (3, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test3', 'Test 3', 'AssignmentsSent', '2023-01-01T00:00:00.000000000Z', '2023-01-02T00:00:00.000000000Z', 5),
+                           (4, 2, 3, 'Itch', 'https://itch.io/jam/another-jam', 'Test4', 'Test 4', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5),
+                           (5, 2, 3, 'Itch', 'https://itch.io/jam/finished-jam', 'Test5', 'Test 5', 'MissedByBot', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5),
+                           (6, 7, 8, 'Itch', 'https://itch.io/jam/example-jam', 'Test6', 'Test 6', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5);
+                "#
+            ).execute(&mut *transaction).await.unwrap();
+
+            // Only extra jams of active exchanges count
+            query!(
+                r#"
+                    INSERT INTO exchange_jams (exchange_id, jam_type, jam_link)
+                    VALUES (1, 'LudumDare', 'https://ldjam.com/events/ludum-dare/56'),
+                           (3, 'LudumDare', 'https://ldjam.com/events/ludum-dare/55');
+                "#
+            )
+            .execute(&mut *transaction)
+            .await
+            .unwrap();
+
+            transaction.commit().await.unwrap();
+        };
+
+        let jams = repository
+            .get_active_jams_in_guild(GuildId::new(2))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            jams,
+            vec![
+                JamSummary {
+                    jam_link: "https://itch.io/jam/another-jam".to_string(),
+                    active_exchanges: 1,
+                },
+                JamSummary {
+                    jam_link: "https://itch.io/jam/example-jam".to_string(),
+                    active_exchanges: 2,
+                },
+                JamSummary {
+                    jam_link: "https://ldjam.com/events/ludum-dare/56".to_string(),
+                    active_exchanges: 1,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn exchanges_by_jam_link() {
+        let pool = setup_database().await;
+        let repository = ExchangeRepository::new(pool.clone());
+
+        query!(
+            r#"
+                INSERT INTO exchanges (id, guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member) 
+                VALUES (1, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test1', 'Test 1', 'AcceptingSubmissions', '2024-01-03T00:00:00.000000000Z', '2024-01-04T00:00:00.000000000Z', 5),
+                       (2, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test2', 'Test 2', 'AssignmentsSent', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5),
+                       (3, 2, 3, 'Itch', 'https://itch.io/jam/another-jam', 'Test3', 'Test 3', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5),
+                       (4, 7, 8, 'Itch', 'https://itch.io/jam/example-jam', 'Test4', 'Test 4', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5);
+            "#
+        ).execute(&pool).await.unwrap();
+
+        let exchanges = repository
+            .get_exchanges_by_jam_link(GuildId::new(2), "https://itch.io/jam/example-jam")
+            .await
+            .unwrap();
+        assert_eq!(
+            exchanges.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![ExchangeId(2), ExchangeId(1)]
+        );
+
+        let exchanges = repository
+            .get_exchanges_by_jam_link(GuildId::new(2), "https://itch.io/jam/unrelated-jam")
+            .await
+            .unwrap();
+        assert!(exchanges.is_empty());
+    }
+
+    #[tokio::test]
+    async fn exchanges_by_unnormalized_jam_link() {
+        let pool = setup_database().await;
+        let repository = ExchangeRepository::new(pool.clone());
+
+        query!(
+            r#"
+                INSERT INTO exchanges (id, guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member) 
+                VALUES (1, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test', 'Test', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5);
+            "#
+        ).execute(&pool).await.unwrap();
+
+        let exchanges = repository
+            .get_exchanges_by_jam_link(GuildId::new(2), " https://Itch.io/jam/Example-Jam/ ")
+            .await
+            .unwrap();
+        assert_eq!(
+            exchanges.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![ExchangeId(1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn exchanges_by_extra_jam_link() {
+        let pool = setup_database().await;
+        let repository = ExchangeRepository::new(pool.clone());
+
+        query!(
+            r#"
+                INSERT INTO exchanges (id, guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member) 
+                VALUES (1, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test1', 'Test 1', 'AcceptingSubmissions', '2024-01-03T00:00:00.000000000Z', '2024-01-04T00:00:00.000000000Z', 5),
+                       (2, 2, 3, 'Itch', 'https://itch.io/jam/another-jam', 'Test2', 'Test 2', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5);
+            "#
+        ).execute(&pool).await.unwrap();
+        query!(
+            r#"
+                INSERT INTO exchange_jams (exchange_id, jam_type, jam_link)
+                VALUES (2, 'Itch', 'https://itch.io/jam/example-jam');
+            "#
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let exchanges = repository
+            .get_exchanges_by_jam_link(GuildId::new(2), "https://itch.io/jam/example-jam")
+            .await
+            .unwrap();
+        assert_eq!(
+            exchanges.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![ExchangeId(2), ExchangeId(1)]
+        );
+    }
+}
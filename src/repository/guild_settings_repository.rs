@@ -0,0 +1,293 @@
+use poise::serenity_prelude::{ChannelId, GuildId};
+use sqlx::{query, query_as, Pool, Sqlite};
+
+use crate::models::GuildSettings;
+
+use super::conversion::{DBConvertible, DBFromConversionError, DBToConversionError};
+use super::slow_query::{TimedQuery, DEFAULT_SLOW_QUERY_THRESHOLD};
+
+pub struct GuildSettingsRepository {
+    pool: Pool<Sqlite>,
+    slow_query_threshold: std::time::Duration,
+}
+
+impl GuildSettingsRepository {
+    pub fn new(pool: Pool<Sqlite>) -> GuildSettingsRepository {
+        GuildSettingsRepository {
+            pool,
+            slow_query_threshold: DEFAULT_SLOW_QUERY_THRESHOLD,
+        }
+    }
+
+    /// Sets how long a query may take before it's logged as slow.
+    pub fn with_slow_query_threshold(self, slow_query_threshold: std::time::Duration) -> Self {
+        Self {
+            slow_query_threshold,
+            ..self
+        }
+    }
+
+    /// Returns the guild settings, falling back to the defaults if the guild has none stored.
+    pub async fn get_settings(&self, guild: GuildId) -> Result<GuildSettings, anyhow::Error> {
+        let settings = {
+            let guild = guild.to_db()?;
+
+            query_as!(
+                SqlGuildSettings,
+                r#"SELECT * FROM guild_settings WHERE guild = $1"#,
+                guild,
+            )
+            .fetch_optional(&self.pool)
+            .timed("get_settings", self.slow_query_threshold)
+            .await?
+        };
+
+        match settings {
+            Some(settings) => Ok(GuildSettings::from_db(&settings)?),
+            None => Ok(GuildSettings::default_for(guild)),
+        }
+    }
+
+    pub async fn set_allow_bot_submissions(
+        &self,
+        guild: GuildId,
+        allow: bool,
+    ) -> Result<(), anyhow::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let guild = guild.to_db()?;
+        query!(
+            r#"
+                INSERT INTO guild_settings (guild, allow_bot_submissions)
+                VALUES ($1, $2)
+                ON CONFLICT (guild) DO UPDATE SET allow_bot_submissions = $2
+            "#,
+            guild,
+            allow,
+        )
+        .execute(&mut *transaction)
+        .timed("set_allow_bot_submissions", self.slow_query_threshold)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn set_slug_pattern(
+        &self,
+        guild: GuildId,
+        pattern: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let guild = guild.to_db()?;
+        query!(
+            r#"
+                INSERT INTO guild_settings (guild, slug_pattern)
+                VALUES ($1, $2)
+                ON CONFLICT (guild) DO UPDATE SET slug_pattern = $2
+            "#,
+            guild,
+            pattern,
+        )
+        .execute(&mut *transaction)
+        .timed("set_slug_pattern", self.slow_query_threshold)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn set_assignment_log_channel(
+        &self,
+        guild: GuildId,
+        channel: Option<ChannelId>,
+    ) -> Result<(), anyhow::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let guild = guild.to_db()?;
+        let channel = channel.map(|channel| channel.to_db()).transpose()?;
+        query!(
+            r#"
+                INSERT INTO guild_settings (guild, assignment_log_channel)
+                VALUES ($1, $2)
+                ON CONFLICT (guild) DO UPDATE SET assignment_log_channel = $2
+            "#,
+            guild,
+            channel,
+        )
+        .execute(&mut *transaction)
+        .timed("set_assignment_log_channel", self.slow_query_threshold)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+}
+
+pub struct SqlGuildSettings {
+    guild: i64,
+    allow_bot_submissions: i64,
+    slug_pattern: Option<String>,
+    assignment_log_channel: Option<i64>,
+}
+
+impl DBConvertible for GuildSettings {
+    type DBType = SqlGuildSettings;
+
+    fn to_db(&self) -> Result<Self::DBType, DBToConversionError> {
+        Ok(SqlGuildSettings {
+            guild: self.guild.to_db()?,
+            allow_bot_submissions: if self.allow_bot_submissions { 1 } else { 0 },
+            slug_pattern: self.slug_pattern.clone(),
+            assignment_log_channel: self
+                .assignment_log_channel
+                .map(|channel| channel.to_db())
+                .transpose()?,
+        })
+    }
+
+    fn from_db(value: &Self::DBType) -> Result<Self, DBFromConversionError> {
+        Ok(GuildSettings {
+            guild: GuildId::from_db(&value.guild)?,
+            allow_bot_submissions: value.allow_bot_submissions > 0,
+            slug_pattern: value.slug_pattern.clone(),
+            assignment_log_channel: value
+                .assignment_log_channel
+                .as_ref()
+                .map(ChannelId::from_db)
+                .transpose()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serenity::all::{ChannelId, GuildId};
+    use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+
+    use crate::{models::GuildSettings, repository::GuildSettingsRepository};
+
+    async fn setup_database() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn default_settings() {
+        let pool = setup_database().await;
+        let repository = GuildSettingsRepository::new(pool);
+
+        let settings = repository.get_settings(GuildId::new(1)).await.unwrap();
+
+        assert_eq!(settings, GuildSettings::default_for(GuildId::new(1)));
+    }
+
+    #[tokio::test]
+    async fn updating_settings() {
+        let pool = setup_database().await;
+        let repository = GuildSettingsRepository::new(pool);
+
+        repository
+            .set_allow_bot_submissions(GuildId::new(1), true)
+            .await
+            .unwrap();
+
+        assert!(
+            repository
+                .get_settings(GuildId::new(1))
+                .await
+                .unwrap()
+                .allow_bot_submissions
+        );
+        assert!(
+            !repository
+                .get_settings(GuildId::new(2))
+                .await
+                .unwrap()
+                .allow_bot_submissions
+        );
+
+        repository
+            .set_allow_bot_submissions(GuildId::new(1), false)
+            .await
+            .unwrap();
+
+        assert!(
+            !repository
+                .get_settings(GuildId::new(1))
+                .await
+                .unwrap()
+                .allow_bot_submissions
+        );
+    }
+
+    #[tokio::test]
+    async fn updating_slug_pattern() {
+        let pool = setup_database().await;
+        let repository = GuildSettingsRepository::new(pool);
+
+        repository
+            .set_allow_bot_submissions(GuildId::new(1), true)
+            .await
+            .unwrap();
+        repository
+            .set_slug_pattern(GuildId::new(1), Some("2024-.*"))
+            .await
+            .unwrap();
+
+        let settings = repository.get_settings(GuildId::new(1)).await.unwrap();
+        assert_eq!(settings.slug_pattern.as_deref(), Some("2024-.*"));
+        // Other settings are kept
+        assert!(settings.allow_bot_submissions);
+
+        repository
+            .set_slug_pattern(GuildId::new(1), None)
+            .await
+            .unwrap();
+
+        let settings = repository.get_settings(GuildId::new(1)).await.unwrap();
+        assert_eq!(settings.slug_pattern, None);
+    }
+
+    #[tokio::test]
+    async fn updating_assignment_log_channel() {
+        let pool = setup_database().await;
+        let repository = GuildSettingsRepository::new(pool);
+
+        repository
+            .set_slug_pattern(GuildId::new(1), Some("2024-.*"))
+            .await
+            .unwrap();
+        repository
+            .set_assignment_log_channel(GuildId::new(1), Some(ChannelId::new(5)))
+            .await
+            .unwrap();
+
+        let settings = repository.get_settings(GuildId::new(1)).await.unwrap();
+        assert_eq!(settings.assignment_log_channel, Some(ChannelId::new(5)));
+        // Other settings are kept
+        assert_eq!(settings.slug_pattern.as_deref(), Some("2024-.*"));
+
+        repository
+            .set_assignment_log_channel(GuildId::new(1), None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            repository
+                .get_settings(GuildId::new(1))
+                .await
+                .unwrap()
+                .assignment_log_channel,
+            None
+        );
+    }
+}
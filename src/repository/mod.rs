@@ -1,8 +1,18 @@
+mod ack_repository;
+mod assignment_repository;
+mod backfill_repository;
 mod conversion;
 mod exchange_repository;
+mod guild_settings_repository;
 mod played_game_repository;
+mod slow_query;
 mod submission_repository;
 
-pub use exchange_repository::{ExchangeRepository, ExchangeStorageEvent};
+pub use ack_repository::AckRepository;
+pub use assignment_repository::AssignmentRepository;
+pub use backfill_repository::BackfillRepository;
+pub use exchange_repository::{ExchangeRepository, ExchangeStorageEvent, DEFAULT_EVENT_CAPACITY};
+pub use guild_settings_repository::GuildSettingsRepository;
 pub use played_game_repository::PlayedGameRepository;
-pub use submission_repository::SubmissionRepository;
+pub use slow_query::DEFAULT_SLOW_QUERY_THRESHOLD;
+pub use submission_repository::{AddSubmissionError, SubmissionRepository};
@@ -1,17 +1,35 @@
-use serenity::all::UserId;
-use sqlx::{query, query_as, Pool, Sqlite};
+use std::collections::BTreeSet;
 
-use crate::models::{ExchangeId, PlayedGame, PlayedGameId};
+use serenity::all::{GuildId, UserId};
+use sqlx::{query, query_as, query_scalar, Pool, Sqlite};
+
+use crate::{
+    jam_types::normalize_entry_link,
+    models::{ExchangeId, ExchangeState, PlayedGame, PlayedGameId},
+};
 
 use super::conversion::DBConvertible;
+use super::slow_query::{TimedQuery, DEFAULT_SLOW_QUERY_THRESHOLD};
 
 pub struct PlayedGameRepository {
     pool: Pool<Sqlite>,
+    slow_query_threshold: std::time::Duration,
 }
 
 impl PlayedGameRepository {
     pub fn new(pool: Pool<Sqlite>) -> PlayedGameRepository {
-        PlayedGameRepository { pool }
+        PlayedGameRepository {
+            pool,
+            slow_query_threshold: DEFAULT_SLOW_QUERY_THRESHOLD,
+        }
+    }
+
+    /// Sets how long a query may take before it's logged as slow.
+    pub fn with_slow_query_threshold(self, slow_query_threshold: std::time::Duration) -> Self {
+        Self {
+            slow_query_threshold,
+            ..self
+        }
     }
 
     pub async fn submit(
@@ -34,6 +52,7 @@ impl PlayedGameRepository {
             is_manual,
         )
         .execute(&mut *transaction)
+        .timed("submit", self.slow_query_threshold)
         .await?;
 
         transaction.commit().await?;
@@ -41,12 +60,15 @@ impl PlayedGameRepository {
         Ok(())
     }
 
+    /// Returns the games played by the members who submitted to the exchange.
+    ///
+    /// Only submitters become reviewers when the assignments are made, so the games played by
+    /// other members are left out. Should reviewers ever come from elsewhere, their played games
+    /// have to be included here as well, or they may be assigned games they have already played.
     pub async fn get_played_games_for_exchange(
         &self,
         exchange_id: ExchangeId,
     ) -> Result<Vec<PlayedGame>, anyhow::Error> {
-        let mut transaction = self.pool.begin().await?;
-
         let played_games = {
             let exchange_id = exchange_id.to_db()?;
 
@@ -59,16 +81,102 @@ impl PlayedGameRepository {
                 "#,
                 exchange_id,
             )
-            .fetch_all(&mut *transaction)
+            .fetch_all(&self.pool)
+            .timed("get_played_games_for_exchange", self.slow_query_threshold)
             .await?
             .iter()
             .map(PlayedGame::from_db)
             .collect::<Result<Vec<PlayedGame>, _>>()?
         };
 
+        Ok(played_games)
+    }
+
+    /// Records every entry assigned in the guild's finished exchanges as played by its reviewer.
+    /// Returns the number of newly recorded games; already recorded ones are left untouched.
+    pub async fn backfill_from_assignments(&self, guild: GuildId) -> Result<u64, anyhow::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let guild = guild.to_db()?;
+        let assignments_sent = ExchangeState::AssignmentsSent.to_db()?;
+        let result = query!(
+            r#"
+                INSERT INTO played_games (member, link, is_manual)
+                SELECT assignments.reviewer, COALESCE(submissions.link, backfill_entries.link), FALSE
+                FROM assignments
+                LEFT JOIN submissions ON submissions.id = assignments.submission_id
+                LEFT JOIN backfill_entries ON backfill_entries.id = assignments.backfill_entry_id
+                INNER JOIN exchanges ON exchanges.id = assignments.exchange_id
+                WHERE exchanges.guild = $1 AND exchanges.state = $2
+                ON CONFLICT (member, link) DO NOTHING
+            "#,
+            guild,
+            assignments_sent,
+        )
+        .execute(&mut *transaction)
+        .timed("backfill_from_assignments", self.slow_query_threshold)
+        .await?;
+
         transaction.commit().await?;
 
-        Ok(played_games)
+        Ok(result.rows_affected())
+    }
+
+    /// Counts entries of the exchange, other than the member's own, that the member has played.
+    /// Links are compared the same way the assignment solver compares them.
+    pub async fn count_played_entries(
+        &self,
+        exchange_id: ExchangeId,
+        member: UserId,
+    ) -> Result<u32, anyhow::Error> {
+        // Both reads have to see the same state, so they share a transaction
+        let mut transaction = self.pool.begin().await?;
+
+        let (submission_links, played_links) = {
+            let exchange_id = exchange_id.to_db()?;
+            let member = member.to_db()?;
+
+            let submission_links = query_scalar!(
+                r#"
+                    SELECT link FROM submissions
+                    WHERE exchange_id = $1 AND submitter != $2
+                "#,
+                exchange_id,
+                member,
+            )
+            .fetch_all(&mut *transaction)
+            .timed(
+                "count_played_entries_submissions",
+                self.slow_query_threshold,
+            )
+            .await?;
+
+            let played_links = query_scalar!(
+                r#"
+                    SELECT link FROM played_games
+                    WHERE member = $1
+                "#,
+                member,
+            )
+            .fetch_all(&mut *transaction)
+            .timed("count_played_entries_played", self.slow_query_threshold)
+            .await?;
+
+            (submission_links, played_links)
+        };
+
+        transaction.commit().await?;
+
+        let played_links = played_links
+            .iter()
+            .map(|link| normalize_entry_link(link))
+            .collect::<BTreeSet<_>>();
+        let count = submission_links
+            .iter()
+            .filter(|link| played_links.contains(&normalize_entry_link(link)))
+            .count();
+
+        Ok(count.try_into()?)
     }
 }
 
@@ -104,7 +212,7 @@ impl DBConvertible for PlayedGame {
 
 #[cfg(test)]
 mod test {
-    use serenity::all::UserId;
+    use serenity::all::{GuildId, UserId};
     use sqlx::{query, sqlite::SqlitePoolOptions, SqlitePool};
 
     use crate::{
@@ -254,4 +362,232 @@ mod test {
             ]
         );
     }
+
+    #[tokio::test]
+    async fn counting_played_entries() {
+        let pool = setup_database().await;
+        let repository = PlayedGameRepository::new(pool.clone());
+
+        {
+            let mut transaction = pool.begin().await.unwrap();
+
+            query!(
+                r#"
+                    INSERT INTO exchanges (id, guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member) 
+                    VALUES (1, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test', 'Test', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5);
+
+                    INSERT INTO submissions (id, exchange_id, link, submitter, submitted_at)
+                    VALUES (1, 1, 'https://itch.io/jam/example-jam/rate/000001', 7, '2024-01-01T00:01:00.000000000Z'),
+                           (2, 1, 'https://itch.io/jam/example-jam/rate/000002', 8, '2024-01-01T00:01:00.000000000Z'),
+                           (3, 1, 'https://itch.io/jam/example-jam/rate/000003', 9, '2024-01-01T00:01:00.000000000Z');
+
+                    INSERT INTO played_games (id, member, link, is_manual)
+                    VALUES (1, 7, 'https://itch.io/jam/example-jam/rate/000001', FALSE),
+                           (2, 7, 'https://itch.io/jam/example-jam/rate/000002', TRUE),
+                           (3, 7, 'https://itch.io/jam/other-jam/rate/000003', TRUE),
+                           (4, 8, 'https://itch.io/jam/example-jam/rate/000003', FALSE),
+                           (5, 9, 'https://Itch.io/jam/Example-Jam/rate/000002/', TRUE);
+                "#
+            ).execute(&mut *transaction).await.unwrap();
+
+            transaction.commit().await.unwrap();
+        };
+
+        // Own entry and games outside of the exchange don't count, links differing only in casing
+        // or a trailing slash do.
+        assert_eq!(
+            repository
+                .count_played_entries(ExchangeId(1), UserId::new(7))
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            repository
+                .count_played_entries(ExchangeId(1), UserId::new(8))
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            repository
+                .count_played_entries(ExchangeId(1), UserId::new(9))
+                .await
+                .unwrap(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn backfilling_from_assignments() {
+        let pool = setup_database().await;
+        let repository = PlayedGameRepository::new(pool.clone());
+
+        {
+            let mut transaction = pool.begin().await.unwrap();
+
+            query!(
+                r#"
+                    INSERT INTO exchanges (id, guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member) 
+                    VALUES (1, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test', 'Test', 'AssignmentsSent', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5),
+                           (4, 5, 6, 'Itch', 'https://itch.io/jam/example-jam-2', 'Test2', 'Test 2', 'AssignmentsSent', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5);
+
+                    INSERT INTO submissions (id, exchange_id, link, submitter, submitted_at)
+                    VALUES (1, 1, 'https://itch.io/jam/example-jam/rate/000001', 7, '2024-01-01T00:01:00.000000000Z'),
+                           (2, 1, 'https://itch.io/jam/example-jam/rate/000002', 8, '2024-01-01T00:01:00.000000000Z'),
+                           (3, 4, 'https://itch.io/jam/example-jam-2/rate/000003', 7, '2024-01-01T00:01:00.000000000Z'),
+                           (4, 4, 'https://itch.io/jam/example-jam-2/rate/000004', 8, '2024-01-01T00:01:00.000000000Z');
+
+                    INSERT INTO assignments (exchange_id, reviewer, submission_id)
+                    VALUES (1, 7, 2),
+                           (1, 8, 1),
+                           (4, 7, 4);
+
+                    INSERT INTO played_games (id, member, link, is_manual)
+                    VALUES (1, 8, 'https://itch.io/jam/example-jam/rate/000001', TRUE);
+                "#
+            ).execute(&mut *transaction).await.unwrap();
+
+            transaction.commit().await.unwrap();
+        };
+
+        let inserted = repository
+            .backfill_from_assignments(GuildId::new(2))
+            .await
+            .unwrap();
+        assert_eq!(inserted, 1);
+
+        let played_games = repository
+            .get_played_games_for_exchange(ExchangeId(1))
+            .await
+            .unwrap();
+
+        assert_eq!(played_games.len(), 2);
+        assert!(played_games.contains(&PlayedGame {
+            id: PlayedGameId(1),
+            link: "https://itch.io/jam/example-jam/rate/000001".to_string(),
+            member: UserId::new(8),
+            is_manual: true,
+        }));
+        assert!(played_games.iter().any(|game| game.member == UserId::new(7)
+            && game.link == "https://itch.io/jam/example-jam/rate/000002"
+            && !game.is_manual));
+
+        // Running it again doesn't duplicate anything.
+        let inserted = repository
+            .backfill_from_assignments(GuildId::new(2))
+            .await
+            .unwrap();
+        assert_eq!(inserted, 0);
+    }
+
+    #[tokio::test]
+    async fn submitting_manual_and_auto_games() {
+        let pool = setup_database().await;
+        let repository = PlayedGameRepository::new(pool.clone());
+
+        {
+            let mut transaction = pool.begin().await.unwrap();
+
+            query!(
+                r#"
+                    INSERT INTO exchanges (id, guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member) 
+                    VALUES (1, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test', 'Test', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5);
+
+                    INSERT INTO submissions (id, exchange_id, link, submitter, submitted_at)
+                    VALUES (1, 1, 'https://itch.io/jam/example-jam/rate/000001', 7, '2024-01-01T00:01:00.000000000Z'),
+                           (2, 1, 'https://itch.io/jam/example-jam/rate/000002', 8, '2024-01-01T00:01:00.000000000Z');
+                "#
+            ).execute(&mut *transaction).await.unwrap();
+
+            transaction.commit().await.unwrap();
+        };
+
+        repository
+            .submit(
+                UserId::new(7),
+                "https://itch.io/jam/example-jam/rate/000002",
+                true,
+            )
+            .await
+            .unwrap();
+        repository
+            .submit(
+                UserId::new(8),
+                "https://itch.io/jam/example-jam/rate/000001",
+                false,
+            )
+            .await
+            .unwrap();
+        // Submitting the same game again keeps the original row
+        repository
+            .submit(
+                UserId::new(8),
+                "https://itch.io/jam/example-jam/rate/000001",
+                true,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            repository
+                .get_played_games_for_exchange(ExchangeId(1))
+                .await
+                .unwrap(),
+            vec![
+                PlayedGame {
+                    id: PlayedGameId(1),
+                    link: "https://itch.io/jam/example-jam/rate/000002".to_string(),
+                    member: UserId::new(7),
+                    is_manual: true,
+                },
+                PlayedGame {
+                    id: PlayedGameId(2),
+                    link: "https://itch.io/jam/example-jam/rate/000001".to_string(),
+                    member: UserId::new(8),
+                    is_manual: false,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn only_submitters_played_games() {
+        let pool = setup_database().await;
+        let repository = PlayedGameRepository::new(pool.clone());
+
+        {
+            let mut transaction = pool.begin().await.unwrap();
+
+            query!(
+                r#"
+                    INSERT INTO exchanges (id, guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member) 
+                    VALUES (1, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test', 'Test', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5);
+
+                    INSERT INTO submissions (id, exchange_id, link, submitter, submitted_at)
+                    VALUES (1, 1, 'https://itch.io/jam/example-jam/rate/000001', 7, '2024-01-01T00:01:00.000000000Z');
+
+                    INSERT INTO played_games (id, member, link, is_manual)
+                    VALUES (1, 7, 'https://itch.io/jam/example-jam/rate/000002', TRUE),
+                           (2, 8, 'https://itch.io/jam/example-jam/rate/000001', TRUE);
+                "#
+            ).execute(&mut *transaction).await.unwrap();
+
+            transaction.commit().await.unwrap();
+        };
+
+        // Member 8 played an entry of the exchange but did not submit, so they won't be a reviewer
+        assert_eq!(
+            repository
+                .get_played_games_for_exchange(ExchangeId(1))
+                .await
+                .unwrap(),
+            vec![PlayedGame {
+                id: PlayedGameId(1),
+                link: "https://itch.io/jam/example-jam/rate/000002".to_string(),
+                member: UserId::new(7),
+                is_manual: true,
+            }]
+        );
+    }
 }
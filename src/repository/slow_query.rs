@@ -0,0 +1,83 @@
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use tracing::warn;
+
+pub const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(500);
+
+/// Lets repositories time a query by calling `.timed("method_name", threshold)` right before
+/// `.await`.
+pub(super) trait TimedQuery: Future + Sized {
+    /// Runs the query and warns if it took longer than `threshold`.
+    async fn timed(self, method: &'static str, threshold: Duration) -> Self::Output {
+        let started = Instant::now();
+        let result = self.await;
+
+        warn_if_slow(method, started.elapsed(), threshold);
+
+        result
+    }
+}
+
+impl<F: Future> TimedQuery for F {}
+
+/// Returns whether the query was logged as slow.
+fn warn_if_slow(method: &'static str, elapsed: Duration, threshold: Duration) -> bool {
+    if elapsed <= threshold {
+        return false;
+    }
+
+    warn!("Slow query in {method}: took {elapsed:?}, threshold is {threshold:?}");
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use sqlx::{query_scalar, sqlite::SqlitePoolOptions};
+
+    use super::{warn_if_slow, TimedQuery};
+
+    #[test]
+    fn fast_query_not_logged() {
+        assert!(!warn_if_slow(
+            "fast",
+            Duration::from_millis(10),
+            Duration::from_millis(500)
+        ));
+        assert!(!warn_if_slow(
+            "borderline",
+            Duration::from_millis(500),
+            Duration::from_millis(500)
+        ));
+    }
+
+    #[test]
+    fn slow_query_logged() {
+        assert!(warn_if_slow(
+            "slow",
+            Duration::from_millis(501),
+            Duration::from_millis(500)
+        ));
+    }
+
+    #[tokio::test]
+    async fn timed_returns_query_result() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        let value = query_scalar!("SELECT 1")
+            .fetch_one(&pool)
+            .timed("select_one", Duration::ZERO)
+            .await
+            .unwrap();
+
+        assert_eq!(value, 1);
+    }
+}
@@ -1,30 +1,78 @@
-use poise::serenity_prelude::UserId;
-use sqlx::{query, query_as, Pool, Sqlite};
+use poise::serenity_prelude::{GuildId, UserId};
+use sqlx::{query, query_as, query_scalar, Pool, Sqlite};
+use thiserror::Error;
 
 use crate::{
+    jam_types::normalize_entry_link,
     models::{
-        types::UtcDateTime, ExchangeId, ExchangeState, NewSubmission, Submission, SubmissionId,
+        types::UtcDateTime, ExchangeId, ExchangeState, NewSubmission, Submission,
+        SubmissionHistoryEntry, SubmissionId,
     },
     repository::conversion::DBConvertible,
 };
 
 use super::conversion::{DBFromConversionError, DBToConversionError};
+use super::slow_query::{TimedQuery, DEFAULT_SLOW_QUERY_THRESHOLD};
 
 pub struct SubmissionRepository {
     pool: Pool<Sqlite>,
+    slow_query_threshold: std::time::Duration,
+}
+
+#[derive(Debug, Error)]
+pub enum AddSubmissionError {
+    #[error("The link has already been submitted by someone else")]
+    LinkAlreadySubmitted,
+    #[error("The submission can't be edited anymore")]
+    EditLimitReached,
+    #[error(transparent)]
+    ToConversion(#[from] DBToConversionError),
+    #[error(transparent)]
+    FromConversion(#[from] DBFromConversionError),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+impl AddSubmissionError {
+    /// Maps an error from inserting a submission. Conflicts on the submitter are resolved by the
+    /// upsert, so only a violation of the link's unique constraint means someone else has
+    /// submitted the link.
+    fn from_insert(err: sqlx::Error) -> AddSubmissionError {
+        match err.as_database_error() {
+            Some(db_err) if is_link_violation(db_err) => AddSubmissionError::LinkAlreadySubmitted,
+            _ => AddSubmissionError::Database(err),
+        }
+    }
+}
+
+/// SQLite doesn't report constraint names, but names the columns of the violated one.
+fn is_link_violation(err: &dyn sqlx::error::DatabaseError) -> bool {
+    err.is_unique_violation()
+        && err
+            .message()
+            .ends_with("submissions.exchange_id, submissions.link")
 }
 
 impl SubmissionRepository {
     pub fn new(pool: Pool<Sqlite>) -> SubmissionRepository {
-        SubmissionRepository { pool }
+        SubmissionRepository {
+            pool,
+            slow_query_threshold: DEFAULT_SLOW_QUERY_THRESHOLD,
+        }
+    }
+
+    /// Sets how long a query may take before it's logged as slow.
+    pub fn with_slow_query_threshold(self, slow_query_threshold: std::time::Duration) -> Self {
+        Self {
+            slow_query_threshold,
+            ..self
+        }
     }
 
     pub async fn get_conflicting_submission(
         &self,
         new_submission: &NewSubmission,
     ) -> Result<Option<Submission>, anyhow::Error> {
-        let mut transaction = self.pool.begin().await?;
-
         let conflict = {
             let exchange_id = new_submission.exchange_id.to_db()?;
             let submitter = new_submission.submitter.to_db()?;
@@ -40,12 +88,11 @@ impl SubmissionRepository {
                 submitter,
                 new_submission.link,
             )
-            .fetch_optional(&mut *transaction)
+            .fetch_optional(&self.pool)
+            .timed("get_conflicting_submission", self.slow_query_threshold)
             .await?
         };
 
-        transaction.commit().await?;
-
         match conflict {
             Some(conflict) => Ok(Some(Submission::from_db(&conflict)?)),
             None => Ok(None),
@@ -55,7 +102,7 @@ impl SubmissionRepository {
     pub async fn add_or_update_submission(
         &self,
         submission: &NewSubmission,
-    ) -> Result<Submission, anyhow::Error> {
+    ) -> Result<Submission, AddSubmissionError> {
         let mut transaction = self.pool.begin().await?;
 
         let added_submission = {
@@ -69,26 +116,36 @@ impl SubmissionRepository {
                 r#"
                     INSERT INTO submissions (exchange_id, link, submitter, submitted_at)
                     VALUES ($1, $2, $3, $4)
-                    ON CONFLICT (exchange_id, submitter) DO UPDATE SET link = $2
+                    ON CONFLICT (exchange_id, submitter) DO UPDATE
+                        SET link = $2, edit_count = edit_count + 1
+                        WHERE (SELECT max_submission_edits FROM exchanges WHERE id = $1) = 0
+                            OR edit_count < (SELECT max_submission_edits FROM exchanges WHERE id = $1)
                     RETURNING 
                         id AS "id!", 
                         exchange_id AS "exchange_id!",
                         link AS "link!",
                         submitter AS "submitter!",
-                        submitted_at AS "submitted_at!"
+                        submitted_at AS "submitted_at!",
+                        edit_count AS "edit_count!"
                 "#,
                 exchange_id,
                 link,
                 submitter,
                 submitted_at,
             )
-            .fetch_one(&mut *transaction)
-            .await?
+            .fetch_optional(&mut *transaction)
+            .timed("add_or_update_submission", self.slow_query_threshold)
+            .await
+            .map_err(AddSubmissionError::from_insert)?
         };
 
         transaction.commit().await?;
 
-        Ok(Submission::from_db(&added_submission)?)
+        // The update is skipped when the exchange's edit limit is reached.
+        match added_submission {
+            Some(added_submission) => Ok(Submission::from_db(&added_submission)?),
+            None => Err(AddSubmissionError::EditLimitReached),
+        }
     }
 
     pub async fn revoke(
@@ -113,6 +170,7 @@ impl SubmissionRepository {
             accepting_submissions,
         )
         .execute(&mut *transaction)
+        .timed("revoke", self.slow_query_threshold)
         .await?;
 
         transaction.commit().await?;
@@ -120,12 +178,119 @@ impl SubmissionRepository {
         Ok(result.rows_affected() > 0)
     }
 
+    pub async fn count_submissions(&self, exchange_id: ExchangeId) -> Result<u32, anyhow::Error> {
+        let count = {
+            let exchange_id = exchange_id.to_db()?;
+
+            query_scalar!(
+                r#"SELECT COUNT(*) AS "count!: i64" FROM submissions WHERE exchange_id = $1"#,
+                exchange_id,
+            )
+            .fetch_one(&self.pool)
+            .timed("count_submissions", self.slow_query_threshold)
+            .await?
+        };
+
+        Ok(count.try_into()?)
+    }
+
+    pub async fn get_submission_by_submitter(
+        &self,
+        exchange_id: ExchangeId,
+        submitter: UserId,
+    ) -> Result<Option<Submission>, anyhow::Error> {
+        let submission = {
+            let exchange_id = exchange_id.to_db()?;
+            let submitter = submitter.to_db()?;
+
+            query_as!(
+                SqlSubmission,
+                r#"
+                    SELECT * FROM submissions
+                    WHERE exchange_id = $1 AND submitter = $2
+                "#,
+                exchange_id,
+                submitter,
+            )
+            .fetch_optional(&self.pool)
+            .timed("get_submission_by_submitter", self.slow_query_threshold)
+            .await?
+        };
+
+        Ok(submission.map(|s| Submission::from_db(&s)).transpose()?)
+    }
+
+    /// Looks up a submission by its link. Submitted links are stored normalized, so the link is
+    /// normalized the same way before comparing.
+    pub async fn get_submission_by_link(
+        &self,
+        exchange_id: ExchangeId,
+        link: &str,
+    ) -> Result<Option<Submission>, anyhow::Error> {
+        let submission = {
+            let exchange_id = exchange_id.to_db()?;
+            let link = normalize_entry_link(link);
+
+            query_as!(
+                SqlSubmission,
+                r#"
+                    SELECT * FROM submissions
+                    WHERE exchange_id = $1 AND link = $2
+                    LIMIT 1
+                "#,
+                exchange_id,
+                link,
+            )
+            .fetch_optional(&self.pool)
+            .timed("get_submission_by_link", self.slow_query_threshold)
+            .await?
+        };
+
+        Ok(submission.map(|s| Submission::from_db(&s)).transpose()?)
+    }
+
+    /// Gets all submissions of a user to the guild's exchanges, oldest first.
+    pub async fn get_submissions_by_user(
+        &self,
+        guild: GuildId,
+        user: UserId,
+    ) -> Result<Vec<SubmissionHistoryEntry>, anyhow::Error> {
+        let entries = {
+            let guild = guild.to_db()?;
+            let user = user.to_db()?;
+
+            query!(
+                r#"
+                    SELECT exchanges.slug, submissions.link, submissions.submitted_at
+                    FROM submissions
+                    INNER JOIN exchanges ON exchanges.id = submissions.exchange_id
+                    WHERE exchanges.guild = $1 AND submissions.submitter = $2
+                    ORDER BY submissions.submitted_at, submissions.id
+                "#,
+                guild,
+                user,
+            )
+            .fetch_all(&self.pool)
+            .timed("get_submissions_by_user", self.slow_query_threshold)
+            .await?
+        };
+
+        entries
+            .into_iter()
+            .map(|entry| {
+                Ok(SubmissionHistoryEntry {
+                    exchange_slug: entry.slug,
+                    link: entry.link,
+                    submitted_at: UtcDateTime::from_db(&entry.submitted_at)?,
+                })
+            })
+            .collect()
+    }
+
     pub async fn get_submissions_for_exchange(
         &self,
         exchange_id: ExchangeId,
     ) -> Result<Vec<Submission>, anyhow::Error> {
-        let mut transaction = self.pool.begin().await?;
-
         let submissions = {
             let exchange_id = exchange_id.to_db()?;
 
@@ -136,15 +301,14 @@ impl SubmissionRepository {
                 "#,
                 exchange_id,
             )
-            .fetch_all(&mut *transaction)
+            .fetch_all(&self.pool)
+            .timed("get_submissions_for_exchange", self.slow_query_threshold)
             .await?
             .iter()
             .map(Submission::from_db)
             .collect::<Result<Vec<Submission>, _>>()?
         };
 
-        transaction.commit().await?;
-
         Ok(submissions)
     }
 }
@@ -156,6 +320,7 @@ pub struct SqlSubmission {
     link: String,
     submitter: i64,
     submitted_at: String,
+    edit_count: i64,
 }
 
 impl DBConvertible for Submission {
@@ -168,6 +333,7 @@ impl DBConvertible for Submission {
             link: self.link.clone(),
             submitter: self.submitter.to_db()?,
             submitted_at: self.submitted_at.to_db()?,
+            edit_count: self.edit_count.into(),
         })
     }
 
@@ -178,19 +344,26 @@ impl DBConvertible for Submission {
             link: value.link.clone(),
             submitter: UserId::from_db(&value.submitter)?,
             submitted_at: UtcDateTime::from_db(&value.submitted_at)?,
+            edit_count: value
+                .edit_count
+                .try_into()
+                .map_err(|_| DBFromConversionError::InvalidNumber(value.edit_count))?,
         })
     }
 }
 
 #[cfg(test)]
 mod test {
-    use serenity::all::UserId;
+    use serenity::all::{GuildId, UserId};
     use sqlx::{query, sqlite::SqlitePoolOptions, SqlitePool};
     use time::macros::datetime;
 
     use crate::{
-        models::{types::UtcDateTime, ExchangeId, Submission, SubmissionId},
-        repository::SubmissionRepository,
+        models::{
+            types::UtcDateTime, ExchangeId, NewSubmission, Submission, SubmissionHistoryEntry,
+            SubmissionId,
+        },
+        repository::{AddSubmissionError, SubmissionRepository},
     };
 
     async fn setup_database() -> SqlitePool {
@@ -270,6 +443,7 @@ mod test {
                 link: "https://itch.io/jam/example-jam/rate/000003".to_string(),
                 submitter: UserId::new(9),
                 submitted_at: UtcDateTime::assume_utc(datetime!(2024-01-01 00:01:00.000000000)),
+                edit_count: 0,
             }]
         );
     }
@@ -312,6 +486,7 @@ mod test {
                     link: "https://itch.io/jam/example-jam-2/rate/000004".to_string(),
                     submitter: UserId::new(7),
                     submitted_at: UtcDateTime::assume_utc(datetime!(2024-01-01 00:01:00.000000000)),
+                    edit_count: 0,
                 },
                 Submission {
                     id: SubmissionId(2),
@@ -319,8 +494,317 @@ mod test {
                     link: "https://itch.io/jam/example-jam-2/rate/000005".to_string(),
                     submitter: UserId::new(8),
                     submitted_at: UtcDateTime::assume_utc(datetime!(2024-01-01 00:01:00.000000000)),
+                    edit_count: 0,
                 }
             ]
         );
     }
+
+    #[tokio::test]
+    async fn link_submitted_by_someone_else() {
+        let pool = setup_database().await;
+        let repository = SubmissionRepository::new(pool.clone());
+
+        {
+            let mut transaction = pool.begin().await.unwrap();
+
+            query!(
+                r#"
+                    INSERT INTO exchanges (id, guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member) 
+                    VALUES (1, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test', 'Test', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5);
+
+                    INSERT INTO submissions (id, exchange_id, link, submitter, submitted_at)
+                    VALUES (1, 1, 'https://itch.io/jam/example-jam/rate/000001', 7, '2024-01-01T00:01:00.000000000Z');
+                "#
+            ).execute(&mut *transaction).await.unwrap();
+
+            transaction.commit().await.unwrap();
+        };
+
+        let result = repository
+            .add_or_update_submission(&NewSubmission {
+                exchange_id: ExchangeId(1),
+                link: "https://itch.io/jam/example-jam/rate/000001".to_string(),
+                submitter: UserId::new(8),
+                submitted_at: UtcDateTime::assume_utc(datetime!(2024-01-01 00:02:00.000000000)),
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(AddSubmissionError::LinkAlreadySubmitted)
+        ));
+
+        let submissions = repository
+            .get_submissions_for_exchange(ExchangeId(1))
+            .await
+            .unwrap();
+
+        assert_eq!(submissions.len(), 1);
+        assert_eq!(submissions[0].submitter, UserId::new(7));
+    }
+
+    #[tokio::test]
+    async fn other_unique_violation_is_not_a_link_conflict() {
+        let pool = setup_database().await;
+
+        let insert = || {
+            query!(
+                r#"
+                    INSERT INTO exchanges (guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member) 
+                    VALUES (2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test', 'Test', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5);
+                "#
+            )
+            .execute(&pool)
+        };
+        insert().await.unwrap();
+        let err = insert().await.unwrap_err();
+
+        assert!(err.as_database_error().unwrap().is_unique_violation());
+        assert!(matches!(
+            AddSubmissionError::from_insert(err),
+            AddSubmissionError::Database(_)
+        ));
+    }
+
+    async fn submit_link(
+        repository: &SubmissionRepository,
+        link_id: u32,
+    ) -> Result<Submission, AddSubmissionError> {
+        repository
+            .add_or_update_submission(&NewSubmission {
+                exchange_id: ExchangeId(1),
+                link: format!("https://itch.io/jam/example-jam/rate/00000{link_id}"),
+                submitter: UserId::new(7),
+                submitted_at: UtcDateTime::assume_utc(datetime!(2024-01-01 00:02:00.000000000)),
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn counting_edits() {
+        let pool = setup_database().await;
+        let repository = SubmissionRepository::new(pool.clone());
+
+        {
+            let mut transaction = pool.begin().await.unwrap();
+
+            query!(
+                r#"
+                    INSERT INTO exchanges (id, guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member) 
+                    VALUES (1, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test', 'Test', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5);
+                "#
+            ).execute(&mut *transaction).await.unwrap();
+
+            transaction.commit().await.unwrap();
+        };
+
+        assert_eq!(submit_link(&repository, 1).await.unwrap().edit_count, 0);
+        assert_eq!(submit_link(&repository, 2).await.unwrap().edit_count, 1);
+        assert_eq!(submit_link(&repository, 3).await.unwrap().edit_count, 2);
+
+        let submissions = repository
+            .get_submissions_for_exchange(ExchangeId(1))
+            .await
+            .unwrap();
+
+        assert_eq!(submissions.len(), 1);
+        assert_eq!(submissions[0].edit_count, 2);
+        assert_eq!(
+            submissions[0].link,
+            "https://itch.io/jam/example-jam/rate/000003"
+        );
+    }
+
+    #[tokio::test]
+    async fn enforcing_edit_limit() {
+        let pool = setup_database().await;
+        let repository = SubmissionRepository::new(pool.clone());
+
+        {
+            let mut transaction = pool.begin().await.unwrap();
+
+            query!(
+                r#"
+                    INSERT INTO exchanges (id, guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member, max_submission_edits) 
+                    VALUES (1, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test', 'Test', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5, 2);
+                "#
+            ).execute(&mut *transaction).await.unwrap();
+
+            transaction.commit().await.unwrap();
+        };
+
+        submit_link(&repository, 1).await.unwrap();
+        submit_link(&repository, 2).await.unwrap();
+        submit_link(&repository, 3).await.unwrap();
+
+        assert!(matches!(
+            submit_link(&repository, 4).await,
+            Err(AddSubmissionError::EditLimitReached)
+        ));
+
+        let submissions = repository
+            .get_submissions_for_exchange(ExchangeId(1))
+            .await
+            .unwrap();
+
+        assert_eq!(submissions.len(), 1);
+        assert_eq!(submissions[0].edit_count, 2);
+        assert_eq!(
+            submissions[0].link,
+            "https://itch.io/jam/example-jam/rate/000003"
+        );
+    }
+
+    #[tokio::test]
+    async fn submissions_by_user() {
+        let pool = setup_database().await;
+        let repository = SubmissionRepository::new(pool.clone());
+
+        {
+            let mut transaction = pool.begin().await.unwrap();
+
+            query!(
+                r#"
+                    INSERT INTO exchanges (id, guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member) 
+                    VALUES (1, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Second', 'Second', 'AcceptingSubmissions', '2024-02-01T00:00:00.000000000Z', '2024-02-02T00:00:00.000000000Z', 5),
+                           (4, 2, 3, 'Itch', 'https://itch.io/jam/example-jam-2', 'First', 'First', 'AssignmentsSent', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5),
+                           (5, 9, 3, 'Itch', 'https://itch.io/jam/example-jam-3', 'Elsewhere', 'Elsewhere', 'AcceptingSubmissions', '2024-02-01T00:00:00.000000000Z', '2024-02-02T00:00:00.000000000Z', 5);
+
+                    INSERT INTO submissions (id, exchange_id, link, submitter, submitted_at)
+                    VALUES (1, 1, 'https://itch.io/jam/example-jam/rate/000001', 7, '2024-02-01T00:01:00.000000000Z'),
+                           (2, 4, 'https://itch.io/jam/example-jam-2/rate/000002', 7, '2024-01-01T00:01:00.000000000Z'),
+                           (3, 4, 'https://itch.io/jam/example-jam-2/rate/000003', 8, '2024-01-01T00:02:00.000000000Z'),
+                           (4, 5, 'https://itch.io/jam/example-jam-3/rate/000004', 7, '2024-02-01T00:03:00.000000000Z');
+                "#
+            ).execute(&mut *transaction).await.unwrap();
+
+            transaction.commit().await.unwrap();
+        };
+
+        let history = repository
+            .get_submissions_by_user(GuildId::new(2), UserId::new(7))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            history,
+            vec![
+                SubmissionHistoryEntry {
+                    exchange_slug: "First".to_string(),
+                    link: "https://itch.io/jam/example-jam-2/rate/000002".to_string(),
+                    submitted_at: UtcDateTime::assume_utc(datetime!(2024-01-01 00:01:00)),
+                },
+                SubmissionHistoryEntry {
+                    exchange_slug: "Second".to_string(),
+                    link: "https://itch.io/jam/example-jam/rate/000001".to_string(),
+                    submitted_at: UtcDateTime::assume_utc(datetime!(2024-02-01 00:01:00)),
+                },
+            ]
+        );
+
+        let no_history = repository
+            .get_submissions_by_user(GuildId::new(2), UserId::new(9))
+            .await
+            .unwrap();
+        assert!(no_history.is_empty());
+    }
+
+    async fn insert_exchanges_with_submissions(pool: &SqlitePool) {
+        let mut transaction = pool.begin().await.unwrap();
+
+        query!(
+            r#"
+                INSERT INTO exchanges (id, guild, channel, jam_type, jam_link, slug, display_name, state, submissions_start, submissions_end, games_per_member) 
+                VALUES (1, 2, 3, 'Itch', 'https://itch.io/jam/example-jam', 'Test', 'Test', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5),
+                       (4, 5, 6, 'Itch', 'https://itch.io/jam/example-jam', 'Test2', 'Test 2', 'AcceptingSubmissions', '2024-01-01T00:00:00.000000000Z', '2024-01-02T00:00:00.000000000Z', 5);
+
+                INSERT INTO submissions (id, exchange_id, link, submitter, submitted_at)
+                VALUES (1, 1, 'https://itch.io/jam/example-jam/rate/000001', 7, '2024-01-01T00:01:00.000000000Z'),
+                       (2, 1, 'https://itch.io/jam/example-jam/rate/000002', 8, '2024-01-01T00:01:00.000000000Z'),
+                       (3, 4, 'https://itch.io/jam/example-jam/rate/000003', 9, '2024-01-01T00:01:00.000000000Z');
+            "#
+        ).execute(&mut *transaction).await.unwrap();
+
+        transaction.commit().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn submission_by_link_found() {
+        let pool = setup_database().await;
+        insert_exchanges_with_submissions(&pool).await;
+        let repository = SubmissionRepository::new(pool.clone());
+
+        let submission = repository
+            .get_submission_by_link(ExchangeId(1), "https://itch.io/jam/example-jam/rate/000002")
+            .await
+            .unwrap();
+
+        assert_eq!(submission.map(|s| s.id), Some(SubmissionId(2)));
+    }
+
+    #[tokio::test]
+    async fn submission_by_link_not_found() {
+        let pool = setup_database().await;
+        insert_exchanges_with_submissions(&pool).await;
+        let repository = SubmissionRepository::new(pool.clone());
+
+        let unknown = repository
+            .get_submission_by_link(ExchangeId(1), "https://itch.io/jam/example-jam/rate/000009")
+            .await
+            .unwrap();
+        assert_eq!(unknown, None);
+
+        // Submitted to another exchange
+        let other_exchange = repository
+            .get_submission_by_link(ExchangeId(1), "https://itch.io/jam/example-jam/rate/000003")
+            .await
+            .unwrap();
+        assert_eq!(other_exchange, None);
+    }
+
+    #[tokio::test]
+    async fn submission_by_link_is_normalized() {
+        let pool = setup_database().await;
+        insert_exchanges_with_submissions(&pool).await;
+        let repository = SubmissionRepository::new(pool.clone());
+
+        let submission = repository
+            .get_submission_by_link(
+                ExchangeId(1),
+                "https://itch.io/jam/example-jam/rate/000001/",
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(submission.map(|s| s.id), Some(SubmissionId(1)));
+
+        let submission = repository
+            .get_submission_by_link(
+                ExchangeId(1),
+                " https://Itch.io/jam/Example-Jam/rate/000002 ",
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(submission.map(|s| s.id), Some(SubmissionId(2)));
+
+        // Entries of an extra jam
+        query!(
+            r#"
+                INSERT INTO submissions (id, exchange_id, link, submitter, submitted_at)
+                VALUES (4, 1, 'https://ldjam.com/events/ludum-dare/56/example-game', 10, '2024-01-01T00:01:00.000000000Z');
+            "#
+        ).execute(&pool).await.unwrap();
+
+        let submission = repository
+            .get_submission_by_link(
+                ExchangeId(1),
+                "https://ldjam.com/events/ludum-dare/56/example-game/",
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(submission.map(|s| s.id), Some(SubmissionId(4)));
+    }
 }
@@ -0,0 +1,263 @@
+use tracing::trace;
+
+use crate::solver::flow_network::{Cost, Edge, Flow, FlowNetwork, Id};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Lowers the total cost of the flow without changing its value by repeatedly cancelling
+/// negative-cost cycles in the residual graph. Run it on a max flow to get a min-cost max flow.
+pub fn minimize_cost(network: &mut FlowNetwork) {
+    while let Some(cycle) = find_negative_cycle(network) {
+        let amount = cycle
+            .iter()
+            .map(|arc| arc.residual_capacity(network))
+            .min()
+            .unwrap_or(0);
+
+        trace!("Cancelling negative cycle {cycle:?} with {amount} units of flow");
+
+        if amount == 0 {
+            break;
+        }
+
+        for arc in &cycle {
+            let flow = network.flow(arc.edge);
+
+            if arc.forward {
+                network.set_flow(arc.edge, flow + amount);
+            } else {
+                network.set_flow(arc.edge, flow - amount);
+            }
+        }
+    }
+}
+
+/// Vertex potentials under which every residual edge has a non-negative reduced cost
+/// `cost + potential(start) - potential(end)`, so cycles of zero cost are exactly the cycles of
+/// edges with zero reduced cost. They only exist for a flow of minimum cost, otherwise `None`
+/// is returned.
+pub fn potentials(network: &FlowNetwork) -> Option<BTreeMap<Id, Cost>> {
+    let arcs = residual_arcs(network);
+
+    let vertices: BTreeSet<Id> = network
+        .edges()
+        .iter()
+        .flat_map(|edge| [edge.start, edge.end])
+        .collect();
+
+    // Bellman-Ford from a virtual vertex connected to every vertex
+    let mut distances: BTreeMap<Id, Cost> = vertices.iter().map(|&vertex| (vertex, 0)).collect();
+
+    for _ in 0..=vertices.len() {
+        let mut relaxed = false;
+
+        for arc in &arcs {
+            let candidate = distances[&arc.start] + arc.cost;
+
+            if candidate < distances[&arc.end] {
+                distances.insert(arc.end, candidate);
+                relaxed = true;
+            }
+        }
+
+        if !relaxed {
+            return Some(distances);
+        }
+    }
+
+    // Still relaxing after |V| rounds means there is a negative cycle
+    None
+}
+
+/// An edge of the residual graph: either an edge with spare capacity or the reverse of an edge
+/// carrying flow, which costs the negated edge cost.
+#[derive(Clone, Copy, Debug)]
+struct ResidualArc {
+    start: Id,
+    end: Id,
+    cost: Cost,
+    edge: Edge,
+    forward: bool,
+}
+
+impl ResidualArc {
+    fn residual_capacity(&self, network: &FlowNetwork) -> Flow {
+        if self.forward {
+            network.available_capacity(self.edge)
+        } else {
+            network.flow(self.edge)
+        }
+    }
+}
+
+fn residual_arcs(network: &FlowNetwork) -> Vec<ResidualArc> {
+    let mut arcs = Vec::new();
+
+    for &edge in network.edges() {
+        let cost = network.cost(edge);
+
+        if network.available_capacity(edge) > 0 {
+            arcs.push(ResidualArc {
+                start: edge.start,
+                end: edge.end,
+                cost,
+                edge,
+                forward: true,
+            });
+        }
+
+        if network.flow(edge) > 0 {
+            arcs.push(ResidualArc {
+                start: edge.end,
+                end: edge.start,
+                cost: -cost,
+                edge,
+                forward: false,
+            });
+        }
+    }
+
+    arcs
+}
+
+/// Bellman-Ford from a virtual vertex connected to every vertex, so cycles anywhere are found.
+fn find_negative_cycle(network: &FlowNetwork) -> Option<Vec<ResidualArc>> {
+    let arcs = residual_arcs(network);
+
+    let vertices: BTreeSet<Id> = network
+        .edges()
+        .iter()
+        .flat_map(|edge| [edge.start, edge.end])
+        .collect();
+
+    let mut distances: BTreeMap<Id, Cost> = vertices.iter().map(|&vertex| (vertex, 0)).collect();
+    let mut predecessors: BTreeMap<Id, ResidualArc> = BTreeMap::new();
+    let mut last_relaxed = None;
+
+    for _ in 0..vertices.len() {
+        last_relaxed = None;
+
+        for arc in &arcs {
+            let candidate = distances[&arc.start] + arc.cost;
+
+            if candidate < distances[&arc.end] {
+                distances.insert(arc.end, candidate);
+                predecessors.insert(arc.end, *arc);
+                last_relaxed = Some(arc.end);
+            }
+        }
+
+        last_relaxed?;
+    }
+
+    // Something still relaxed after |V| rounds, so it is reachable from a negative cycle.
+    // Walking back |V| steps is guaranteed to land on the cycle itself.
+    let mut vertex = last_relaxed?;
+    for _ in 0..vertices.len() {
+        vertex = predecessors[&vertex].start;
+    }
+
+    let mut cycle = Vec::new();
+    let mut current = vertex;
+    loop {
+        let arc = predecessors[&current];
+        cycle.push(arc);
+        current = arc.start;
+
+        if current == vertex {
+            break;
+        }
+    }
+    cycle.reverse();
+
+    Some(cycle)
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use crate::solver::{dinic, flow_network::FlowNetwork};
+
+    use super::{minimize_cost, potentials};
+
+    /// Two parallel routes from the source to the sink, the first one more expensive.
+    fn two_routes() -> FlowNetwork {
+        let mut network = FlowNetwork::empty(0, 1);
+
+        network.add_edge((0, 2), 1, 0);
+        network.add_edge((2, 3), 1, 0);
+        network.add_edge((2, 4), 1, 0);
+        network.add_edge((3, 1), 1, 0);
+        network.add_edge((4, 1), 1, 0);
+
+        network
+    }
+
+    #[test]
+    fn moves_flow_to_cheaper_route() {
+        let mut network = two_routes();
+        network.set_cost((2, 3), 5);
+
+        network.set_flow((0, 2), 1);
+        network.set_flow((2, 3), 1);
+        network.set_flow((3, 1), 1);
+
+        minimize_cost(&mut network);
+
+        assert_eq!(network.flow((2, 3)), 0);
+        assert_eq!(network.flow((2, 4)), 1);
+        assert_eq!(network.flow((4, 1)), 1);
+        assert_eq!(network.total_cost(), 0);
+        assert_eq!(network.validate(Some(1)), Ok(()));
+    }
+
+    #[test]
+    fn keeps_flow_value() {
+        let mut network = two_routes();
+        network.set_cost((2, 3), 5);
+        network.set_cost((2, 4), 5);
+
+        dinic::solve(&mut network);
+        minimize_cost(&mut network);
+
+        assert_eq!(network.total_cost(), 5);
+        assert_eq!(network.validate(Some(1)), Ok(()));
+    }
+
+    #[test]
+    fn nothing_to_improve() {
+        let mut network = two_routes();
+
+        dinic::solve(&mut network);
+        let flows = network.flows().clone();
+
+        minimize_cost(&mut network);
+
+        assert_eq!(network.flows(), &flows);
+    }
+
+    #[test]
+    fn potentials_only_for_minimum_cost() {
+        let mut network = two_routes();
+        network.set_cost((2, 3), 5);
+
+        network.set_flow((0, 2), 1);
+        network.set_flow((2, 3), 1);
+        network.set_flow((3, 1), 1);
+        assert_eq!(potentials(&network), None);
+
+        minimize_cost(&mut network);
+        let potentials = potentials(&network).unwrap();
+
+        // Every edge of the residual graph has a non-negative reduced cost
+        for &edge in network.edges() {
+            let reduced = network.cost(edge) + potentials[&edge.start] - potentials[&edge.end];
+            if network.available_capacity(edge) > 0 {
+                assert!(reduced >= 0, "{edge:?}");
+            }
+            if network.flow(edge) > 0 {
+                assert!(reduced <= 0, "{edge:?}");
+            }
+        }
+    }
+}
@@ -1,10 +1,11 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use std::fmt::{Debug, Formatter};
 
 pub type Id = u16;
 pub type Flow = u16;
+pub type Cost = i32;
 
-#[derive(Copy, Clone, Hash, Eq, PartialEq)]
+#[derive(Copy, Clone, Hash, Eq, PartialEq, PartialOrd, Ord)]
 pub struct Edge {
     pub start: Id,
     pub end: Id,
@@ -33,27 +34,29 @@ pub fn edge(start: Id, end: Id) -> Edge {
 }
 
 pub struct FlowNetwork {
-    edges: HashSet<Edge>,
+    edges: BTreeSet<Edge>,
     capacities: HashMap<Edge, Flow>,
     flows: HashMap<Edge, Flow>,
-    outgoing_edges: HashMap<Id, HashSet<Edge>>,
-    incoming_edges: HashMap<Id, HashSet<Edge>>,
+    costs: HashMap<Edge, Cost>,
+    outgoing_edges: BTreeMap<Id, BTreeSet<Edge>>,
+    incoming_edges: BTreeMap<Id, BTreeSet<Edge>>,
     source: Id,
     sink: Id,
-    empty: HashSet<Edge>, // FIXME: Replace this with something better.
+    empty: BTreeSet<Edge>, // FIXME: Replace this with something better.
 }
 
 impl FlowNetwork {
     pub fn empty(source: Id, sink: Id) -> FlowNetwork {
         FlowNetwork {
-            edges: HashSet::new(),
+            edges: BTreeSet::new(),
             capacities: HashMap::new(),
             flows: HashMap::new(),
-            outgoing_edges: HashMap::new(),
-            incoming_edges: HashMap::new(),
+            costs: HashMap::new(),
+            outgoing_edges: BTreeMap::new(),
+            incoming_edges: BTreeMap::new(),
             source,
             sink,
-            empty: HashSet::new(),
+            empty: BTreeSet::new(),
         }
     }
 
@@ -81,6 +84,7 @@ impl FlowNetwork {
         self.edges.remove(&edge);
         self.capacities.remove(&edge);
         self.flows.remove(&edge);
+        self.costs.remove(&edge);
 
         self.outgoing_edges.get_mut(&start).unwrap().remove(&edge);
         if self.outgoing_edges.get(&start).unwrap().is_empty() {
@@ -97,6 +101,7 @@ impl FlowNetwork {
         self.edges.clear();
         self.capacities.clear();
         self.flows.clear();
+        self.costs.clear();
         self.outgoing_edges.clear();
         self.incoming_edges.clear();
     }
@@ -109,15 +114,15 @@ impl FlowNetwork {
         self.sink
     }
 
-    pub fn edges(&self) -> &HashSet<Edge> {
+    pub fn edges(&self) -> &BTreeSet<Edge> {
         &self.edges
     }
 
-    pub fn outgoing_edges(&self, vertex: Id) -> &HashSet<Edge> {
+    pub fn outgoing_edges(&self, vertex: Id) -> &BTreeSet<Edge> {
         self.outgoing_edges.get(&vertex).unwrap_or(&self.empty)
     }
 
-    pub fn incoming_edges(&self, vertex: Id) -> &HashSet<Edge> {
+    pub fn incoming_edges(&self, vertex: Id) -> &BTreeSet<Edge> {
         self.incoming_edges.get(&vertex).unwrap_or(&self.empty)
     }
 
@@ -158,6 +163,58 @@ impl FlowNetwork {
         }
     }
 
+    /// The cost of pushing one unit of flow through the edge. Edges cost nothing by default.
+    pub fn cost(&self, edge: impl Into<Edge>) -> Cost {
+        let edge = edge.into();
+        *self.costs.get(&edge).unwrap_or(&0)
+    }
+
+    pub fn set_cost(&mut self, edge: impl Into<Edge>, cost: Cost) {
+        let edge = edge.into();
+        assert!(self.edges.contains(&edge));
+
+        self.costs.insert(edge, cost);
+    }
+
+    pub fn total_cost(&self) -> Cost {
+        self.flows
+            .iter()
+            .map(|(&edge, &flow)| self.cost(edge) * flow as Cost)
+            .sum()
+    }
+
+    /// Returns the edges of a minimum cut. Only meaningful after a max-flow solve, when the cut
+    /// edges are all saturated and show what limits the total flow.
+    pub fn min_cut(&self) -> BTreeSet<Edge> {
+        let mut reachable = BTreeSet::from([self.source]);
+        let mut queue = VecDeque::from([self.source]);
+
+        while let Some(vertex) = queue.pop_front() {
+            let forward = self
+                .outgoing_edges(vertex)
+                .iter()
+                .filter(|&&edge| self.available_capacity(edge) > 0)
+                .map(|edge| edge.end);
+            let backward = self
+                .incoming_edges(vertex)
+                .iter()
+                .filter(|&&edge| self.flow(edge) > 0)
+                .map(|edge| edge.start);
+
+            for next in forward.chain(backward).collect::<Vec<Id>>() {
+                if reachable.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        self.edges
+            .iter()
+            .filter(|edge| reachable.contains(&edge.start) && !reachable.contains(&edge.end))
+            .copied()
+            .collect()
+    }
+
     pub fn validate(&self, expected_total_flow: Option<Flow>) -> Result<(), String> {
         for &edge in self.edges() {
             let capacity = self.capacity(edge);
@@ -170,7 +227,7 @@ impl FlowNetwork {
             }
         }
 
-        let mut vertices = HashSet::new();
+        let mut vertices = BTreeSet::new();
         vertices.extend(self.edges().iter().flat_map(|e| [e.start, e.end]));
         for vertex in vertices {
             if vertex == self.source() || vertex == self.sink() {
@@ -223,17 +280,7 @@ impl FlowNetwork {
 
 impl Debug for FlowNetwork {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut edges = self.edges().iter().copied().collect::<Vec<Edge>>();
-        edges.sort_by(|&a, &b| {
-            use std::cmp::Ordering::*;
-
-            match a.start.cmp(&b.start) {
-                Equal => a.end.cmp(&b.end),
-                ord => ord,
-            }
-        });
-
-        for edge in edges {
+        for &edge in self.edges() {
             let capacity = self.capacity(edge);
             let flow = self.flow(edge);
 
@@ -246,10 +293,12 @@ impl Debug for FlowNetwork {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::{HashMap, HashSet};
+    use std::collections::{BTreeMap, BTreeSet, HashMap};
     use test_log::test;
 
-    use map_macro::{hash_map as map, hash_set as set};
+    use map_macro::{btree_map, btree_set as set, hash_map};
+
+    use crate::solver::dinic;
 
     use super::{edge, FlowNetwork};
 
@@ -258,11 +307,11 @@ mod tests {
         let network = FlowNetwork::empty(0, 1);
         assert_eq!(network.source, 0);
         assert_eq!(network.sink, 1);
-        assert_eq!(network.edges, HashSet::new());
+        assert_eq!(network.edges, BTreeSet::new());
         assert_eq!(network.capacities, HashMap::new());
         assert_eq!(network.flows, HashMap::new());
-        assert_eq!(network.outgoing_edges, HashMap::new());
-        assert_eq!(network.incoming_edges, HashMap::new());
+        assert_eq!(network.outgoing_edges, BTreeMap::new());
+        assert_eq!(network.incoming_edges, BTreeMap::new());
     }
 
     #[test]
@@ -290,7 +339,7 @@ mod tests {
 
         assert_eq!(
             network.capacities,
-            map! {
+            hash_map! {
                 edge(0, 1) => 5,
                 edge(1, 3) => 19,
                 edge(0, 2) => 3,
@@ -301,7 +350,7 @@ mod tests {
 
         assert_eq!(
             network.flows,
-            map! {
+            hash_map! {
                 edge(0, 1) => 0,
                 edge(1, 3) => 10,
                 edge(0, 2) => 3,
@@ -312,7 +361,7 @@ mod tests {
 
         assert_eq!(
             network.outgoing_edges,
-            map! {
+            btree_map! {
                 0 => set!(edge(0, 1), edge(0, 2)),
                 1 => set!(edge(1, 3), edge(1, 2)),
                 2 => set!(edge(2, 3)),
@@ -321,7 +370,7 @@ mod tests {
 
         assert_eq!(
             network.incoming_edges,
-            map! {
+            btree_map! {
                 1 => set!(edge(0, 1)),
                 2 => set!(edge(0, 2), edge(1, 2)),
                 3 => set!(edge(1, 3), edge(2, 3)),
@@ -355,7 +404,7 @@ mod tests {
 
         assert_eq!(
             network.capacities,
-            map! {
+            hash_map! {
                 edge(0, 1) => 5,
                 edge(1, 2) => 3,
             }
@@ -363,7 +412,7 @@ mod tests {
 
         assert_eq!(
             network.flows,
-            map! {
+            hash_map! {
                 edge(0, 1) => 0,
                 edge(1, 2) => 0,
             }
@@ -371,7 +420,7 @@ mod tests {
 
         assert_eq!(
             network.outgoing_edges,
-            map! {
+            btree_map! {
                 0 => set!(edge(0, 1)),
                 1 => set!(edge(1, 2)),
             }
@@ -379,10 +428,39 @@ mod tests {
 
         assert_eq!(
             network.incoming_edges,
-            map! {
+            btree_map! {
                 1 => set!(edge(0, 1)),
                 2 => set!(edge(1, 2)),
             }
         );
     }
+
+    #[test]
+    fn min_cut() {
+        // Two submitters can review a single entry each, which makes the entry side the bottleneck.
+        let mut network = FlowNetwork::empty(0, 5);
+        network.add_edge((0, 1), 2, 0);
+        network.add_edge((0, 2), 2, 0);
+        network.add_edge((1, 3), 1, 0);
+        network.add_edge((2, 3), 1, 0);
+        network.add_edge((1, 4), 1, 0);
+        network.add_edge((3, 5), 1, 0);
+        network.add_edge((4, 5), 3, 0);
+
+        dinic::solve(&mut network);
+        network.validate(Some(2)).unwrap();
+
+        let cut = network.min_cut();
+
+        assert_eq!(cut, set! { edge(3, 5), edge(1, 4) });
+        assert_eq!(cut.iter().map(|&e| network.capacity(e)).sum::<u16>(), 2);
+        assert!(cut.iter().all(|&e| network.flow(e) == network.capacity(e)));
+    }
+
+    #[test]
+    fn min_cut_of_empty_network() {
+        let network = FlowNetwork::empty(0, 1);
+
+        assert!(network.min_cut().is_empty());
+    }
 }
@@ -1,2 +1,3 @@
+pub mod cycle_canceling;
 pub mod dinic;
 pub mod flow_network;
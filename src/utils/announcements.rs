@@ -0,0 +1,480 @@
+use indoc::formatdoc;
+use serenity::all::Mentionable;
+
+use crate::{
+    assignment_service::{AssignmentStats, ExchangeAssignmentOutcome},
+    models::Exchange,
+    utils::formatting::{escape_markdown, format_local, format_utc, MAX_MESSAGE_LENGTH},
+};
+
+/// How many characters of a fetched entry title the assignment DM shows.
+const MAX_TITLE_LENGTH: usize = 100;
+
+/// How many unacknowledged submitters the admin summary mentions by name.
+const MAX_LISTED_UNACKED: usize = 20;
+
+/// The channel message telling members that they can submit their entries now.
+pub fn submissions_open(exchange: &Exchange) -> String {
+    formatdoc! {
+        r#"
+            # Review exchange {name} starts now!
+
+            **Submit your jam entry using the `/submit <entry link>` command.**
+
+            The exchange ends on {end_local} your time or {end_utc} UTC. You should submit your entry before this deadline.
+
+            After the deadline, you will receive a list of entries to play and rate in your DMs. Use the `/ready` command to confirm you can receive them.
+        "#,
+        name = exchange.display_name,
+        end_local = format_local(exchange.submissions_end),
+        end_utc = format_utc(exchange.submissions_end),
+    }
+}
+
+/// The channel message telling members that the assignments were sent.
+pub fn submissions_closed(exchange: &Exchange) -> String {
+    formatdoc! {
+        r#"
+            # Review exchange {name} has just ended!
+
+            **You should have received your assignments to play and rate in the DMs.**
+
+            If that didn't happen, please contact the moderators.
+        "#,
+        name = exchange.display_name,
+    }
+}
+
+/// An entry assigned to a member, with the page title when it could be fetched.
+pub struct AssignedEntry {
+    pub link: String,
+    pub title: Option<String>,
+}
+
+/// The DMs listing the entries a member should play and rate. Long lists are split into several
+/// messages, since titles can make them longer than Discord allows.
+pub fn assignments_dm(exchange: &Exchange, entries: &[AssignedEntry]) -> Vec<String> {
+    if entries.is_empty() {
+        return vec![formatdoc! {
+            r#"
+                # Could not assign you any entries for {exchange_name}
+
+                This probably means you have already played all entries for this exchange, or the algorithm could not find a solution.
+
+                No actions are needed on your side.
+            "#,
+            exchange_name = exchange.display_name,
+        }];
+    }
+
+    let footer = formatdoc! {
+        r#"
+
+            You are supposed to play and rate the assignments before the jam ends.
+
+            If you decide to rate some entries outside of the assignments, you can use the `/played <entry link>` command.
+            This will make sure these entries won't be assigned to you in the future.
+        "#,
+    };
+
+    let mut messages = Vec::new();
+    let mut message = "# Here are your assignments\n\n".to_string();
+
+    for entry in entries {
+        let line = assignment_line(&entry.link, entry.title.as_deref()) + "\n";
+        if message.len() + line.len() > MAX_MESSAGE_LENGTH {
+            messages.push(std::mem::take(&mut message));
+        }
+        message += &line;
+    }
+
+    if message.len() + footer.len() > MAX_MESSAGE_LENGTH {
+        messages.push(std::mem::take(&mut message));
+    }
+    message += &footer;
+    messages.push(message);
+
+    messages
+}
+
+/// The DM telling a member about an entry assigned to them by an admin.
+pub fn manual_assignment_dm(exchange: &Exchange, link: &str) -> String {
+    formatdoc! {
+        r#"
+            # You have been assigned an entry for {exchange_name}
+
+            {assignment}
+
+            You are supposed to play and rate the assignments before the jam ends.
+        "#,
+        exchange_name = exchange.display_name,
+        assignment = assignment_line(link, None),
+    }
+}
+
+/// The channel message with assignment statistics, for exchanges that share them publicly.
+pub fn assignment_stats(exchange: &Exchange, stats: &AssignmentStats) -> String {
+    formatdoc! {
+        r#"
+            # Assignment statistics for {name}
+
+            Reviewers: {reviewers}
+            Entries: {entries}
+            Average reviews per entry: {average:.1}
+        "#,
+        name = exchange.display_name,
+        reviewers = stats.reviewers,
+        entries = stats.entries,
+        average = stats.average_reviews_per_entry(),
+    }
+}
+
+/// The entry in the guild's assignment log, for moderators to keep track of every pass.
+pub fn assignment_log(exchange: &Exchange, outcome: &ExchangeAssignmentOutcome) -> String {
+    formatdoc! {
+        r#"
+            ### {name} (`{slug}`)
+            Outcome: {state}
+            Entries: {entries}
+            Reviewers: {reviewers}
+            Assignments: {assignments}
+            Backfill assignments: {backfill_assignments}
+            DMs delivered: {dms_delivered}
+            DMs failed: {dms_failed}
+            Unacknowledged submitters: {unacked}
+        "#,
+        name = exchange.display_name,
+        slug = exchange.slug,
+        state = outcome.final_state.label(),
+        entries = outcome.stats.entries,
+        reviewers = outcome.stats.reviewers,
+        assignments = outcome.stats.assignments,
+        backfill_assignments = outcome.stats.backfill_assignments,
+        dms_delivered = outcome.dms_delivered,
+        dms_failed = outcome.dms_failed,
+        unacked = outcome.unacked_submitters.len(),
+    }
+}
+
+/// The DM telling the exchange creator how the assignments went.
+pub fn admin_summary(exchange: &Exchange, outcome: &ExchangeAssignmentOutcome) -> String {
+    let failed_note = if outcome.dms_failed > 0 {
+        "\nMembers who didn't get their assignments probably have DMs from server members disabled. You can find their assignments with `/exchange export`, they have `no` in the `delivered` column.\n"
+    } else {
+        ""
+    };
+    let unacked_note = if outcome.unacked_submitters.is_empty() {
+        String::new()
+    } else {
+        let mut mentions = outcome
+            .unacked_submitters
+            .iter()
+            .take(MAX_LISTED_UNACKED)
+            .map(|user| user.mention().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        if let Some(rest) = outcome
+            .unacked_submitters
+            .len()
+            .checked_sub(MAX_LISTED_UNACKED)
+            .filter(|&rest| rest > 0)
+        {
+            mentions += &format!(" and {rest} more");
+        }
+        format!("\nThese submitters never confirmed with `/ready` that they can receive DMs: {mentions}\n")
+    };
+
+    formatdoc! {
+        r#"
+            # Assignments for {exchange_name} were sent
+
+            Assignments made: {assignments_made}
+            Backfill assignments: {backfill_assignments}
+            DMs delivered: {dms_delivered}
+            DMs failed: {dms_failed}
+            {failed_note}{unacked_note}
+        "#,
+        exchange_name = exchange.display_name,
+        assignments_made = outcome.stats.assignments,
+        backfill_assignments = outcome.stats.backfill_assignments,
+        dms_delivered = outcome.dms_delivered,
+        dms_failed = outcome.dms_failed,
+    }
+}
+
+fn assignment_line(link: &str, title: Option<&str>) -> String {
+    match title {
+        Some(title) => {
+            let title = if title.chars().count() > MAX_TITLE_LENGTH {
+                let cut = title.chars().take(MAX_TITLE_LENGTH - 1).collect::<String>();
+                format!("{}…", cut.trim_end())
+            } else {
+                title.to_string()
+            };
+            format!("- {}: {link}", escape_markdown(&title))
+        }
+        None => format!("- {link}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::all::{ChannelId, GuildId, UserId};
+    use time::OffsetDateTime;
+
+    use crate::{
+        assignment_service::{AssignmentStats, ExchangeAssignmentOutcome},
+        models::{test_exchange, types::UtcDateTime, Exchange, ExchangeId, ExchangeState},
+        utils::formatting::{format_local, format_utc, MAX_MESSAGE_LENGTH},
+    };
+
+    use super::{
+        admin_summary, assignment_line, assignment_log, assignment_stats, assignments_dm,
+        manual_assignment_dm, submissions_closed, submissions_open, AssignedEntry,
+    };
+
+    fn exchange(id: ExchangeId) -> Exchange {
+        let now = UtcDateTime::from(OffsetDateTime::now_utc());
+
+        Exchange {
+            id,
+            guild: GuildId::new(1),
+            channel: ChannelId::new(2),
+            display_name: "Test Exchange".to_string(),
+            state: ExchangeState::AssignmentsSent,
+            submissions_start: now,
+            submissions_end: now,
+            created_by: Some(UserId::new(42)),
+            ..test_exchange()
+        }
+    }
+
+    #[test]
+    fn submissions_open_mentions_deadline() {
+        let exchange = exchange(ExchangeId(1));
+
+        let announcement = submissions_open(&exchange);
+
+        assert!(announcement.starts_with("# Review exchange Test Exchange starts now!"));
+        assert!(announcement.contains("`/submit <entry link>`"));
+        assert!(announcement.contains(&format!(
+            "The exchange ends on {} your time or {} UTC.",
+            format_local(exchange.submissions_end),
+            format_utc(exchange.submissions_end),
+        )));
+    }
+
+    #[test]
+    fn public_assignment_stats() {
+        let stats = AssignmentStats {
+            reviewers: 6,
+            entries: 7,
+            assignments: 17,
+            backfill_assignments: 3,
+        };
+
+        let announcement = assignment_stats(&exchange(ExchangeId(1)), &stats);
+
+        assert_eq!(
+            announcement,
+            "# Assignment statistics for Test Exchange\n\nReviewers: 6\nEntries: 7\nAverage reviews per entry: 2.4\n"
+        );
+    }
+
+    #[test]
+    fn assignment_stats_without_entries() {
+        let announcement = assignment_stats(&exchange(ExchangeId(1)), &AssignmentStats::default());
+
+        assert!(announcement.contains("Average reviews per entry: 0.0"));
+    }
+
+    #[test]
+    fn assignment_log_entry() {
+        let outcome = ExchangeAssignmentOutcome {
+            exchange_id: ExchangeId(1),
+            stats: AssignmentStats {
+                reviewers: 4,
+                entries: 5,
+                assignments: 8,
+                backfill_assignments: 2,
+            },
+            dms_delivered: 3,
+            dms_failed: 1,
+            unacked_submitters: vec![UserId::new(7)],
+            final_state: ExchangeState::AssignmentsSent,
+        };
+
+        assert_eq!(
+            assignment_log(&exchange(ExchangeId(1)), &outcome),
+            "### Test Exchange (`Test`)\nOutcome: Assignments sent\nEntries: 5\nReviewers: 4\nAssignments: 8\nBackfill assignments: 2\nDMs delivered: 3\nDMs failed: 1\nUnacknowledged submitters: 1\n"
+        );
+    }
+
+    #[test]
+    fn admin_summary_counts() {
+        let outcome = ExchangeAssignmentOutcome {
+            exchange_id: ExchangeId(1),
+            stats: AssignmentStats {
+                reviewers: 4,
+                entries: 4,
+                assignments: 12,
+                backfill_assignments: 0,
+            },
+            dms_delivered: 3,
+            dms_failed: 1,
+            unacked_submitters: Vec::new(),
+            final_state: ExchangeState::AssignmentsSent,
+        };
+        let exchange = exchange(outcome.exchange_id);
+
+        let summary = admin_summary(&exchange, &outcome);
+
+        assert!(summary.starts_with("# Assignments for Test Exchange were sent"));
+        assert!(summary.contains("Assignments made: 12"));
+        assert!(summary.contains("DMs delivered: 3"));
+        assert!(summary.contains("DMs failed: 1"));
+        assert!(summary.contains("`/exchange export`"));
+        assert!(!summary.contains("`/ready`"));
+
+        let all_delivered = admin_summary(
+            &exchange,
+            &ExchangeAssignmentOutcome {
+                dms_delivered: 4,
+                dms_failed: 0,
+                ..outcome
+            },
+        );
+        assert!(!all_delivered.contains("`/exchange export`"));
+    }
+
+    #[test]
+    fn admin_summary_mentions_unacked_submitters() {
+        let outcome = ExchangeAssignmentOutcome {
+            exchange_id: ExchangeId(1),
+            stats: AssignmentStats::default(),
+            dms_delivered: 2,
+            dms_failed: 0,
+            unacked_submitters: vec![UserId::new(5), UserId::new(6)],
+            final_state: ExchangeState::AssignmentsSent,
+        };
+        let exchange = exchange(outcome.exchange_id);
+
+        let summary = admin_summary(&exchange, &outcome);
+        assert!(summary.contains(
+            "These submitters never confirmed with `/ready` that they can receive DMs: <@5>, <@6>\n"
+        ));
+
+        let many = admin_summary(
+            &exchange,
+            &ExchangeAssignmentOutcome {
+                unacked_submitters: (1..=25).map(UserId::new).collect(),
+                ..outcome
+            },
+        );
+        assert!(many.contains("<@20> and 5 more\n"));
+        assert!(!many.contains("<@21>"));
+    }
+
+    #[test]
+    fn submissions_closed_names_exchange() {
+        let announcement = submissions_closed(&exchange(ExchangeId(1)));
+
+        assert!(announcement.starts_with("# Review exchange Test Exchange has just ended!"));
+        assert!(announcement.contains("please contact the moderators"));
+    }
+
+    #[test]
+    fn assignments_dm_lists_entries() {
+        let exchange = exchange(ExchangeId(1));
+        let entries = [
+            AssignedEntry {
+                link: "https://itch.io/jam/example-jam/rate/000001".to_string(),
+                title: Some("First Game by dev".to_string()),
+            },
+            AssignedEntry {
+                link: "https://itch.io/jam/example-jam/rate/000002".to_string(),
+                title: None,
+            },
+        ];
+
+        let dms = assignments_dm(&exchange, &entries);
+
+        assert_eq!(dms.len(), 1);
+        let dm = &dms[0];
+        assert!(dm.starts_with("# Here are your assignments\n\n"));
+        assert!(dm.contains(
+            "- First Game by dev: https://itch.io/jam/example-jam/rate/000001\n- https://itch.io/jam/example-jam/rate/000002\n"
+        ));
+        assert!(dm.contains("`/played <entry link>`"));
+    }
+
+    #[test]
+    fn assignments_dm_without_entries() {
+        let dms = assignments_dm(&exchange(ExchangeId(1)), &[]);
+
+        assert_eq!(dms.len(), 1);
+        let dm = &dms[0];
+        assert!(dm.starts_with("# Could not assign you any entries for Test Exchange"));
+        assert!(dm.contains("No actions are needed on your side."));
+    }
+
+    #[test]
+    fn long_assignments_dm_is_split() {
+        let entries = (1..=32)
+            .map(|i| AssignedEntry {
+                link: format!("https://itch.io/jam/example-jam/rate/{i:06}"),
+                title: Some("a".repeat(100)),
+            })
+            .collect::<Vec<_>>();
+
+        let dms = assignments_dm(&exchange(ExchangeId(1)), &entries);
+
+        assert!(dms.len() > 1);
+        assert!(dms.iter().all(|dm| dm.len() <= MAX_MESSAGE_LENGTH));
+        assert!(dms[0].starts_with("# Here are your assignments\n\n"));
+        assert!(dms.last().unwrap().contains("`/played <entry link>`"));
+
+        let all = dms.concat();
+        for entry in &entries {
+            let line = format!("- {}: {}\n", "a".repeat(100), entry.link);
+            assert_eq!(all.matches(&line).count(), 1);
+        }
+    }
+
+    #[test]
+    fn manual_assignment_dm_names_exchange_and_entry() {
+        let link = "https://itch.io/jam/example-jam/rate/000001";
+
+        assert_eq!(
+            manual_assignment_dm(&exchange(ExchangeId(1)), link),
+            format!("# You have been assigned an entry for Test Exchange\n\n- {link}\n\nYou are supposed to play and rate the assignments before the jam ends.\n")
+        );
+    }
+
+    #[test]
+    fn assignment_line_falls_back_to_link() {
+        let link = "https://itch.io/jam/example-jam/rate/000001";
+
+        assert_eq!(
+            assignment_line(link, Some("Example Game by dev")),
+            format!("- Example Game by dev: {link}")
+        );
+        assert_eq!(assignment_line(link, None), format!("- {link}"));
+    }
+
+    #[test]
+    fn assignment_line_escapes_and_caps_title() {
+        let link = "https://itch.io/jam/example-jam/rate/000001";
+
+        assert_eq!(
+            assignment_line(link, Some("**Bold** [click](https://example.com) by _dev_")),
+            format!("- \\*\\*Bold\\*\\* \\[click\\]\\(https://example.com\\) by \\_dev\\_: {link}")
+        );
+
+        let long = "a".repeat(150);
+        assert_eq!(
+            assignment_line(link, Some(&long)),
+            format!("- {}…: {link}", "a".repeat(99))
+        );
+    }
+}
@@ -1,18 +1,39 @@
-use bimap::BiMap;
+use bimap::BiBTreeMap;
 use serenity::all::UserId;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use tracing::error;
 
 use crate::{
+    jam_types::normalize_entry_link,
     models::{Exchange, PlayedGame, Submission, SubmissionId},
-    solver::flow_network::{self, FlowNetwork},
+    solver::{
+        cycle_canceling,
+        flow_network::{self, FlowNetwork},
+    },
 };
 
+/// The highest reciprocal penalty an exchange can set. The penalty is the cost of the penalized
+/// edge in each pair of edges that would form a reciprocal assignment. Only one edge of the pair
+/// carries it, so this is a heuristic: it also charges the penalized edge when it's used alone,
+/// but the cheapest flow still tends to avoid reciprocal pairs.
+pub const MAX_RECIPROCAL_PENALTY: u8 = 10;
+/// Cost of assigning a backfill entry. Higher than any reciprocal penalty, since members should
+/// rather review each other's entries than fall back to entries from outside the exchange.
+const BACKFILL_COST: flow_network::Cost = MAX_RECIPROCAL_PENALTY as flow_network::Cost + 1;
+
 #[derive(Debug)]
 pub struct AssignmentNetwork {
     pub network: FlowNetwork,
-    submissions: HashMap<SubmissionId, Submission>,
-    submitter_nodes: BiMap<UserId, flow_network::Id>,
-    submission_nodes: BiMap<SubmissionId, flow_network::Id>,
+    submissions: BTreeMap<SubmissionId, Submission>,
+    submitter_nodes: BiBTreeMap<UserId, flow_network::Id>,
+    submission_nodes: BiBTreeMap<SubmissionId, flow_network::Id>,
+    /// Entries from outside the exchange, e.g. from a previous round, that top up reviewers who
+    /// couldn't get enough entries of the exchange.
+    backfill_nodes: BiBTreeMap<String, flow_network::Id>,
+    allocator: IndexAllocator,
+    games_per_member: u8,
+    reciprocal_penalty: u8,
+    played_games: BTreeMap<UserId, BTreeSet<String>>,
 }
 
 impl AssignmentNetwork {
@@ -22,7 +43,7 @@ impl AssignmentNetwork {
         played_games: &[PlayedGame],
     ) -> AssignmentNetwork {
         let submissions = {
-            let mut map = HashMap::new();
+            let mut map = BTreeMap::new();
 
             for submission in submissions {
                 map.insert(submission.id, submission);
@@ -31,88 +52,317 @@ impl AssignmentNetwork {
             map
         };
 
-        let submitter_played_games = {
-            let mut map: HashMap<UserId, HashSet<String>> = HashMap::new();
+        let mut allocator = IndexAllocator::new();
+        let source = allocator.next();
+        let sink = allocator.next();
+
+        let mut network = AssignmentNetwork {
+            network: FlowNetwork::empty(source, sink),
+            submissions: BTreeMap::new(),
+            submitter_nodes: BiBTreeMap::new(),
+            submission_nodes: BiBTreeMap::new(),
+            backfill_nodes: BiBTreeMap::new(),
+            allocator,
+            games_per_member: exchange.games_per_member.get(),
+            reciprocal_penalty: exchange.reciprocal_penalty.min(MAX_RECIPROCAL_PENALTY),
+            played_games: submitter_played_games(exchange, played_games),
+        };
+
+        let submission_count = submissions.len();
+        for submission in submissions.into_values() {
+            network.add_submission(submission);
+        }
+
+        if lacks_source_edges(&network.network, submission_count) {
+            error!(
+                "The assignment network for exchange {:?} has {} submissions but no edges from the source, nothing will be assigned",
+                exchange.id,
+                submission_count
+            );
+        }
+
+        network
+    }
+
+    /// Brings the network up to date with the current submissions of the exchange.
+    ///
+    /// If submissions were only added, they are added to the network and the existing flow is
+    /// kept, so solving again only has to route the flow through the new edges. Entries played
+    /// since the last sync only lose their edge, along with the flow through it. Any other change,
+    /// like a revoked submission, rebuilds the network from scratch.
+    /// Returns whether the network was updated rather than rebuilt.
+    pub fn sync(
+        &mut self,
+        exchange: &Exchange,
+        submissions: Vec<Submission>,
+        played_games: &[PlayedGame],
+        backfill: &[String],
+    ) -> bool {
+        let submissions_kept = submissions
+            .iter()
+            .filter(|submission| self.submissions.get(&submission.id) == Some(submission))
+            .count()
+            == self.submissions.len();
+        let backfill_kept = self.backfill_nodes.len() == backfill.len()
+            && backfill.iter().all(|link| {
+                self.backfill_nodes
+                    .contains_left(&normalize_entry_link(link))
+            });
+        let settings_kept = self.games_per_member == exchange.games_per_member.get()
+            && self.reciprocal_penalty == exchange.reciprocal_penalty.min(MAX_RECIPROCAL_PENALTY);
 
-            for played_game in played_games {
-                let member = played_game.member;
+        if !(submissions_kept && backfill_kept && settings_kept) {
+            *self = AssignmentNetwork::build(exchange, submissions, played_games);
+            self.add_backfill(backfill);
+            return false;
+        }
 
-                map.entry(member)
-                    .or_default()
-                    .insert(played_game.link.clone());
+        let old_played_games = std::mem::replace(
+            &mut self.played_games,
+            submitter_played_games(exchange, played_games),
+        );
+
+        // Played games only matter for the edges to the entries already in the network, the
+        // new submissions get their edges from the current ones.
+        let entries = self
+            .submissions
+            .values()
+            .map(|submission| {
+                let entry_node = self.submission_nodes.get_by_left(&submission.id).copied();
+                (
+                    Some(submission.submitter),
+                    submission.link.clone(),
+                    entry_node,
+                )
+            })
+            .chain(
+                self.backfill_nodes
+                    .iter()
+                    .map(|(link, &node)| (None, link.clone(), Some(node))),
+            )
+            .filter_map(|(author, link, entry_node)| Some((author, link, entry_node?)))
+            .collect::<Vec<_>>();
+        let mut changed = Vec::new();
+        for &reviewer in self.submitter_nodes.left_values() {
+            for (author, link, entry_node) in &entries {
+                let played = has_played(&self.played_games, reviewer, link);
+                if played != has_played(&old_played_games, reviewer, link) {
+                    changed.push((reviewer, *author, *entry_node, played));
+                }
             }
+        }
+        for (reviewer, author, entry_node, played) in changed {
+            self.update_played_edge(reviewer, author, entry_node, played);
+        }
 
-            map
-        };
+        let added = submissions
+            .into_iter()
+            .filter(|submission| !self.submissions.contains_key(&submission.id))
+            .map(|submission| (submission.id, submission))
+            .collect::<BTreeMap<_, _>>();
+        for submission in added.into_values() {
+            self.add_submission(submission);
+        }
 
-        let mut allocator = IndexAllocator::new();
-        let source = allocator.next();
-        let sink = allocator.next();
-        let mut network = FlowNetwork::empty(source, sink);
-
-        let (submitter_nodes, submission_nodes) = {
-            let mut submitter_nodes = BiMap::new();
-            let mut submission_nodes = BiMap::new();
-
-            for submission in submissions.values() {
-                let submitter_node = allocator.next();
-                submitter_nodes.insert(submission.submitter, submitter_node);
-                network.add_edge(
-                    (source, submitter_node),
-                    exchange.games_per_member.get() as _,
-                    0,
-                );
-
-                let submission_node = allocator.next();
-                submission_nodes.insert(submission.id, submission_node);
-                network.add_edge(
-                    (submission_node, sink),
-                    exchange.games_per_member.get() as _,
-                    0,
-                );
+        true
+    }
+
+    /// Adds the nodes and edges of a submission without touching the existing flow.
+    fn add_submission(&mut self, submission: Submission) {
+        let source = self.network.source();
+        let sink = self.network.sink();
+        let capacity = self.games_per_member as _;
+
+        let submitter_node = self.allocator.next();
+        self.submitter_nodes
+            .insert(submission.submitter, submitter_node);
+        self.network.add_edge((source, submitter_node), capacity, 0);
+
+        let submission_node = self.allocator.next();
+        self.submission_nodes.insert(submission.id, submission_node);
+        self.network.add_edge((submission_node, sink), capacity, 0);
+
+        let others = self
+            .submissions
+            .values()
+            .filter(|other| other.submitter != submission.submitter)
+            .filter_map(|other| {
+                let reviewer_node = *self.submitter_nodes.get_by_left(&other.submitter)?;
+                let entry_node = *self.submission_nodes.get_by_left(&other.id)?;
+
+                Some((
+                    other.submitter,
+                    other.link.clone(),
+                    reviewer_node,
+                    entry_node,
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        for (other_submitter, other_link, other_reviewer_node, other_entry_node) in others {
+            let reviews_other = flow_network::edge(submitter_node, other_entry_node);
+            if !has_played(&self.played_games, submission.submitter, &other_link) {
+                self.network.add_edge(reviews_other, 1, 0);
+            }
+
+            let reviewed_by_other = flow_network::edge(other_reviewer_node, submission_node);
+            if !has_played(&self.played_games, other_submitter, &submission.link) {
+                self.network.add_edge(reviewed_by_other, 1, 0);
+            }
+
+            self.update_reciprocal_cost(
+                (submission.submitter, reviews_other),
+                (other_submitter, reviewed_by_other),
+            );
+        }
+
+        let backfill = self
+            .backfill_nodes
+            .iter()
+            .map(|(link, &node)| (link.clone(), node))
+            .collect::<Vec<_>>();
+        for (link, backfill_node) in backfill {
+            if normalize_entry_link(&link) != normalize_entry_link(&submission.link)
+                && !has_played(&self.played_games, submission.submitter, &link)
+            {
+                self.network.add_edge((submitter_node, backfill_node), 1, 0);
+                self.network
+                    .set_cost((submitter_node, backfill_node), BACKFILL_COST);
             }
+        }
+
+        self.submissions.insert(submission.id, submission);
+    }
 
-            (submitter_nodes, submission_nodes)
+    /// A flow network can't charge for using two edges together, so as an approximation one
+    /// edge of each pair that could form a reciprocal assignment (the one reviewed by the higher
+    /// ID, so the choice is stable) is penalized. This biases the cheapest flow towards longer
+    /// review chains, but it can also avoid the penalized edge when the pair wouldn't have been
+    /// reciprocal.
+    ///
+    /// Takes each member with the edge for them reviewing the other's entry, which may not exist.
+    fn update_reciprocal_cost(
+        &mut self,
+        (first, first_reviews): (UserId, flow_network::Edge),
+        (second, second_reviews): (UserId, flow_network::Edge),
+    ) {
+        if self.reciprocal_penalty == 0 {
+            return;
+        }
+
+        let (penalized, other) = if first > second {
+            (first_reviews, second_reviews)
+        } else {
+            (second_reviews, first_reviews)
         };
+        if !self.network.edges().contains(&penalized) {
+            return;
+        }
 
-        for src_submission in submissions.values() {
-            let src_node = match submitter_nodes.get_by_left(&src_submission.submitter) {
-                Some(node) => *node,
-                None => continue,
-            };
+        let cost = if self.network.edges().contains(&other) {
+            self.reciprocal_penalty.into()
+        } else {
+            0
+        };
+        self.network.set_cost(penalized, cost);
+    }
 
-            let played_games = match submitter_played_games.get(&src_submission.submitter) {
-                Some(games) => games,
-                None => &HashSet::new(),
-            };
+    /// Removes the edge from the reviewer to an entry they have played since, or adds it back
+    /// if the entry is no longer played. Entries without an author are backfill entries.
+    fn update_played_edge(
+        &mut self,
+        reviewer: UserId,
+        author: Option<UserId>,
+        entry_node: flow_network::Id,
+        played: bool,
+    ) {
+        let Some(&reviewer_node) = self.submitter_nodes.get_by_left(&reviewer) else {
+            return;
+        };
+        // Members never review their own entry
+        if author == Some(reviewer) {
+            return;
+        }
 
-            for dst_submission in submissions.values() {
-                if src_submission.submitter == dst_submission.submitter {
-                    continue;
-                }
-                if played_games.contains(&dst_submission.link) {
-                    continue;
-                }
+        let review = flow_network::edge(reviewer_node, entry_node);
+        let exists = self.network.edges().contains(&review);
 
-                let dst_node = match submission_nodes.get_by_left(&dst_submission.id) {
-                    Some(node) => *node,
-                    None => continue,
-                };
+        if played && exists {
+            // Take the flow through the edge back out, the next solve routes it elsewhere
+            let flow = self.network.flow(review);
+            for edge in [
+                flow_network::edge(self.network.source(), reviewer_node),
+                flow_network::edge(entry_node, self.network.sink()),
+            ] {
+                let remaining = self.network.flow(edge) - flow;
+                self.network.set_flow(edge, remaining);
+            }
 
-                network.add_edge((src_node, dst_node), 1, 0);
+            self.network.remove_edge(review);
+        } else if !played && !exists {
+            self.network.add_edge(review, 1, 0);
+            if author.is_none() {
+                self.network.set_cost(review, BACKFILL_COST);
             }
         }
 
-        AssignmentNetwork {
-            network,
-            submissions,
-            submitter_nodes,
-            submission_nodes,
+        let Some(author) = author else {
+            return;
+        };
+        let reverse = self
+            .submitter_nodes
+            .get_by_left(&author)
+            .zip(self.own_entry_node(reviewer))
+            .map(|(&author_node, reviewer_entry)| flow_network::edge(author_node, reviewer_entry));
+        if let Some(reverse) = reverse {
+            self.update_reciprocal_cost((reviewer, review), (author, reverse));
+        }
+    }
+
+    fn own_entry_node(&self, member: UserId) -> Option<flow_network::Id> {
+        let submission = self
+            .submissions
+            .values()
+            .find(|submission| submission.submitter == member)?;
+
+        self.submission_nodes.get_by_left(&submission.id).copied()
+    }
+
+    /// Adds entries from outside the exchange that reviewers get only if there aren't enough
+    /// entries of the exchange they can review. Solving with [`Self::minimize_cost`] keeps their
+    /// use to a minimum. Links of the exchange's own submissions are skipped.
+    pub fn add_backfill(&mut self, links: &[String]) {
+        let sink = self.network.sink();
+        let capacity = self.games_per_member as _;
+
+        for link in links {
+            let link = &normalize_entry_link(link);
+            let is_submission = self
+                .submissions
+                .values()
+                .any(|submission| &normalize_entry_link(&submission.link) == link);
+            if is_submission || self.backfill_nodes.contains_left(link) {
+                continue;
+            }
+
+            let backfill_node = self.allocator.next();
+            self.backfill_nodes.insert(link.clone(), backfill_node);
+            self.network.add_edge((backfill_node, sink), capacity, 0);
+
+            for (&reviewer, &reviewer_node) in &self.submitter_nodes {
+                if has_played(&self.played_games, reviewer, link) {
+                    continue;
+                }
+
+                self.network.add_edge((reviewer_node, backfill_node), 1, 0);
+                self.network
+                    .set_cost((reviewer_node, backfill_node), BACKFILL_COST);
+            }
         }
     }
 
-    pub fn get_assignments(&self) -> HashMap<UserId, Vec<Submission>> {
-        let mut map = HashMap::new();
+    pub fn get_assignments(&self) -> BTreeMap<UserId, Vec<Submission>> {
+        let mut map = BTreeMap::new();
 
         for (&user_id, &node) in &self.submitter_nodes {
             // TODO: Maybe log something if an entry was not found
@@ -123,6 +373,17 @@ impl AssignmentNetwork {
                 .filter(|&&edge| self.network.flow(edge) > 0)
                 .filter_map(|edge| self.submission_nodes.get_by_right(&edge.end))
                 .filter_map(|submission_id| self.submissions.get(submission_id))
+                .filter(|submission| {
+                    // `build` never adds self-edges, so this only guards against bugs elsewhere.
+                    let is_own = submission.submitter == user_id;
+                    if is_own {
+                        error!(
+                            "User {user_id} was assigned their own submission {:?}, dropping it",
+                            submission.id
+                        );
+                    }
+                    !is_own
+                })
                 .cloned()
                 .collect();
 
@@ -131,8 +392,245 @@ impl AssignmentNetwork {
 
         map
     }
+
+    /// The backfill entries assigned to each reviewer, by link.
+    pub fn get_backfill_assignments(&self) -> BTreeMap<UserId, Vec<String>> {
+        let mut map = BTreeMap::new();
+
+        for (&user_id, &node) in &self.submitter_nodes {
+            let links = self
+                .network
+                .outgoing_edges(node)
+                .iter()
+                .filter(|&&edge| self.network.flow(edge) > 0)
+                .filter_map(|edge| self.backfill_nodes.get_by_right(&edge.end))
+                .cloned()
+                .collect::<Vec<_>>();
+
+            if !links.is_empty() {
+                map.insert(user_id, links);
+            }
+        }
+
+        map
+    }
+
+    /// Rewrites the solved flow into a canonical assignment with the same total flow and cost:
+    /// reviewers are visited in ascending ID order and each one gets the lowest submission IDs
+    /// that can still be given to them without touching earlier reviewers. Only swaps that keep
+    /// the cost are made, so this must run after [`Self::minimize_cost`].
+    ///
+    /// The assignment service always runs this on the final assignments. Every reviewer costs a
+    /// sort of their candidates and one residual search per candidate that isn't assigned yet.
+    pub fn canonicalize_assignment(&mut self) {
+        let Some(potentials) = cycle_canceling::potentials(&self.network) else {
+            error!("Not canonicalizing an assignment whose cost is not minimal");
+            return;
+        };
+
+        let reviewers: Vec<(UserId, flow_network::Id)> = self
+            .submitter_nodes
+            .iter()
+            .map(|(&user_id, &node)| (user_id, node))
+            .collect();
+
+        let mut locked_nodes = BTreeSet::new();
+
+        for (_, reviewer_node) in reviewers {
+            locked_nodes.insert(reviewer_node);
+
+            let mut candidates: Vec<(SubmissionId, flow_network::Id)> = self
+                .network
+                .outgoing_edges(reviewer_node)
+                .iter()
+                .filter_map(|edge| {
+                    self.submission_nodes
+                        .get_by_right(&edge.end)
+                        .map(|&submission_id| (submission_id, edge.end))
+                })
+                .collect();
+            candidates.sort();
+
+            // The currently assigned submissions that can be swapped out as long as the swap
+            // keeps the cost, by their index in `candidates`. Swaps only change the reviewer's
+            // own edges at both ends of the path, so this is kept up to date along the way.
+            let mut swappable: BiBTreeMap<usize, flow_network::Id> = candidates
+                .iter()
+                .enumerate()
+                .filter(|&(_, &(_, node))| self.network.flow((reviewer_node, node)) > 0)
+                .filter(|&(_, &(_, node))| {
+                    reduced_cost(&self.network, &potentials, (reviewer_node, node)) == 0
+                })
+                .map(|(index, &(_, node))| (index, node))
+                .collect();
+
+            for (index, &(_, submission_node)) in candidates.iter().enumerate() {
+                let assigned = flow_network::edge(reviewer_node, submission_node);
+                if self.network.flow(assigned) > 0
+                    || reduced_cost(&self.network, &potentials, assigned) != 0
+                {
+                    continue;
+                }
+
+                // Any currently assigned submission with a higher ID can be swapped out
+                if swappable.left_range(index + 1..).next().is_none() {
+                    break;
+                }
+
+                let Some(path) = self.find_residual_path(
+                    submission_node,
+                    |node| {
+                        swappable
+                            .get_by_right(&node)
+                            .is_some_and(|&other| other > index)
+                    },
+                    &locked_nodes,
+                    &potentials,
+                ) else {
+                    continue;
+                };
+
+                let swapped_out = *path.last().unwrap_or(&submission_node);
+
+                self.push_unit((reviewer_node, submission_node));
+                for step in path.windows(2) {
+                    self.push_unit((step[0], step[1]));
+                }
+                self.push_unit((swapped_out, reviewer_node));
+
+                swappable.remove_by_right(&swapped_out);
+                swappable.insert(index, submission_node);
+            }
+        }
+    }
+
+    /// Lowers the cost of the solved flow without changing its value, e.g. moving away from
+    /// the edges penalized in `build`.
+    pub fn minimize_cost(&mut self) {
+        cycle_canceling::minimize_cost(&mut self.network);
+    }
+
+    /// Breadth-first search in the residual graph from `start` to any node that `is_target`,
+    /// never entering `excluded` nodes. Only edges with zero reduced cost are followed, so
+    /// pushing flow around a cycle through the path doesn't change the total cost. Returns the
+    /// visited nodes in order.
+    fn find_residual_path(
+        &self,
+        start: flow_network::Id,
+        is_target: impl Fn(flow_network::Id) -> bool,
+        excluded: &BTreeSet<flow_network::Id>,
+        potentials: &BTreeMap<flow_network::Id, flow_network::Cost>,
+    ) -> Option<Vec<flow_network::Id>> {
+        let mut parents = BTreeMap::new();
+        let mut queue = VecDeque::from([start]);
+        parents.insert(start, start);
+
+        while let Some(node) = queue.pop_front() {
+            if is_target(node) {
+                let mut path = vec![node];
+                let mut current = node;
+                while current != start {
+                    current = parents[&current];
+                    path.push(current);
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            let forward = self
+                .network
+                .outgoing_edges(node)
+                .iter()
+                .filter(|&&edge| self.network.available_capacity(edge) > 0)
+                .filter(|&&edge| reduced_cost(&self.network, potentials, edge) == 0)
+                .map(|edge| edge.end);
+            let backward = self
+                .network
+                .incoming_edges(node)
+                .iter()
+                .filter(|&&edge| self.network.flow(edge) > 0)
+                .filter(|&&edge| reduced_cost(&self.network, potentials, edge) == 0)
+                .map(|edge| edge.start);
+
+            let mut next: Vec<flow_network::Id> = forward.chain(backward).collect();
+            next.sort();
+
+            for next in next {
+                if excluded.contains(&next) || parents.contains_key(&next) {
+                    continue;
+                }
+
+                parents.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+
+        None
+    }
+
+    /// Pushes one unit of flow along a residual edge.
+    fn push_unit(&mut self, (start, end): (flow_network::Id, flow_network::Id)) {
+        let forward = flow_network::edge(start, end);
+
+        if self.network.edges().contains(&forward) && self.network.available_capacity(forward) > 0 {
+            let flow = self.network.flow(forward);
+            self.network.set_flow(forward, flow + 1);
+        } else {
+            let backward = forward.opposite();
+            let flow = self.network.flow(backward);
+            self.network.set_flow(backward, flow - 1);
+        }
+    }
+}
+
+/// The cost of the edge relative to the potentials, see [`cycle_canceling::potentials`].
+fn reduced_cost(
+    network: &FlowNetwork,
+    potentials: &BTreeMap<flow_network::Id, flow_network::Cost>,
+    edge: impl Into<flow_network::Edge>,
+) -> flow_network::Cost {
+    let edge = edge.into();
+    let potential = |node| potentials.get(&node).copied().unwrap_or(0);
+
+    network.cost(edge) + potential(edge.start) - potential(edge.end)
+}
+
+fn submitter_played_games(
+    exchange: &Exchange,
+    played_games: &[PlayedGame],
+) -> BTreeMap<UserId, BTreeSet<String>> {
+    let mut map: BTreeMap<UserId, BTreeSet<String>> = BTreeMap::new();
+
+    // Exchanges like re-rate rounds let everyone review everything again.
+    if exchange.ignore_played {
+        return map;
+    }
+
+    for played_game in played_games {
+        map.entry(played_game.member)
+            .or_default()
+            .insert(normalize_entry_link(&played_game.link));
+    }
+
+    map
+}
+
+fn has_played(
+    played_games: &BTreeMap<UserId, BTreeSet<String>>,
+    member: UserId,
+    link: &str,
+) -> bool {
+    played_games
+        .get(&member)
+        .is_some_and(|games| games.contains(&normalize_entry_link(link)))
+}
+
+/// Every submitter gets an edge from the source, so a network without them has been built wrong.
+fn lacks_source_edges(network: &FlowNetwork, submission_count: usize) -> bool {
+    submission_count > 0 && network.outgoing_edges(network.source()).is_empty()
 }
 
+#[derive(Debug)]
 struct IndexAllocator {
     index: u16,
 }
@@ -151,39 +649,29 @@ impl IndexAllocator {
 
 #[cfg(test)]
 mod test {
-    use std::num::NonZero;
+    use std::{collections::BTreeMap, num::NonZero};
 
-    use bimap::BiHashMap;
-    use map_macro::hash_map;
-    use serenity::all::{ChannelId, GuildId, UserId};
+    use bimap::BiBTreeMap;
+    use map_macro::btree_map;
+    use serenity::all::UserId;
     use time::macros::datetime;
 
     use crate::{
-        jam_types::JamType,
         models::{
-            types::UtcDateTime, Exchange, ExchangeId, ExchangeState, PlayedGame, PlayedGameId,
+            test_exchange, types::UtcDateTime, Exchange, ExchangeId, PlayedGame, PlayedGameId,
             Submission, SubmissionId,
         },
-        solver::flow_network::{edge, FlowNetwork},
+        solver::{
+            dinic,
+            flow_network::{edge, Cost, Edge, FlowNetwork},
+        },
     };
 
-    use super::AssignmentNetwork;
+    use super::{lacks_source_edges, AssignmentNetwork, IndexAllocator, MAX_RECIPROCAL_PENALTY};
 
     #[test]
     fn empty() {
-        let exchange = Exchange {
-            id: ExchangeId(1),
-            guild: GuildId::new(2),
-            channel: ChannelId::new(3),
-            jam_type: JamType::Itch,
-            jam_link: "https://itch.io/jam/example-jam".to_string(),
-            slug: "Test".to_string(),
-            display_name: "Test".to_string(),
-            state: ExchangeState::AcceptingSubmissions,
-            submissions_start: UtcDateTime::assume_utc(datetime!(2024-01-01 12:00:00)),
-            submissions_end: UtcDateTime::assume_utc(datetime!(2024-01-01 13:00:00)),
-            games_per_member: NonZero::new(5).unwrap(),
-        };
+        let exchange = test_exchange();
         let submissions = vec![];
         let played_games = vec![];
 
@@ -197,25 +685,14 @@ mod test {
 
     #[test]
     fn one_submitter() {
-        let exchange = Exchange {
-            id: ExchangeId(1),
-            guild: GuildId::new(2),
-            channel: ChannelId::new(3),
-            jam_type: JamType::Itch,
-            jam_link: "https://itch.io/jam/example-jam".to_string(),
-            slug: "Test".to_string(),
-            display_name: "Test".to_string(),
-            state: ExchangeState::AcceptingSubmissions,
-            submissions_start: UtcDateTime::assume_utc(datetime!(2024-01-01 12:00:00)),
-            submissions_end: UtcDateTime::assume_utc(datetime!(2024-01-01 13:00:00)),
-            games_per_member: NonZero::new(5).unwrap(),
-        };
+        let exchange = test_exchange();
         let submissions = vec![Submission {
             id: SubmissionId(1),
             exchange_id: exchange.id,
             link: "https://itch.io/jam/example-jam/rate/123456".to_string(),
             submitter: UserId::new(1),
             submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+            edit_count: 0,
         }];
         let played_games = vec![];
 
@@ -246,17 +723,8 @@ mod test {
     #[test]
     fn multiple_submitters() {
         let exchange = Exchange {
-            id: ExchangeId(1),
-            guild: GuildId::new(2),
-            channel: ChannelId::new(3),
-            jam_type: JamType::Itch,
-            jam_link: "https://itch.io/jam/example-jam".to_string(),
-            slug: "Test".to_string(),
-            display_name: "Test".to_string(),
-            state: ExchangeState::AcceptingSubmissions,
-            submissions_start: UtcDateTime::assume_utc(datetime!(2024-01-01 12:00:00)),
-            submissions_end: UtcDateTime::assume_utc(datetime!(2024-01-01 13:00:00)),
             games_per_member: NonZero::new(3).unwrap(),
+            ..test_exchange()
         };
         let submissions = vec![
             Submission {
@@ -265,6 +733,7 @@ mod test {
                 link: "https://itch.io/jam/example-jam/rate/000001".to_string(),
                 submitter: UserId::new(1),
                 submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                edit_count: 0,
             },
             Submission {
                 id: SubmissionId(2),
@@ -272,6 +741,7 @@ mod test {
                 link: "https://itch.io/jam/example-jam/rate/000002".to_string(),
                 submitter: UserId::new(2),
                 submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                edit_count: 0,
             },
             Submission {
                 id: SubmissionId(3),
@@ -279,6 +749,7 @@ mod test {
                 link: "https://itch.io/jam/example-jam/rate/000003".to_string(),
                 submitter: UserId::new(3),
                 submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                edit_count: 0,
             },
             Submission {
                 id: SubmissionId(4),
@@ -286,6 +757,7 @@ mod test {
                 link: "https://itch.io/jam/example-jam/rate/000004".to_string(),
                 submitter: UserId::new(4),
                 submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                edit_count: 0,
             },
         ];
         let played_games = vec![
@@ -469,14 +941,251 @@ mod test {
         )));
     }
 
+    #[test]
+    fn submitter_played_all_entries() {
+        let exchange = Exchange {
+            games_per_member: NonZero::new(2).unwrap(),
+            ..test_exchange()
+        };
+        let submissions = (1..=3)
+            .map(|i| Submission {
+                id: SubmissionId(i),
+                exchange_id: exchange.id,
+                link: format!("https://itch.io/jam/example-jam/rate/00000{i}"),
+                submitter: UserId::new(i),
+                submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                edit_count: 0,
+            })
+            .collect::<Vec<_>>();
+        let played_games = vec![
+            PlayedGame {
+                id: PlayedGameId(12),
+                link: "https://itch.io/jam/example-jam/rate/000002".to_string(),
+                member: UserId::new(1),
+                is_manual: true,
+            },
+            PlayedGame {
+                id: PlayedGameId(13),
+                link: "https://itch.io/jam/example-jam/rate/000003".to_string(),
+                member: UserId::new(1),
+                is_manual: false,
+            },
+        ];
+
+        let mut network = AssignmentNetwork::build(&exchange, submissions, &played_games);
+
+        let fully_played_node = *network
+            .submitter_nodes
+            .get_by_left(&UserId::new(1))
+            .unwrap();
+        assert_eq!(network.network.outgoing_edges(fully_played_node).len(), 0);
+        // 3 source edges, 3 sink edges and 2 eligible edges for each of the other two submitters.
+        assert_eq!(network.network.edges().len(), 10);
+
+        dinic::solve(&mut network.network);
+
+        network.network.validate(None).unwrap();
+        let assignments = network.get_assignments();
+        assert!(assignments.get(&UserId::new(1)).unwrap().is_empty());
+        assert_eq!(assignments.get(&UserId::new(2)).unwrap().len(), 2);
+        assert_eq!(assignments.get(&UserId::new(3)).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn played_game_outside_of_exchange() {
+        let exchange = Exchange {
+            games_per_member: NonZero::new(2).unwrap(),
+            ..test_exchange()
+        };
+        let submissions = (1..=3)
+            .map(|i| Submission {
+                id: SubmissionId(i),
+                exchange_id: exchange.id,
+                link: format!("https://itch.io/jam/example-jam/rate/00000{i}"),
+                submitter: UserId::new(i),
+                submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                edit_count: 0,
+            })
+            .collect::<Vec<_>>();
+        let played_games = vec![PlayedGame {
+            id: PlayedGameId(1),
+            link: "https://itch.io/jam/another-jam/rate/000002".to_string(),
+            member: UserId::new(1),
+            is_manual: true,
+        }];
+
+        let network = AssignmentNetwork::build(&exchange, submissions, &played_games);
+
+        // 3 source edges, 3 sink edges and 2 eligible edges for each submitter.
+        assert_eq!(network.network.edges().len(), 12);
+        for user in 1..=3 {
+            let node = *network
+                .submitter_nodes
+                .get_by_left(&UserId::new(user))
+                .unwrap();
+            assert_eq!(network.network.outgoing_edges(node).len(), 2);
+        }
+    }
+
+    #[test]
+    fn played_link_with_different_casing_and_trailing_slash() {
+        let exchange = reciprocal_exchange(2);
+        let submissions = numbered_submissions(3);
+        let played_games = vec![
+            PlayedGame {
+                id: PlayedGameId(1),
+                link: "https://itch.io/jam/example-jam/rate/000002/".to_string(),
+                member: UserId::new(1),
+                is_manual: true,
+            },
+            PlayedGame {
+                id: PlayedGameId(2),
+                link: "https://Itch.io/jam/Example-Jam/rate/000003".to_string(),
+                member: UserId::new(1),
+                is_manual: true,
+            },
+        ];
+
+        let network = AssignmentNetwork::build(&exchange, submissions, &played_games);
+
+        let node = *network
+            .submitter_nodes
+            .get_by_left(&UserId::new(1))
+            .unwrap();
+        assert!(network.network.outgoing_edges(node).is_empty());
+    }
+
+    #[test]
+    fn ignoring_played_games() {
+        let exchange = Exchange {
+            games_per_member: NonZero::new(2).unwrap(),
+            ignore_played: true,
+            ..test_exchange()
+        };
+        let submissions = (1..=3)
+            .map(|i| Submission {
+                id: SubmissionId(i),
+                exchange_id: exchange.id,
+                link: format!("https://itch.io/jam/example-jam/rate/00000{i}"),
+                submitter: UserId::new(i),
+                submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                edit_count: 0,
+            })
+            .collect::<Vec<_>>();
+        // Everyone has already played every other entry.
+        let played_games = submissions
+            .iter()
+            .flat_map(|played| {
+                submissions
+                    .iter()
+                    .filter(move |member| member.submitter != played.submitter)
+                    .map(move |member| PlayedGame {
+                        id: PlayedGameId(played.id.0 * 10 + member.id.0),
+                        link: played.link.clone(),
+                        member: member.submitter,
+                        is_manual: false,
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        let mut network = AssignmentNetwork::build(&exchange, submissions, &played_games);
+        dinic::solve(&mut network.network);
+
+        network.network.validate(Some(6)).unwrap();
+        let assignments = network.get_assignments();
+        for user in 1..=3 {
+            let assignments = assignments.get(&UserId::new(user)).unwrap();
+            assert_eq!(assignments.len(), 2);
+            assert!(assignments.iter().all(|a| a.submitter != UserId::new(user)));
+        }
+    }
+
+    fn canonical_assignment_ids(
+        exchange: &Exchange,
+        submissions: Vec<Submission>,
+    ) -> Vec<(UserId, Vec<SubmissionId>)> {
+        let mut network = AssignmentNetwork::build(exchange, submissions, &[]);
+        dinic::solve(&mut network.network);
+        network.canonicalize_assignment();
+
+        let mut assignments: Vec<(UserId, Vec<SubmissionId>)> = network
+            .get_assignments()
+            .into_iter()
+            .map(|(user, submissions)| {
+                let mut ids: Vec<SubmissionId> = submissions.iter().map(|s| s.id).collect();
+                ids.sort();
+                (user, ids)
+            })
+            .collect();
+        assignments.sort();
+
+        assignments
+    }
+
+    #[test]
+    fn canonical_assignment() {
+        let exchange = Exchange {
+            games_per_member: NonZero::new(1).unwrap(),
+            ..test_exchange()
+        };
+        let submissions = (1..=3)
+            .map(|i| Submission {
+                id: SubmissionId(i),
+                exchange_id: exchange.id,
+                link: format!("https://itch.io/jam/example-jam/rate/00000{i}"),
+                submitter: UserId::new(i),
+                submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                edit_count: 0,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            canonical_assignment_ids(&exchange, submissions),
+            vec![
+                (UserId::new(1), vec![SubmissionId(2)]),
+                (UserId::new(2), vec![SubmissionId(3)]),
+                (UserId::new(3), vec![SubmissionId(1)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn canonical_assignment_is_reproducible() {
+        let exchange = Exchange {
+            games_per_member: NonZero::new(3).unwrap(),
+            ..test_exchange()
+        };
+        let submissions = (1..=9)
+            .map(|i| Submission {
+                id: SubmissionId(i),
+                exchange_id: exchange.id,
+                link: format!("https://itch.io/jam/example-jam/rate/00000{i}"),
+                submitter: UserId::new(i),
+                submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                edit_count: 0,
+            })
+            .collect::<Vec<_>>();
+
+        let first = canonical_assignment_ids(&exchange, submissions.clone());
+        assert_eq!(first.len(), 9);
+        assert!(first.iter().all(|(_, ids)| ids.len() == 3));
+
+        for _ in 0..10 {
+            assert_eq!(
+                canonical_assignment_ids(&exchange, submissions.clone()),
+                first
+            );
+        }
+    }
+
     #[test]
     fn getting_assignments() {
         let network = AssignmentNetwork {
             network: {
                 let source = 0;
                 let sink = 1;
-                let submitters = vec![2, 3, 4, 5];
-                let submissions = vec![6, 7, 8, 9];
+                let submitters = [2, 3, 4, 5];
+                let submissions = [6, 7, 8, 9];
 
                 let mut net = FlowNetwork::empty(source, sink);
 
@@ -499,13 +1208,14 @@ mod test {
 
                 net
             },
-            submissions: hash_map! {
+            submissions: btree_map! {
                 SubmissionId(1) => Submission {
                     id: SubmissionId(1),
                     exchange_id: ExchangeId(1),
                     link: "https://itch.io/example-jam/rate/000001".to_string(),
                     submitter: UserId::new(1),
                     submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                    edit_count: 0,
                 },
                 SubmissionId(2) => Submission {
                     id: SubmissionId(2),
@@ -513,6 +1223,7 @@ mod test {
                     link: "https://itch.io/example-jam/rate/000002".to_string(),
                     submitter: UserId::new(2),
                     submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                    edit_count: 0,
                 },
                 SubmissionId(3) => Submission {
                     id: SubmissionId(3),
@@ -520,6 +1231,7 @@ mod test {
                     link: "https://itch.io/example-jam/rate/000003".to_string(),
                     submitter: UserId::new(3),
                     submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                    edit_count: 0,
                 },
                 SubmissionId(4) => Submission {
                     id: SubmissionId(4),
@@ -527,10 +1239,11 @@ mod test {
                     link: "https://itch.io/example-jam/rate/000004".to_string(),
                     submitter: UserId::new(4),
                     submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                    edit_count: 0,
                 },
             },
             submitter_nodes: {
-                let mut map = BiHashMap::new();
+                let mut map = BiBTreeMap::new();
 
                 map.insert(UserId::new(1), 2);
                 map.insert(UserId::new(2), 3);
@@ -540,7 +1253,7 @@ mod test {
                 map
             },
             submission_nodes: {
-                let mut map = BiHashMap::new();
+                let mut map = BiBTreeMap::new();
 
                 map.insert(SubmissionId(1), 6);
                 map.insert(SubmissionId(2), 7);
@@ -549,6 +1262,11 @@ mod test {
 
                 map
             },
+            backfill_nodes: BiBTreeMap::new(),
+            allocator: IndexAllocator { index: 10 },
+            games_per_member: 5,
+            reciprocal_penalty: 0,
+            played_games: BTreeMap::new(),
         };
 
         let assignments = network.get_assignments();
@@ -562,6 +1280,7 @@ mod test {
                 link: "https://itch.io/example-jam/rate/000002".to_string(),
                 submitter: UserId::new(2),
                 submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                edit_count: 0,
             }));
             assert!(assignments.contains(&Submission {
                 id: SubmissionId(4),
@@ -569,6 +1288,7 @@ mod test {
                 link: "https://itch.io/example-jam/rate/000004".to_string(),
                 submitter: UserId::new(4),
                 submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                edit_count: 0,
             }));
         }
         {
@@ -580,6 +1300,7 @@ mod test {
                 link: "https://itch.io/example-jam/rate/000001".to_string(),
                 submitter: UserId::new(1),
                 submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                edit_count: 0,
             }));
             assert!(assignments.contains(&Submission {
                 id: SubmissionId(3),
@@ -587,6 +1308,7 @@ mod test {
                 link: "https://itch.io/example-jam/rate/000003".to_string(),
                 submitter: UserId::new(3),
                 submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                edit_count: 0,
             }));
         }
         {
@@ -598,6 +1320,7 @@ mod test {
                 link: "https://itch.io/example-jam/rate/000002".to_string(),
                 submitter: UserId::new(2),
                 submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                edit_count: 0,
             }));
         }
         {
@@ -605,4 +1328,457 @@ mod test {
             assert_eq!(assignments.len(), 0);
         }
     }
+
+    #[test]
+    fn dropping_self_assignments() {
+        let network = AssignmentNetwork {
+            network: {
+                let source = 0;
+                let sink = 1;
+                let submitters = [2, 3];
+                let submissions = [4, 5];
+
+                let mut net = FlowNetwork::empty(source, sink);
+
+                net.add_edge((source, submitters[0]), 5, 2);
+                net.add_edge((source, submitters[1]), 5, 1);
+
+                // Deliberately broken: user 1 reviews their own submission.
+                net.add_edge((submitters[0], submissions[0]), 1, 1);
+                net.add_edge((submitters[0], submissions[1]), 1, 1);
+                net.add_edge((submitters[1], submissions[0]), 1, 1);
+
+                net.add_edge((submissions[0], sink), 5, 2);
+                net.add_edge((submissions[1], sink), 5, 1);
+
+                net
+            },
+            submissions: btree_map! {
+                SubmissionId(1) => Submission {
+                    id: SubmissionId(1),
+                    exchange_id: ExchangeId(1),
+                    link: "https://itch.io/example-jam/rate/000001".to_string(),
+                    submitter: UserId::new(1),
+                    submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                    edit_count: 0,
+                },
+                SubmissionId(2) => Submission {
+                    id: SubmissionId(2),
+                    exchange_id: ExchangeId(1),
+                    link: "https://itch.io/example-jam/rate/000002".to_string(),
+                    submitter: UserId::new(2),
+                    submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                    edit_count: 0,
+                },
+            },
+            submitter_nodes: {
+                let mut map = BiBTreeMap::new();
+
+                map.insert(UserId::new(1), 2);
+                map.insert(UserId::new(2), 3);
+
+                map
+            },
+            submission_nodes: {
+                let mut map = BiBTreeMap::new();
+
+                map.insert(SubmissionId(1), 4);
+                map.insert(SubmissionId(2), 5);
+
+                map
+            },
+            backfill_nodes: BiBTreeMap::new(),
+            allocator: IndexAllocator { index: 6 },
+            games_per_member: 5,
+            reciprocal_penalty: 0,
+            played_games: BTreeMap::new(),
+        };
+
+        let assignments = network.get_assignments();
+
+        let first = assignments.get(&UserId::new(1)).unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].id, SubmissionId(2));
+
+        let second = assignments.get(&UserId::new(2)).unwrap();
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].id, SubmissionId(1));
+    }
+
+    fn reciprocal_exchange(games_per_member: u8) -> Exchange {
+        Exchange {
+            games_per_member: NonZero::new(games_per_member).unwrap(),
+            reciprocal_penalty: 1,
+            ..test_exchange()
+        }
+    }
+
+    fn numbered_submissions(count: u64) -> Vec<Submission> {
+        (1..=count)
+            .map(|i| Submission {
+                id: SubmissionId(i),
+                exchange_id: ExchangeId(1),
+                link: format!("https://itch.io/jam/example-jam/rate/00000{i}"),
+                submitter: UserId::new(i),
+                submitted_at: UtcDateTime::assume_utc(datetime!(2020-01-01 00:00:00)),
+                edit_count: 0,
+            })
+            .collect()
+    }
+
+    fn reciprocal_pairs(network: &AssignmentNetwork) -> Vec<(UserId, UserId)> {
+        let assignments = network.get_assignments();
+        let reviews = |reviewer: UserId, author: UserId| {
+            assignments
+                .get(&reviewer)
+                .is_some_and(|assigned| assigned.iter().any(|s| s.submitter == author))
+        };
+
+        let mut pairs = Vec::new();
+        for &first in assignments.keys() {
+            for &second in assignments.keys() {
+                if first < second && reviews(first, second) && reviews(second, first) {
+                    pairs.push((first, second));
+                }
+            }
+        }
+
+        pairs
+    }
+
+    #[test]
+    fn reciprocal_penalty_weight() {
+        for penalty in [1, 7, MAX_RECIPROCAL_PENALTY, u8::MAX] {
+            let exchange = Exchange {
+                reciprocal_penalty: penalty,
+                ..reciprocal_exchange(1)
+            };
+            let network = AssignmentNetwork::build(&exchange, numbered_submissions(2), &[]);
+
+            // One edge of the only pair carries the penalty, capped below the backfill cost
+            let costs = network
+                .network
+                .edges()
+                .iter()
+                .map(|&edge| network.network.cost(edge))
+                .filter(|&cost| cost != 0)
+                .collect::<Vec<_>>();
+            assert_eq!(costs, vec![i32::from(penalty.min(MAX_RECIPROCAL_PENALTY))]);
+        }
+    }
+
+    #[test]
+    fn reciprocal_assignment_avoided() {
+        let exchange = reciprocal_exchange(1);
+        let mut network = AssignmentNetwork::build(&exchange, numbered_submissions(4), &[]);
+        dinic::solve(&mut network.network);
+        network.minimize_cost();
+        let cost = network.network.total_cost();
+        network.canonicalize_assignment();
+
+        // Canonicalization doesn't undo the cost minimization
+        assert_eq!(network.network.total_cost(), cost);
+        network.network.validate(Some(4)).unwrap();
+        assert_eq!(reciprocal_pairs(&network), vec![]);
+        assert!(network
+            .get_assignments()
+            .values()
+            .all(|assigned| assigned.len() == 1));
+    }
+
+    fn played_game(id: u64, member: u64, entry: u64) -> PlayedGame {
+        PlayedGame {
+            id: PlayedGameId(id),
+            link: format!("https://itch.io/jam/example-jam/rate/00000{entry}"),
+            member: UserId::new(member),
+            is_manual: true,
+        }
+    }
+
+    fn total_flow(network: &AssignmentNetwork) -> i64 {
+        let source = network.network.source();
+
+        network
+            .network
+            .outgoing_edges(source)
+            .iter()
+            .map(|&edge| network.network.flow(edge) as i64)
+            .sum()
+    }
+
+    fn finish(mut network: AssignmentNetwork) -> BTreeMap<UserId, Vec<Submission>> {
+        network.minimize_cost();
+        network.canonicalize_assignment();
+        network.network.validate(None).unwrap();
+
+        network.get_assignments()
+    }
+
+    #[test]
+    fn incremental_solve_matches_scratch() {
+        let exchange = reciprocal_exchange(2);
+        let submissions = numbered_submissions(7);
+        let played_games = [played_game(1, 3, 5), played_game(2, 6, 1)];
+
+        let mut incremental =
+            AssignmentNetwork::build(&exchange, submissions[..3].to_vec(), &played_games);
+        dinic::solve(&mut incremental.network);
+        assert!(incremental.sync(&exchange, submissions[..5].to_vec(), &played_games, &[]));
+        dinic::solve(&mut incremental.network);
+        assert!(incremental.sync(&exchange, submissions.clone(), &played_games, &[]));
+        dinic::solve(&mut incremental.network);
+
+        let mut scratch = AssignmentNetwork::build(&exchange, submissions, &played_games);
+        dinic::solve(&mut scratch.network);
+
+        // The flows may differ before canonicalization, but not in total
+        assert_eq!(incremental.network.edges(), scratch.network.edges());
+        assert_eq!(total_flow(&incremental), total_flow(&scratch));
+        assert_eq!(finish(incremental), finish(scratch));
+    }
+
+    #[test]
+    fn sync_rebuilds_after_revoke() {
+        let exchange = reciprocal_exchange(1);
+        let submissions = numbered_submissions(5);
+
+        let mut incremental = AssignmentNetwork::build(&exchange, submissions.clone(), &[]);
+        dinic::solve(&mut incremental.network);
+
+        let remaining = submissions
+            .into_iter()
+            .filter(|submission| submission.id != SubmissionId(2))
+            .collect::<Vec<_>>();
+        assert!(!incremental.sync(&exchange, remaining.clone(), &[], &[]));
+        dinic::solve(&mut incremental.network);
+
+        let mut scratch = AssignmentNetwork::build(&exchange, remaining, &[]);
+        dinic::solve(&mut scratch.network);
+
+        assert_eq!(finish(incremental), finish(scratch));
+    }
+
+    fn costs(network: &AssignmentNetwork) -> Vec<(Edge, Cost)> {
+        network
+            .network
+            .edges()
+            .iter()
+            .map(|&edge| (edge, network.network.cost(edge)))
+            .collect()
+    }
+
+    #[test]
+    fn sync_updates_played_edges() {
+        let exchange = reciprocal_exchange(1);
+        let submissions = numbered_submissions(4);
+
+        let mut network = AssignmentNetwork::build(&exchange, submissions[..3].to_vec(), &[]);
+        dinic::solve(&mut network.network);
+        network.minimize_cost();
+
+        // Playing a new entry only affects the new edges
+        assert!(network.sync(&exchange, submissions.clone(), &[played_game(1, 1, 4)], &[]));
+        assert!(!network
+            .network
+            .edges()
+            .contains(&edge(network_node(&network, 1), entry_node(&network, 4))));
+
+        // Playing an entry already in the network removes the existing edge and its flow
+        let played_games = [played_game(1, 1, 4), played_game(2, 2, 3)];
+        assert!(network.sync(&exchange, submissions.clone(), &played_games, &[]));
+        assert!(!network
+            .network
+            .edges()
+            .contains(&edge(network_node(&network, 2), entry_node(&network, 3))));
+        network.network.validate(None).unwrap();
+
+        let scratch = AssignmentNetwork::build(&exchange, submissions.clone(), &played_games);
+        assert_eq!(costs(&network), costs(&scratch));
+
+        // Removing the played game adds the edge back
+        assert!(network.sync(&exchange, submissions.clone(), &played_games[..1], &[]));
+        let scratch = AssignmentNetwork::build(&exchange, submissions.clone(), &played_games[..1]);
+        assert_eq!(costs(&network), costs(&scratch));
+        network.network.validate(None).unwrap();
+
+        // Solving the updated network gives the same assignment as solving from scratch
+        assert!(network.sync(&exchange, submissions.clone(), &played_games, &[]));
+        let mut scratch = AssignmentNetwork::build(&exchange, submissions, &played_games);
+        dinic::solve(&mut network.network);
+        dinic::solve(&mut scratch.network);
+        assert_eq!(finish(network), finish(scratch));
+    }
+
+    fn network_node(network: &AssignmentNetwork, submitter: u64) -> u16 {
+        *network
+            .submitter_nodes
+            .get_by_left(&UserId::new(submitter))
+            .unwrap()
+    }
+
+    fn entry_node(network: &AssignmentNetwork, submission: u64) -> u16 {
+        *network
+            .submission_nodes
+            .get_by_left(&SubmissionId(submission))
+            .unwrap()
+    }
+
+    fn backfill_links(count: u64) -> Vec<String> {
+        (1..=count)
+            .map(|i| format!("https://itch.io/jam/previous-jam/rate/00000{i}"))
+            .collect()
+    }
+
+    #[test]
+    fn backfill_fills_shortfalls() {
+        // Three members can review only two entries each, but should get three
+        let exchange = Exchange {
+            reciprocal_penalty: 0,
+            ..reciprocal_exchange(3)
+        };
+        let mut network = AssignmentNetwork::build(&exchange, numbered_submissions(3), &[]);
+        network.add_backfill(&backfill_links(2));
+
+        dinic::solve(&mut network.network);
+        network.minimize_cost();
+        network.canonicalize_assignment();
+        network.network.validate(Some(9)).unwrap();
+
+        let assignments = network.get_assignments();
+        let backfill = network.get_backfill_assignments();
+        for reviewer in 1..=3 {
+            let reviewer = UserId::new(reviewer);
+            assert_eq!(assignments[&reviewer].len(), 2);
+            assert_eq!(backfill[&reviewer].len(), 1);
+        }
+    }
+
+    #[test]
+    fn backfill_unused_without_shortfall() {
+        let exchange = reciprocal_exchange(2);
+        let mut network = AssignmentNetwork::build(&exchange, numbered_submissions(3), &[]);
+        network.add_backfill(&backfill_links(2));
+
+        dinic::solve(&mut network.network);
+        network.minimize_cost();
+        network.canonicalize_assignment();
+        network.network.validate(Some(6)).unwrap();
+
+        assert!(network.get_backfill_assignments().is_empty());
+        assert!(network
+            .get_assignments()
+            .values()
+            .all(|assigned| assigned.len() == 2));
+    }
+
+    #[test]
+    fn backfill_skips_played_and_own_entries() {
+        let exchange = reciprocal_exchange(2);
+        let submissions = numbered_submissions(2);
+        let mut links = backfill_links(1);
+        // Already an entry of the exchange
+        links.push(submissions[0].link.clone());
+
+        let played_games = [PlayedGame {
+            id: PlayedGameId(1),
+            link: links[0].clone(),
+            member: UserId::new(1),
+            is_manual: true,
+        }];
+        let mut network = AssignmentNetwork::build(&exchange, submissions, &played_games);
+        network.add_backfill(&links);
+
+        dinic::solve(&mut network.network);
+        network.minimize_cost();
+
+        assert_eq!(
+            network.get_backfill_assignments(),
+            btree_map! { UserId::new(2) => vec![links[0].clone()] }
+        );
+    }
+
+    #[test]
+    fn source_edges_for_submissions() {
+        let network =
+            AssignmentNetwork::build(&reciprocal_exchange(1), numbered_submissions(3), &[]);
+        assert!(!lacks_source_edges(&network.network, 3));
+
+        let empty = AssignmentNetwork::build(&reciprocal_exchange(1), Vec::new(), &[]);
+        assert!(!lacks_source_edges(&empty.network, 0));
+
+        // Submissions are expected, but nothing is wired to the source
+        assert!(lacks_source_edges(&FlowNetwork::empty(0, 1), 2));
+    }
+
+    #[test]
+    fn reciprocal_assignment_allowed_by_default() {
+        let exchange = Exchange {
+            reciprocal_penalty: 0,
+            ..reciprocal_exchange(1)
+        };
+        let mut network = AssignmentNetwork::build(&exchange, numbered_submissions(4), &[]);
+        dinic::solve(&mut network.network);
+        network.minimize_cost();
+
+        network.canonicalize_assignment();
+
+        assert_eq!(reciprocal_pairs(&network).len(), 2);
+    }
+
+    #[test]
+    fn unavoidable_reciprocal_assignment_kept() {
+        let exchange = reciprocal_exchange(1);
+        let mut network = AssignmentNetwork::build(&exchange, numbered_submissions(2), &[]);
+        dinic::solve(&mut network.network);
+        network.minimize_cost();
+
+        network.canonicalize_assignment();
+
+        network.network.validate(Some(2)).unwrap();
+        assert_eq!(
+            reciprocal_pairs(&network),
+            vec![(UserId::new(1), UserId::new(2))]
+        );
+    }
+
+    fn solved_assignment_ids(
+        exchange: &Exchange,
+        submissions: Vec<Submission>,
+    ) -> Vec<(UserId, Vec<SubmissionId>)> {
+        let mut network = AssignmentNetwork::build(exchange, submissions, &[]);
+        dinic::solve(&mut network.network);
+
+        network
+            .get_assignments()
+            .into_iter()
+            .map(|(user, submissions)| (user, submissions.iter().map(|s| s.id).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn solved_assignment_is_reproducible_without_canonicalization() {
+        let exchange = Exchange {
+            reciprocal_penalty: 0,
+            ..reciprocal_exchange(3)
+        };
+
+        let first = solved_assignment_ids(&exchange, numbered_submissions(12));
+        assert_eq!(first.len(), 12);
+
+        // Reverse and scramble the input order to make sure it doesn't leak into the result
+        let mut reversed = numbered_submissions(12);
+        reversed.reverse();
+        // Stepping by 5, which is coprime to 12, visits every submission once in a fixed order
+        let submissions = numbered_submissions(12);
+        let scrambled = (0..12)
+            .map(|i| submissions[i * 5 % 12].clone())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            solved_assignment_ids(&exchange, numbered_submissions(12)),
+            first
+        );
+        assert_eq!(solved_assignment_ids(&exchange, reversed), first);
+        assert_eq!(solved_assignment_ids(&exchange, scrambled), first);
+    }
 }
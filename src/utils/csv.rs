@@ -0,0 +1,154 @@
+use std::borrow::Cow;
+
+use crate::models::{Assignment, DeliveryStatus, Submission};
+
+use super::formatting::format_utc;
+
+/// Escapes a CSV field according to RFC 4180.
+pub fn escape_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+pub fn submissions_csv(submissions: &[Submission]) -> String {
+    let mut csv = "submitter_id,link,submitted_at\n".to_string();
+
+    for submission in submissions {
+        csv += &format!(
+            "{},{},{}\n",
+            submission.submitter,
+            escape_field(&submission.link),
+            format_utc(submission.submitted_at),
+        );
+    }
+
+    csv
+}
+
+pub fn assignments_csv(assignments: &[Assignment]) -> String {
+    let mut csv = "reviewer_id,assigned_link,delivered\n".to_string();
+
+    for assignment in assignments {
+        let delivered = match assignment.delivered {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "yes",
+            DeliveryStatus::Failed => "no",
+        };
+
+        csv += &format!(
+            "{},{},{}\n",
+            assignment.reviewer,
+            escape_field(&assignment.link),
+            delivered,
+        );
+    }
+
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::all::UserId;
+    use time::macros::datetime;
+
+    use crate::models::{
+        types::UtcDateTime, Assignment, DeliveryStatus, ExchangeId, Submission, SubmissionId,
+    };
+
+    use super::{assignments_csv, escape_field, submissions_csv};
+
+    #[test]
+    fn plain_field() {
+        assert_eq!(
+            escape_field("https://itch.io/jam/example-jam/rate/000001"),
+            "https://itch.io/jam/example-jam/rate/000001"
+        );
+    }
+
+    #[test]
+    fn field_with_special_characters() {
+        assert_eq!(escape_field("foo,bar"), "\"foo,bar\"");
+        assert_eq!(escape_field("foo \"bar\""), "\"foo \"\"bar\"\"\"");
+        assert_eq!(escape_field("foo\nbar"), "\"foo\nbar\"");
+    }
+
+    #[test]
+    fn no_submissions() {
+        assert_eq!(submissions_csv(&[]), "submitter_id,link,submitted_at\n");
+    }
+
+    #[test]
+    fn multiple_submissions() {
+        let submissions = vec![
+            Submission {
+                id: SubmissionId(1),
+                exchange_id: ExchangeId(1),
+                link: "https://itch.io/jam/example-jam/rate/000001".to_string(),
+                submitter: UserId::new(7),
+                submitted_at: UtcDateTime::assume_utc(datetime!(2024-01-01 12:30:00)),
+                edit_count: 0,
+            },
+            Submission {
+                id: SubmissionId(2),
+                exchange_id: ExchangeId(1),
+                link: "https://itch.io/jam/example-jam/rate/000002,\"weird\"".to_string(),
+                submitter: UserId::new(8),
+                submitted_at: UtcDateTime::assume_utc(datetime!(2024-01-02 08:05:00)),
+                edit_count: 1,
+            },
+        ];
+
+        assert_eq!(
+            submissions_csv(&submissions),
+            "submitter_id,link,submitted_at\n\
+             7,https://itch.io/jam/example-jam/rate/000001,2024-01-01 12:30\n\
+             8,\"https://itch.io/jam/example-jam/rate/000002,\"\"weird\"\"\",2024-01-02 08:05\n"
+        );
+    }
+
+    #[test]
+    fn no_assignments() {
+        assert_eq!(
+            assignments_csv(&[]),
+            "reviewer_id,assigned_link,delivered\n"
+        );
+    }
+
+    #[test]
+    fn multiple_assignments() {
+        let assignments = vec![
+            Assignment {
+                exchange_id: ExchangeId(1),
+                reviewer: UserId::new(7),
+                submission_id: Some(SubmissionId(2)),
+                link: "https://itch.io/jam/example-jam/rate/000002".to_string(),
+                delivered: DeliveryStatus::Delivered,
+            },
+            Assignment {
+                exchange_id: ExchangeId(1),
+                reviewer: UserId::new(8),
+                submission_id: Some(SubmissionId(1)),
+                link: "https://itch.io/jam/example-jam/rate/000001,\"weird\"".to_string(),
+                delivered: DeliveryStatus::Failed,
+            },
+            Assignment {
+                exchange_id: ExchangeId(1),
+                reviewer: UserId::new(9),
+                submission_id: None,
+                link: "https://itch.io/jam/example-jam/rate/000003".to_string(),
+                delivered: DeliveryStatus::Pending,
+            },
+        ];
+
+        assert_eq!(
+            assignments_csv(&assignments),
+            "reviewer_id,assigned_link,delivered\n\
+             7,https://itch.io/jam/example-jam/rate/000002,yes\n\
+             8,\"https://itch.io/jam/example-jam/rate/000001,\"\"weird\"\"\",no\n\
+             9,https://itch.io/jam/example-jam/rate/000003,pending\n"
+        );
+    }
+}
@@ -3,6 +3,9 @@ use time::{format_description, OffsetDateTime};
 
 use super::{timestamp, TimestampStyle};
 
+/// Discord rejects messages longer than 2000 characters.
+pub const MAX_MESSAGE_LENGTH: usize = 2000;
+
 const DATETIME_FORMAT: &[format_description::FormatItem<'_>] =
     format_description!("[year]-[month]-[day] [hour]:[minute]");
 
@@ -16,3 +19,18 @@ pub fn format_utc(date_time: impl Into<OffsetDateTime>) -> String {
 pub fn format_local(date_time: impl Into<OffsetDateTime>) -> String {
     timestamp(date_time.into(), TimestampStyle::ShortDateTime)
 }
+
+/// Escapes the characters Discord would treat as markdown, so that untrusted text is shown as is.
+pub fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '*' | '_' | '~' | '`' | '|' | '>' | '<' | '#' | '-' | '[' | ']' | '(' | ')'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
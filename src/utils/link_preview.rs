@@ -0,0 +1,192 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use futures::{stream, StreamExt};
+use reqwest::Client;
+use tracing::debug;
+
+const TITLE_FETCH_TIMEOUT: Duration = Duration::from_secs(3);
+/// How long a failed fetch is remembered before the page is tried again.
+const FAILED_FETCH_TTL: Duration = Duration::from_secs(60);
+/// How many pages [`LinkPreviewer::prefetch`] fetches at the same time.
+const MAX_CONCURRENT_FETCHES: usize = 8;
+
+/// Fetches entry page titles to show next to the bare links in assignment DMs.
+///
+/// Titles are cached per link so that an entry assigned to many members is only fetched once.
+/// Failures are only cached for a short while, so a page that was down is tried again later.
+pub struct LinkPreviewer {
+    client: Client,
+    cache: Mutex<HashMap<String, CachedTitle>>,
+}
+
+struct CachedTitle {
+    title: Option<String>,
+    /// When the fetch may be retried, `None` if the page was fetched successfully.
+    retry_at: Option<Instant>,
+}
+
+impl CachedTitle {
+    fn is_fresh(&self, now: Instant) -> bool {
+        self.retry_at.map_or(true, |retry_at| now < retry_at)
+    }
+}
+
+impl LinkPreviewer {
+    pub fn new() -> reqwest::Result<Self> {
+        let client = Client::builder().timeout(TITLE_FETCH_TIMEOUT).build()?;
+
+        Ok(LinkPreviewer {
+            client,
+            cache: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Returns the page title, or `None` if the page could not be fetched or has no title.
+    pub async fn title(&self, link: &str) -> Option<String> {
+        if let Some(cached) = self.cache.lock().unwrap().get(link) {
+            if cached.is_fresh(Instant::now()) {
+                return cached.title.clone();
+            }
+        }
+
+        let cached = match self.fetch_title(link).await {
+            Ok(title) => CachedTitle {
+                title,
+                retry_at: None,
+            },
+            Err(err) => {
+                debug!("Could not fetch the title of {link}: {err}");
+                CachedTitle {
+                    title: None,
+                    retry_at: Some(Instant::now() + FAILED_FETCH_TTL),
+                }
+            }
+        };
+        let title = cached.title.clone();
+
+        self.cache.lock().unwrap().insert(link.to_string(), cached);
+
+        title
+    }
+
+    /// Fetches the titles of the links into the cache, a few pages at a time.
+    pub async fn prefetch(&self, links: impl IntoIterator<Item = &str>) {
+        stream::iter(links)
+            .for_each_concurrent(MAX_CONCURRENT_FETCHES, |link| async move {
+                self.title(link).await;
+            })
+            .await;
+    }
+
+    async fn fetch_title(&self, link: &str) -> reqwest::Result<Option<String>> {
+        let html = self
+            .client
+            .get(link)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        Ok(extract_title(&html))
+    }
+}
+
+/// Extracts the text of the first `<title>` element, with whitespace collapsed and the common
+/// HTML entities decoded.
+pub fn extract_title(html: &str) -> Option<String> {
+    // Tag names are case-insensitive; ASCII lowercasing keeps byte offsets intact.
+    let lowercase = html.to_ascii_lowercase();
+
+    let tag_start = lowercase.find("<title")?;
+    let content_start = tag_start + lowercase[tag_start..].find('>')? + 1;
+    let content_end = content_start + lowercase[content_start..].find("</title")?;
+
+    let title = html[content_start..content_end]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let title = decode_entities(&title);
+
+    if title.is_empty() {
+        None
+    } else {
+        Some(title)
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{extract_title, CachedTitle};
+
+    #[test]
+    fn failed_fetches_expire() {
+        let now = Instant::now();
+        let fetched = CachedTitle {
+            title: Some("Example Game".to_string()),
+            retry_at: None,
+        };
+        let failed = CachedTitle {
+            title: None,
+            retry_at: Some(now + Duration::from_secs(60)),
+        };
+
+        assert!(fetched.is_fresh(now + Duration::from_secs(3600)));
+        assert!(failed.is_fresh(now));
+        assert!(!failed.is_fresh(now + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn itch_entry_page() {
+        let html = r#"
+            <!DOCTYPE html>
+            <html lang="en">
+            <head>
+                <meta charset="UTF-8"/>
+                <TITLE>
+                    Space Cats &amp; Dogs by example-dev
+                </TITLE>
+                <meta property="og:title" content="Space Cats &amp; Dogs"/>
+            </head>
+            <body><h1>Space Cats &amp; Dogs</h1></body>
+            </html>
+        "#;
+
+        assert_eq!(
+            extract_title(html),
+            Some("Space Cats & Dogs by example-dev".to_string())
+        );
+    }
+
+    #[test]
+    fn title_with_attributes() {
+        let html = r#"<head><title data-rh="true">Ludum Dare 56 :: Tiny Creatures</title></head>"#;
+
+        assert_eq!(
+            extract_title(html),
+            Some("Ludum Dare 56 :: Tiny Creatures".to_string())
+        );
+    }
+
+    #[test]
+    fn missing_or_empty_title() {
+        assert_eq!(extract_title("<html><body>No head</body></html>"), None);
+        assert_eq!(extract_title("<title>   </title>"), None);
+        assert_eq!(extract_title("<title>Unclosed"), None);
+    }
+}
@@ -1,5 +1,8 @@
+pub mod announcements;
 pub mod assignment_network;
+pub mod csv;
 mod discord_timestamp;
 pub mod formatting;
+pub mod link_preview;
 
 pub use discord_timestamp::{timestamp, TimestampStyle};